@@ -0,0 +1,137 @@
+//! benchmarks of beacon verification for each scheme, using real beacons from drand's networks.
+//! Run with `cargo bench`; times reported are per beacon unless the name says otherwise.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use drand_client_rs::decode::verify_stream;
+use drand_client_rs::verify::{verify_beacon, Beacon, SchemeID, VerifyingKey};
+use std::hint::black_box;
+
+struct Fixture {
+    scheme_id: SchemeID,
+    public_key: &'static str,
+    round: u64,
+    randomness: &'static str,
+    signature: &'static str,
+    previous_signature: &'static str,
+}
+
+const FIXTURES: [Fixture; 4] = [
+    Fixture {
+        scheme_id: SchemeID::PedersenBlsChained,
+        public_key: "88a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb",
+        round: 397089,
+        randomness: "cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42",
+        signature: "88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a",
+        previous_signature: "a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce",
+    },
+    Fixture {
+        scheme_id: SchemeID::PedersenBlsUnchained,
+        public_key: "8d91ae0f4e3cd277cfc46aba26680232b0d5bb4444602cdb23442d62e17f43cdffb1104909e535430c10a6a1ce680a65",
+        round: 397092,
+        randomness: "7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796",
+        signature: "94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539",
+        previous_signature: "",
+    },
+    Fixture {
+        scheme_id: SchemeID::UnchainedOnG1,
+        public_key: "a0b862a7527fee3a731bcb59280ab6abd62d5c0b6ea03dc4ddf6612fdfc9d01f01c31542541771903475eb1ec6615f8d0df0b8b6dce385811d6dcf8cbefb8759e5e616a3dfd054c928940766d9a5b9db91e3b697e5d70a975181e007f87fca5e",
+        round: 3,
+        randomness: "a4eb0ed6c4132da066843c3bfdce732ce5013eda86e74c136ab8ccc387b798dd",
+        signature: "8176555f90d71aa49ceb37739683749491c2bab15a46094b255289ed25cf8f01cdfb1fe8bd9cd5a19eb09448a3e53186",
+        previous_signature: "",
+    },
+    Fixture {
+        scheme_id: SchemeID::UnchainedOnG1RFC9380,
+        public_key: "83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a",
+        round: 1000,
+        randomness: "fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd",
+        signature: "b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39",
+        previous_signature: "",
+    },
+];
+
+// the number of beacons verified per iteration of the stream benchmark
+const STREAM_LENGTH: usize = 16;
+
+impl Fixture {
+    fn public_key(&self) -> Vec<u8> {
+        hex::decode(self.public_key).unwrap()
+    }
+
+    fn beacon(&self) -> Beacon {
+        Beacon {
+            round_number: self.round,
+            randomness: hex::decode(self.randomness).unwrap(),
+            signature: hex::decode(self.signature).unwrap(),
+            previous_signature: hex::decode(self.previous_signature).unwrap(),
+        }
+    }
+}
+
+fn verify_beacon_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verify_beacon");
+    for fixture in &FIXTURES {
+        let public_key = fixture.public_key();
+        let beacon = fixture.beacon();
+        assert!(verify_beacon(&fixture.scheme_id, &public_key, &beacon).is_ok());
+
+        group.bench_function(fixture.scheme_id.name(), |b| {
+            b.iter(|| {
+                verify_beacon(
+                    &fixture.scheme_id,
+                    black_box(&public_key),
+                    black_box(&beacon),
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+fn verifying_key_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verifying_key");
+    for fixture in &FIXTURES {
+        let key = VerifyingKey::new(&fixture.scheme_id, &fixture.public_key()).unwrap();
+        let beacon = fixture.beacon();
+
+        group.bench_function(fixture.scheme_id.name(), |b| {
+            b.iter(|| key.verify(black_box(&beacon)))
+        });
+    }
+    group.finish();
+}
+
+fn verify_stream_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verify_stream");
+    group.throughput(Throughput::Elements(STREAM_LENGTH as u64));
+    for fixture in &FIXTURES {
+        let public_key = fixture.public_key();
+        let beacons = vec![fixture.beacon(); STREAM_LENGTH];
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(fixture.scheme_id.name()),
+            &beacons,
+            |b, beacons| {
+                b.iter(|| {
+                    verify_stream(
+                        beacons.iter().cloned().map(Ok),
+                        &fixture.scheme_id,
+                        &public_key,
+                    )
+                    .for_each(|result| {
+                        black_box(result.unwrap());
+                    })
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    verify_beacon_benchmark,
+    verifying_key_benchmark,
+    verify_stream_benchmark
+);
+criterion_main!(benches);
@@ -0,0 +1,102 @@
+//! # base58
+//!
+//! constructing beacons and public keys from base58, as some blockchain ecosystems encode
+//! BLS keys and signatures, rather than hex
+//!
+
+use crate::verify::{expected_randomness, Beacon, SchemeID, VerificationError, VerifyingKey};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum Base58Error {
+    #[error("public key is not valid base58")]
+    InvalidPublicKey,
+    #[error("signature is not valid base58")]
+    InvalidSignature,
+    #[error("previous signature is not valid base58")]
+    InvalidPreviousSignature,
+    #[error("invalid key")]
+    InvalidKey(#[from] VerificationError),
+}
+
+impl Beacon {
+    /// construct a beacon from a base58 encoded signature, and previous signature for chained
+    /// schemes. The randomness is derived from the signature.
+    pub fn from_base58(
+        round_number: u64,
+        signature: &str,
+        previous_signature: Option<&str>,
+    ) -> Result<Beacon, Base58Error> {
+        let signature = bs58::decode(signature)
+            .into_vec()
+            .map_err(|_| Base58Error::InvalidSignature)?;
+        let previous_signature = match previous_signature {
+            Some(previous_signature) => bs58::decode(previous_signature)
+                .into_vec()
+                .map_err(|_| Base58Error::InvalidPreviousSignature)?,
+            None => Vec::new(),
+        };
+
+        Ok(Beacon {
+            round_number,
+            randomness: expected_randomness(&signature),
+            signature,
+            previous_signature,
+        })
+    }
+}
+
+impl VerifyingKey {
+    /// decode and validate a base58 encoded public key for the scheme
+    pub fn from_base58(scheme_id: &SchemeID, public_key: &str) -> Result<Self, Base58Error> {
+        let public_key = bs58::decode(public_key)
+            .into_vec()
+            .map_err(|_| Base58Error::InvalidPublicKey)?;
+        Ok(VerifyingKey::new(scheme_id, &public_key)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::base58::Base58Error;
+    use crate::test_util::TestSigner;
+    use crate::verify::{Beacon, SchemeID, VerificationError, VerifyingKey};
+
+    #[test]
+    fn base58_key_and_signature_verify() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsChained);
+        let expected = signer.beacon(9);
+        let public_key = bs58::encode(signer.public_key()).into_string();
+        let signature = bs58::encode(&expected.signature).into_string();
+        let previous_signature = bs58::encode(&expected.previous_signature).into_string();
+
+        let key = VerifyingKey::from_base58(&SchemeID::PedersenBlsChained, &public_key).unwrap();
+        let beacon = Beacon::from_base58(9, &signature, Some(&previous_signature)).unwrap();
+
+        assert_eq!(beacon, expected);
+        assert_eq!(key.verify(&beacon), Ok(()));
+    }
+
+    #[test]
+    fn invalid_base58_fails() {
+        // `0`, `O`, `I` and `l` aren't in the base58 alphabet
+        assert_eq!(
+            Beacon::from_base58(1, "0OIl", None),
+            Err(Base58Error::InvalidSignature)
+        );
+        assert_eq!(
+            Beacon::from_base58(1, "abc", Some("0OIl")),
+            Err(Base58Error::InvalidPreviousSignature)
+        );
+        assert_eq!(
+            VerifyingKey::from_base58(&SchemeID::UnchainedOnG1, "0OIl").err(),
+            Some(Base58Error::InvalidPublicKey)
+        );
+        assert!(matches!(
+            VerifyingKey::from_base58(&SchemeID::UnchainedOnG1, "abc"),
+            Err(Base58Error::InvalidKey(
+                VerificationError::WrongLength { .. }
+            ))
+        ));
+    }
+}
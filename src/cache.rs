@@ -0,0 +1,306 @@
+//! # cache
+//!
+//! remembering beacons that have already been verified, so services verifying the same beacons
+//! over and over don't redo the pairings each time, and clients can serve beacons they already
+//! have without going to the network
+//!
+
+use crate::chain_info::ChainInfo;
+use crate::lock_unpoisoned;
+use crate::verify::{
+    verify_beacon, verify_beacon_from_genesis_round, Beacon, SchemeID, VerificationError,
+};
+use sha2::{Digest, Sha256};
+#[cfg(feature = "serde")]
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+/// a set of successful verifications, keyed by a hash of everything that went into them, that
+/// can be shared between threads and clients. Failed verifications are never cached, so a bad
+/// beacon can't stop a good one for the same round from verifying.
+#[derive(Debug)]
+pub struct VerificationCache {
+    capacity: usize,
+    verified: Mutex<HashSet<[u8; 32]>>,
+}
+
+/// create a cache holding up to `capacity` verifications; once full, it is emptied before the
+/// next verification is added. A capacity of 0 caches nothing.
+pub fn new_verification_cache(capacity: usize) -> VerificationCache {
+    VerificationCache {
+        capacity,
+        verified: Mutex::new(HashSet::new()),
+    }
+}
+
+impl VerificationCache {
+    /// verify `beacon` as [verify_beacon] does, skipping the verification if the same beacon has
+    /// already been verified with the same scheme and public key
+    pub fn verify(
+        &self,
+        scheme_id: &SchemeID,
+        public_key: &[u8],
+        beacon: &Beacon,
+    ) -> Result<(), VerificationError> {
+        self.verify_with(scheme_id, public_key, beacon, 1, verify_beacon)
+    }
+
+    /// verify `beacon` as [ChainInfo::verify] does, caching as `verify` does
+    pub fn verify_for_chain(
+        &self,
+        chain_info: &ChainInfo,
+        beacon: &Beacon,
+    ) -> Result<(), VerificationError> {
+        self.verify_with(
+            &chain_info.scheme_id,
+            &chain_info.public_key,
+            beacon,
+            chain_info.genesis_round,
+            |scheme_id, public_key, beacon| {
+                verify_beacon_from_genesis_round(
+                    scheme_id,
+                    public_key,
+                    beacon,
+                    chain_info.genesis_round,
+                )
+            },
+        )
+    }
+
+    fn verify_with(
+        &self,
+        scheme_id: &SchemeID,
+        public_key: &[u8],
+        beacon: &Beacon,
+        genesis_round: u64,
+        verify: impl FnOnce(&SchemeID, &[u8], &Beacon) -> Result<(), VerificationError>,
+    ) -> Result<(), VerificationError> {
+        let key = cache_key(scheme_id, public_key, beacon, genesis_round);
+        if lock_unpoisoned(&self.verified).contains(&key) {
+            return Ok(());
+        }
+
+        // verify without holding the lock, so other threads aren't held up by the pairing
+        verify(scheme_id, public_key, beacon)?;
+        if self.capacity == 0 {
+            return Ok(());
+        }
+
+        let mut verified = lock_unpoisoned(&self.verified);
+        if verified.len() >= self.capacity {
+            verified.clear();
+        }
+        verified.insert(key);
+        Ok(())
+    }
+
+    /// the number of verifications currently cached
+    pub fn len(&self) -> usize {
+        lock_unpoisoned(&self.verified).len()
+    }
+
+    /// whether no verifications are currently cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(feature = "serde")]
+/// the most recent verified beacons of a single chain, as fetched by a client
+#[derive(Debug)]
+pub struct BeaconCache {
+    capacity: usize,
+    beacons: Mutex<BTreeMap<u64, Beacon>>,
+}
+
+#[cfg(feature = "serde")]
+/// create a cache holding up to `capacity` beacons; once full, the lowest rounds are dropped
+/// to make room for new ones
+pub fn new_beacon_cache(capacity: usize) -> BeaconCache {
+    BeaconCache {
+        capacity,
+        beacons: Mutex::new(BTreeMap::new()),
+    }
+}
+
+#[cfg(feature = "serde")]
+impl BeaconCache {
+    /// the cached beacon for `round`, if there is one
+    pub fn get(&self, round: u64) -> Option<Beacon> {
+        lock_unpoisoned(&self.beacons).get(&round).cloned()
+    }
+
+    /// the cached beacon with the highest round, if there is one
+    pub fn latest(&self) -> Option<Beacon> {
+        lock_unpoisoned(&self.beacons)
+            .last_key_value()
+            .map(|(_, beacon)| beacon.clone())
+    }
+
+    /// the number of beacons currently cached
+    pub fn len(&self) -> usize {
+        lock_unpoisoned(&self.beacons).len()
+    }
+
+    /// whether no beacons are currently cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// only verified beacons may be inserted, which is why only the client does so
+    pub(crate) fn insert(&self, beacon: Beacon) {
+        let mut beacons = lock_unpoisoned(&self.beacons);
+        beacons.insert(beacon.round_number, beacon);
+        while beacons.len() > self.capacity {
+            beacons.pop_first();
+        }
+    }
+}
+
+/// every variable-length input is prefixed with its length, so different inputs can't be
+/// concatenated into the same key. The genesis round is included as it changes which rounds
+/// verify, so a beacon verified for one chain isn't accepted for a chain starting later.
+fn cache_key(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    beacon: &Beacon,
+    genesis_round: u64,
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for field in [
+        scheme_id.name().as_bytes(),
+        public_key,
+        &beacon.signature,
+        &beacon.previous_signature,
+        &beacon.randomness,
+    ] {
+        hasher.update((field.len() as u64).to_be_bytes());
+        hasher.update(field);
+    }
+    hasher.update(beacon.round_number.to_be_bytes());
+    hasher.update(genesis_round.to_be_bytes());
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "serde")]
+    use crate::cache::new_beacon_cache;
+    use crate::cache::new_verification_cache;
+    use crate::test_util::TestSigner;
+    use crate::verify::{verify_beacon, Beacon, SchemeID, VerificationError};
+    use std::cell::Cell;
+
+    #[test]
+    fn repeated_verification_skips_the_pairing() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let beacon = signer.beacon(7);
+        let cache = new_verification_cache(16);
+        let calls = Cell::new(0);
+        let counting_verify = |scheme_id: &SchemeID, public_key: &[u8], beacon: &Beacon| {
+            calls.set(calls.get() + 1);
+            verify_beacon(scheme_id, public_key, beacon)
+        };
+
+        let scheme_id = SchemeID::UnchainedOnG1RFC9380;
+        let public_key = signer.public_key();
+        assert_eq!(
+            cache.verify_with(&scheme_id, &public_key, &beacon, 1, counting_verify),
+            Ok(())
+        );
+        assert_eq!(
+            cache.verify_with(&scheme_id, &public_key, &beacon, 1, counting_verify),
+            Ok(())
+        );
+        assert_eq!(calls.get(), 1);
+
+        let next = signer.beacon(8);
+        assert_eq!(
+            cache.verify_with(&scheme_id, &public_key, &next, 1, counting_verify),
+            Ok(())
+        );
+        assert_eq!(calls.get(), 2);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn verifications_are_cached_per_genesis_round() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let mut chain_info = signer.chain_info(1692803367, 3);
+        chain_info.genesis_round = 0;
+        let beacon = signer.beacon(0);
+        let cache = new_verification_cache(16);
+
+        assert_eq!(cache.verify_for_chain(&chain_info, &beacon), Ok(()));
+        assert_eq!(
+            cache.verify(&chain_info.scheme_id, &chain_info.public_key, &beacon),
+            Err(VerificationError::InvalidRound)
+        );
+    }
+
+    #[test]
+    fn failed_verifications_are_not_cached() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let mut beacon = signer.beacon(7);
+        beacon.randomness[0] ^= 1;
+        let cache = new_verification_cache(16);
+
+        for _ in 0..2 {
+            assert_eq!(
+                cache.verify(
+                    &SchemeID::PedersenBlsUnchained,
+                    &signer.public_key(),
+                    &beacon
+                ),
+                Err(VerificationError::InvalidRandomness)
+            );
+        }
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn cache_is_emptied_when_full() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let cache = new_verification_cache(2);
+
+        for round in 1..=3 {
+            let result = cache.verify(
+                &SchemeID::PedersenBlsUnchained,
+                &signer.public_key(),
+                &signer.beacon(round),
+            );
+            assert_eq!(result, Ok(()));
+        }
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn cache_without_capacity_caches_nothing() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let cache = new_verification_cache(0);
+
+        let result = cache.verify(
+            &SchemeID::PedersenBlsUnchained,
+            &signer.public_key(),
+            &signer.beacon(1),
+        );
+        assert_eq!(result, Ok(()));
+        assert!(cache.is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn beacon_cache_drops_lowest_rounds_when_full() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let cache = new_beacon_cache(2);
+        for round in [3, 1, 2] {
+            cache.insert(signer.beacon(round));
+        }
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(1), None);
+        assert_eq!(cache.get(2), Some(signer.beacon(2)));
+        assert_eq!(cache.latest(), Some(signer.beacon(3)));
+    }
+}
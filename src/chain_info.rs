@@ -1,24 +1,350 @@
-use crate::verify::SchemeID;
-use serde::Deserialize;
+use crate::verify::{verify_beacon_from_genesis_round, Beacon, SchemeID, VerificationError};
+use crate::DrandClientError;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-#[derive(Deserialize, Debug, PartialEq, Clone)]
+/// 9999-12-31T23:59:59Z, the latest time `ChainInfo::time_of_round` returns. It's far beyond
+/// any real round, and a `SystemTime` can hold it on every platform.
+const LATEST_TIME_SECONDS: u64 = 253_402_300_799;
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ChainInfo {
-    #[serde(alias = "schemeID")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(alias = "schemeID", default = "legacy_scheme_id")
+    )]
     pub scheme_id: SchemeID,
-    #[serde(with = "hex")]
+    #[cfg_attr(feature = "serde", serde(with = "hex"))]
     pub public_key: Vec<u8>,
-    #[serde(with = "hex", alias = "hash")]
+    #[cfg_attr(feature = "serde", serde(with = "hex", alias = "hash"))]
     pub chain_hash: Vec<u8>,
-    #[serde(with = "hex", alias = "groupHash")]
+    #[cfg_attr(feature = "serde", serde(with = "hex", alias = "groupHash"))]
     pub group_hash: Vec<u8>,
     pub genesis_time: u64,
-    #[serde(alias = "period")]
+    #[cfg_attr(feature = "serde", serde(alias = "period"))]
     pub period_seconds: usize,
+    /// the round emitted at genesis. This is 1 for drand's networks, but experimental networks
+    /// sometimes start elsewhere, e.g. at round 0
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default = "default_genesis_round",
+            skip_serializing_if = "is_default_genesis_round"
+        )
+    )]
+    pub genesis_round: u64,
     pub metadata: ChainInfoMetadata,
 }
 
-#[derive(Deserialize, Debug, PartialEq, Clone)]
+impl ChainInfo {
+    /// the round that was (or will be) current at `epoch_seconds`. Times before genesis are
+    /// the round before the genesis round, i.e. round 0 for chains starting at round 1. Chain
+    /// info with a zero period, or a genesis round so high the round overflows, is rejected.
+    pub fn round_at(&self, epoch_seconds: u64) -> Result<u64, DrandClientError> {
+        if self.period_seconds == 0 {
+            return Err(DrandClientError::InvalidChainInfo);
+        }
+
+        if epoch_seconds < self.genesis_time {
+            return Ok(self.genesis_round.saturating_sub(1));
+        }
+
+        ((epoch_seconds - self.genesis_time) / self.period_seconds as u64)
+            .checked_add(self.genesis_round)
+            .ok_or(DrandClientError::InvalidChainInfo)
+    }
+
+    /// the time `round_number` is due to be emitted. Rounds before the genesis round are due
+    /// at genesis, and rounds too far in the future to represent are due at
+    /// `LATEST_TIME_SECONDS`.
+    pub fn time_of_round(&self, round_number: u64) -> SystemTime {
+        let epoch_seconds = round_number
+            .saturating_sub(self.genesis_round)
+            .saturating_mul(self.period_seconds as u64)
+            .saturating_add(self.genesis_time)
+            .min(LATEST_TIME_SECONDS);
+        UNIX_EPOCH + Duration::from_secs(epoch_seconds)
+    }
+
+    /// how long from `now`, in seconds since the epoch, until `round` is due to be emitted,
+    /// e.g. for a countdown to when a timelocked message can be decrypted. `None` once the
+    /// round is due.
+    pub fn duration_until_round(&self, round: u64, now: u64) -> Option<Duration> {
+        let due = self.time_of_round(round).duration_since(UNIX_EPOCH).ok()?;
+        due.checked_sub(Duration::from_secs(now))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// the chain hash derived from the chain's parameters the same way drand derives it, to check
+    /// the `chain_hash` a relay advertises matches the chain info it serves. drand hashes the
+    /// period as a `u32`, so longer periods fail rather than being truncated to the period of
+    /// another chain with the same hash.
+    pub fn compute_chain_hash(&self) -> Result<Vec<u8>, DrandClientError> {
+        let period =
+            u32::try_from(self.period_seconds).map_err(|_| DrandClientError::InvalidChainInfo)?;
+        let mut hasher = Sha256::new();
+        hasher.update(period.to_be_bytes());
+        hasher.update(self.genesis_time.to_be_bytes());
+        hasher.update(&self.public_key);
+        hasher.update(&self.group_hash);
+        // the default beacon ID is left out so the hashes of chains predating beacon IDs
+        // don't change
+        if self.metadata.beacon_id != "default" {
+            hasher.update(&self.metadata.beacon_id);
+        }
+        Ok(hasher.finalize().to_vec())
+    }
+
+    /// verify `beacon` against this chain's scheme and public key, rejecting rounds before the
+    /// genesis round
+    pub fn verify(&self, beacon: &Beacon) -> Result<(), VerificationError> {
+        verify_beacon_from_genesis_round(
+            &self.scheme_id,
+            &self.public_key,
+            beacon,
+            self.genesis_round,
+        )
+    }
+
+    /// whether `beacon` is the genesis round of this chain and links back to its genesis. In the
+    /// chained scheme, the previous signature of the first round is the genesis seed, which is
+    /// published as the group hash; beacons of unchained schemes have no previous signature.
+    /// Only the link is checked, not the beacon's signature.
+    pub fn links_to_genesis(&self, beacon: &Beacon) -> bool {
+        if beacon.round_number != self.genesis_round {
+            return false;
+        }
+        match self.scheme_id {
+            SchemeID::PedersenBlsChained => beacon.previous_signature == self.group_hash,
+            _ => beacon.previous_signature.is_empty(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ChainInfoMetadata {
-    #[serde(alias = "beaconID")]
+    #[cfg_attr(feature = "serde", serde(alias = "beaconID"))]
     pub beacon_id: String,
 }
+
+/// chain info from before drand supported multiple schemes doesn't include a scheme ID,
+/// and those chains were all `pedersen-bls-chained`
+#[cfg(feature = "serde")]
+pub(crate) fn legacy_scheme_id() -> SchemeID {
+    SchemeID::PedersenBlsChained
+}
+
+#[cfg(feature = "serde")]
+fn default_genesis_round() -> u64 {
+    1
+}
+
+#[cfg(feature = "serde")]
+fn is_default_genesis_round(genesis_round: &u64) -> bool {
+    *genesis_round == 1
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chain_info::ChainInfo;
+    use crate::test_util::TestSigner;
+    use crate::verify::{SchemeID, VerificationError};
+    use crate::DrandClientError;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn chain_info_deserializes() {
+        let json = r#"{"public_key":"83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a","period":3,"genesis_time":1692803367,"hash":"52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971","groupHash":"f477d5c89f21a17c863a7f937c6a6d15859414d2be09cd448d4279af331c5d3e","schemeID":"bls-unchained-g1-rfc9380","metadata":{"beaconID":"quicknet"}}"#;
+
+        let info: ChainInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(info.scheme_id, SchemeID::UnchainedOnG1RFC9380);
+        assert_eq!(info.period_seconds, 3);
+        assert_eq!(info.metadata.beacon_id, "quicknet");
+        assert_eq!(info.compute_chain_hash(), Ok(info.chain_hash));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn legacy_chain_info_without_scheme_defaults_to_chained() {
+        let json = r#"{"public_key":"868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31","period":30,"genesis_time":1595431050,"hash":"8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce","groupHash":"176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a","metadata":{"beaconID":"default"}}"#;
+
+        let info: ChainInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(info.scheme_id, SchemeID::PedersenBlsChained);
+        assert_eq!(info.genesis_time, 1595431050);
+        assert_eq!(info.compute_chain_hash(), Ok(info.chain_hash));
+    }
+
+    #[test]
+    fn periods_too_long_to_hash_fail() {
+        let mut info = TestSigner::new(SchemeID::PedersenBlsChained).chain_info(1000, 3);
+        let hash = info.compute_chain_hash().unwrap();
+        info.period_seconds = u32::MAX as usize;
+        assert!(info.compute_chain_hash().is_ok());
+        info.period_seconds = (1 << 32) + 3;
+        assert_eq!(
+            info.compute_chain_hash(),
+            Err(DrandClientError::InvalidChainInfo)
+        );
+        info.period_seconds = 3;
+        assert_eq!(info.compute_chain_hash(), Ok(hash));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn chain_info_with_invalid_hex_fails_to_parse() {
+        let info = TestSigner::new(SchemeID::PedersenBlsChained).chain_info(1000, 30);
+        for invalid in ["abc", "zz"] {
+            for field in ["public_key", "chain_hash", "group_hash"] {
+                let mut json = serde_json::to_value(&info).unwrap();
+                json[field] = invalid.into();
+
+                assert!(serde_json::from_value::<ChainInfo>(json).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn round_at_counts_from_genesis() {
+        let info = TestSigner::new(SchemeID::PedersenBlsChained).chain_info(1000, 30);
+
+        assert_eq!(info.round_at(1000), Ok(1));
+        assert_eq!(info.round_at(1029), Ok(1));
+        assert_eq!(info.round_at(1030), Ok(2));
+        assert_eq!(info.round_at(1000 + 30 * 100), Ok(101));
+    }
+
+    #[test]
+    fn round_at_before_genesis_is_zero() {
+        let info = TestSigner::new(SchemeID::PedersenBlsChained).chain_info(1000, 30);
+
+        assert_eq!(info.round_at(999), Ok(0));
+        assert_eq!(info.round_at(0), Ok(0));
+    }
+
+    #[test]
+    fn rounds_too_far_ahead_to_represent_saturate() {
+        let info = TestSigner::new(SchemeID::PedersenBlsChained).chain_info(1595431050, 30);
+        let latest = UNIX_EPOCH + Duration::from_secs(253_402_300_799);
+
+        assert_eq!(info.time_of_round(u64::MAX), latest);
+        assert_eq!(
+            info.duration_until_round(u64::MAX, 1595431050),
+            Some(Duration::from_secs(253_402_300_799 - 1595431050))
+        );
+        assert_eq!(info.duration_until_round(u64::MAX, u64::MAX), None);
+    }
+
+    #[test]
+    fn chain_starting_at_round_zero() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let info = ChainInfo {
+            genesis_round: 0,
+            ..signer.chain_info(1000, 30)
+        };
+
+        assert_eq!(info.round_at(999), Ok(0));
+        assert_eq!(info.round_at(1000), Ok(0));
+        assert_eq!(info.round_at(1030), Ok(1));
+        assert_eq!(
+            info.time_of_round(0),
+            UNIX_EPOCH + Duration::from_secs(1000)
+        );
+        assert_eq!(
+            info.time_of_round(2),
+            UNIX_EPOCH + Duration::from_secs(1060)
+        );
+
+        let genesis = signer.beacon(0);
+        assert!(info.links_to_genesis(&genesis));
+        assert!(!info.links_to_genesis(&signer.beacon(1)));
+        assert_eq!(info.verify(&genesis), Ok(()));
+        assert_eq!(info.verify(&signer.beacon(1)), Ok(()));
+        // chains starting at round 1 still reject round 0
+        assert_eq!(
+            signer.chain_info(1000, 30).verify(&genesis),
+            Err(VerificationError::InvalidRound)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn genesis_round_defaults_to_one() {
+        let info = TestSigner::new(SchemeID::PedersenBlsChained).chain_info(1000, 30);
+        let json = serde_json::to_value(&info).unwrap();
+        assert!(json.get("genesis_round").is_none());
+
+        let mut zero = json.clone();
+        zero["genesis_round"] = 0.into();
+        let parsed: ChainInfo = serde_json::from_value(zero).unwrap();
+        assert_eq!(parsed.genesis_round, 0);
+        assert_eq!(serde_json::from_value::<ChainInfo>(json).unwrap(), info);
+    }
+
+    #[test]
+    fn duration_until_round_counts_down_to_future_rounds() {
+        let info = TestSigner::new(SchemeID::PedersenBlsChained).chain_info(1000, 30);
+
+        // round 5 is due at 1000 + 4 * 30
+        assert_eq!(
+            info.duration_until_round(5, 1000),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            info.duration_until_round(5, 1119),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(info.duration_until_round(5, 1120), None);
+        assert_eq!(info.duration_until_round(2, 2000), None);
+    }
+
+    #[test]
+    fn round_at_with_zero_period_fails() {
+        let info = TestSigner::new(SchemeID::PedersenBlsChained).chain_info(1000, 0);
+
+        assert_eq!(info.round_at(5000), Err(DrandClientError::InvalidChainInfo));
+    }
+
+    #[test]
+    fn round_at_overflowing_the_genesis_round_fails() {
+        let mut info = TestSigner::new(SchemeID::PedersenBlsChained).chain_info(1000, 30);
+        info.genesis_round = u64::MAX;
+
+        assert_eq!(info.round_at(1029), Ok(u64::MAX));
+        assert_eq!(info.round_at(1030), Err(DrandClientError::InvalidChainInfo));
+    }
+
+    #[test]
+    fn first_round_links_to_genesis_seed() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsChained);
+        let info = signer.chain_info(1000, 30);
+        let first = signer.chained_beacon(1, &info.group_hash);
+
+        assert!(first.is_first_round());
+        assert!(info.links_to_genesis(&first));
+        assert!(!info.links_to_genesis(&signer.chained_beacon(1, &[0x6f; 32])));
+    }
+
+    #[test]
+    fn second_round_does_not_link_to_genesis() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsChained);
+        let info = signer.chain_info(1000, 30);
+        let second = signer.chained_beacon(2, &info.group_hash);
+
+        assert!(!second.is_first_round());
+        assert!(!info.links_to_genesis(&second));
+    }
+
+    #[test]
+    fn unchained_first_round_links_to_genesis() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let info = signer.chain_info(1000, 3);
+
+        assert!(info.links_to_genesis(&signer.beacon(1)));
+        assert!(!info.links_to_genesis(&signer.beacon(2)));
+    }
+}
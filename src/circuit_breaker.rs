@@ -0,0 +1,183 @@
+//! # circuit_breaker
+//!
+//! a transport decorator that stops sending requests to a transport that keeps failing, so
+//! time isn't wasted waiting on a relay that's down
+//!
+
+use crate::lock_unpoisoned;
+use crate::{AsyncTransport, Transport, TransportError};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// a transport that opens its circuit after `failure_threshold` consecutive failures, failing
+/// every request with `TransportError::CircuitOpen` without making it until `cooldown` has
+/// passed. A single request is then let through to probe the transport: the circuit closes
+/// again if it succeeds, or stays open for another `cooldown` if it fails.
+/// `NotFound` isn't a failure, as it usually means the round hasn't been emitted yet.
+/// The breaker covers every request made through it, so use one per relay.
+pub struct CircuitBreakerTransport<T> {
+    transport: T,
+    failure_threshold: u32,
+    cooldown: Duration,
+    state: Mutex<Breaker>,
+}
+
+#[derive(Default)]
+struct Breaker {
+    consecutive_failures: u32,
+    /// when the circuit was opened, or last probed while open
+    opened_at: Option<Instant>,
+}
+
+/// create a transport that stops making requests with `transport` for `cooldown` after
+/// `failure_threshold` consecutive failures. A threshold of 0 is treated as 1.
+pub fn new_circuit_breaker_transport<T>(
+    transport: T,
+    failure_threshold: u32,
+    cooldown: Duration,
+) -> CircuitBreakerTransport<T> {
+    CircuitBreakerTransport {
+        transport,
+        failure_threshold: failure_threshold.max(1),
+        cooldown,
+        state: Mutex::new(Breaker::default()),
+    }
+}
+
+fn is_failure<V>(result: &Result<V, TransportError>) -> bool {
+    !matches!(result, Ok(_) | Err(TransportError::NotFound))
+}
+
+impl<T> CircuitBreakerTransport<T> {
+    /// whether the circuit is currently open, i.e. requests are being short-circuited
+    pub fn is_open(&self) -> bool {
+        lock_unpoisoned(&self.state)
+            .opened_at
+            .is_some_and(|opened_at| opened_at.elapsed() < self.cooldown)
+    }
+
+    /// fail fast while the circuit is open. Once the cooldown has passed, the caller becomes
+    /// the probe, and the cooldown restarts so other requests wait for its result.
+    fn admit(&self) -> Result<(), TransportError> {
+        let mut breaker = lock_unpoisoned(&self.state);
+        match breaker.opened_at {
+            Some(opened_at) if opened_at.elapsed() < self.cooldown => {
+                Err(TransportError::CircuitOpen)
+            }
+            Some(_) => {
+                breaker.opened_at = Some(Instant::now());
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn record<V>(&self, result: Result<V, TransportError>) -> Result<V, TransportError> {
+        let mut breaker = lock_unpoisoned(&self.state);
+        if is_failure(&result) {
+            breaker.consecutive_failures = breaker.consecutive_failures.saturating_add(1);
+            if breaker.consecutive_failures >= self.failure_threshold {
+                breaker.opened_at = Some(Instant::now());
+            }
+        } else {
+            *breaker = Breaker::default();
+        }
+        result
+    }
+}
+
+impl<T: Transport> Transport for CircuitBreakerTransport<T> {
+    fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        self.admit()?;
+        self.record(Transport::fetch(&self.transport, url))
+    }
+
+    fn exists(&self, url: &str) -> Result<bool, TransportError> {
+        self.admit()?;
+        self.record(self.transport.exists(url))
+    }
+
+    fn fetch_by_deadline(&self, url: &str, deadline: Instant) -> Result<String, TransportError> {
+        self.admit()?;
+        self.record(self.transport.fetch_by_deadline(url, deadline))
+    }
+}
+
+impl<T: AsyncTransport> AsyncTransport for CircuitBreakerTransport<T> {
+    async fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        self.admit()?;
+        self.record(AsyncTransport::fetch(&self.transport, url).await)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::circuit_breaker::new_circuit_breaker_transport;
+    use crate::testing::InMemoryTransport;
+    use crate::{Transport, TransportError};
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct FlakyTransport {
+        healthy: AtomicBool,
+        requests: AtomicU32,
+    }
+
+    impl Transport for &FlakyTransport {
+        fn fetch(&self, _url: &str) -> Result<String, TransportError> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            if self.healthy.load(Ordering::SeqCst) {
+                Ok("{}".to_string())
+            } else {
+                Err(TransportError::Unexpected {
+                    status: Some(502),
+                    body: String::new(),
+                })
+            }
+        }
+    }
+
+    #[test]
+    fn breaker_opens_short_circuits_and_recovers() {
+        let flaky = FlakyTransport::default();
+        let breaker = new_circuit_breaker_transport(&flaky, 2, Duration::from_millis(100));
+
+        for _ in 0..2 {
+            assert!(matches!(
+                breaker.fetch("/info"),
+                Err(TransportError::Unexpected { .. })
+            ));
+        }
+        assert!(breaker.is_open());
+        assert_eq!(breaker.fetch("/info"), Err(TransportError::CircuitOpen));
+        assert_eq!(flaky.requests.load(Ordering::SeqCst), 2);
+
+        // a failed probe keeps the circuit open for another cooldown
+        sleep(Duration::from_millis(150));
+        assert!(matches!(
+            breaker.fetch("/info"),
+            Err(TransportError::Unexpected { .. })
+        ));
+        assert_eq!(breaker.fetch("/info"), Err(TransportError::CircuitOpen));
+        assert_eq!(flaky.requests.load(Ordering::SeqCst), 3);
+
+        flaky.healthy.store(true, Ordering::SeqCst);
+        sleep(Duration::from_millis(150));
+        assert_eq!(breaker.fetch("/info"), Ok("{}".to_string()));
+        assert!(!breaker.is_open());
+        assert_eq!(breaker.fetch("/info"), Ok("{}".to_string()));
+    }
+
+    #[test]
+    fn not_found_does_not_open_the_breaker() {
+        let breaker =
+            new_circuit_breaker_transport(InMemoryTransport::new(), 1, Duration::from_secs(60));
+
+        for _ in 0..3 {
+            assert_eq!(breaker.fetch("/public/1"), Err(TransportError::NotFound));
+        }
+        assert!(!breaker.is_open());
+    }
+}
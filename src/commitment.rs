@@ -0,0 +1,162 @@
+//! # commitment
+//!
+//! committing to the randomness of a set of already verified beacons with a Merkle root,
+//! so membership of a single round can later be proven without revealing every round
+//!
+
+use crate::verify::Beacon;
+use sha2::{Digest, Sha256};
+
+/// a Merkle tree over the randomness of a set of beacons.
+/// Each leaf is `sha256(0x00 || round || randomness)`, with the round as a big-endian `u64`,
+/// and the leaves are ordered by round. Each parent is `sha256(0x01 || left || right)`; a
+/// node without a sibling is carried up to the next level unchanged. The root of an empty
+/// tree is `sha256()` of nothing.
+pub struct RandomnessCommitment {
+    rounds: Vec<u64>,
+    // the levels of the tree, from the leaves up to the root
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+/// one step of a membership proof, from the leaf towards the root
+#[derive(Debug, PartialEq, Clone)]
+pub enum ProofStep {
+    /// the sibling is on the left, i.e. the parent is `hash(sibling, node)`
+    Left([u8; 32]),
+    /// the sibling is on the right, i.e. the parent is `hash(node, sibling)`
+    Right([u8; 32]),
+    /// the node has no sibling and is carried up unchanged
+    Carry,
+}
+
+impl RandomnessCommitment {
+    /// commit to the randomness of `beacons`, which should already have been verified.
+    /// If a round appears more than once, only its first beacon is committed to.
+    pub fn new(beacons: &[Beacon]) -> Self {
+        let mut sorted: Vec<&Beacon> = beacons.iter().collect();
+        sorted.sort_by_key(|beacon| beacon.round_number);
+        sorted.dedup_by_key(|beacon| beacon.round_number);
+
+        let mut levels = vec![sorted
+            .iter()
+            .map(|beacon| leaf_hash(beacon.round_number, &beacon.randomness))
+            .collect::<Vec<_>>()];
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+
+        RandomnessCommitment {
+            rounds: sorted.iter().map(|beacon| beacon.round_number).collect(),
+            levels,
+        }
+    }
+
+    /// the Merkle root committing to every round
+    pub fn root(&self) -> [u8; 32] {
+        match self.levels.last().unwrap().first() {
+            Some(root) => *root,
+            None => Sha256::digest(&[]).into(),
+        }
+    }
+
+    /// a proof that `round` is part of the commitment, or `None` if it isn't
+    pub fn prove(&self, round: u64) -> Option<Vec<ProofStep>> {
+        let mut index = self.rounds.binary_search(&round).ok()?;
+        let mut proof = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            let step = if index % 2 == 1 {
+                ProofStep::Left(level[index - 1])
+            } else if index + 1 < level.len() {
+                ProofStep::Right(level[index + 1])
+            } else {
+                ProofStep::Carry
+            };
+            proof.push(step);
+            index /= 2;
+        }
+        Some(proof)
+    }
+}
+
+/// check that `proof` shows `randomness` for `round` is part of the commitment with `root`
+pub fn verify_proof(root: &[u8; 32], round: u64, randomness: &[u8], proof: &[ProofStep]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(leaf_hash(round, randomness), |node, step| match step {
+            ProofStep::Left(sibling) => node_hash(sibling, &node),
+            ProofStep::Right(sibling) => node_hash(&node, sibling),
+            ProofStep::Carry => node,
+        });
+    computed == *root
+}
+
+fn leaf_hash(round: u64, randomness: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0x00]);
+    hasher.update(round.to_be_bytes());
+    hasher.update(randomness);
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::commitment::{verify_proof, RandomnessCommitment};
+    use crate::test_util::TestSigner;
+    use crate::verify::SchemeID;
+
+    #[test]
+    fn membership_proofs_verify_for_every_round() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let beacons: Vec<_> = (1..=5).rev().map(|round| signer.beacon(round)).collect();
+
+        let commitment = RandomnessCommitment::new(&beacons);
+        let root = commitment.root();
+
+        for beacon in &beacons {
+            let proof = commitment.prove(beacon.round_number).unwrap();
+            assert!(verify_proof(
+                &root,
+                beacon.round_number,
+                &beacon.randomness,
+                &proof
+            ));
+        }
+        assert_eq!(commitment.prove(6), None);
+    }
+
+    #[test]
+    fn membership_proofs_reject_wrong_randomness_or_round() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let beacons: Vec<_> = (1..=4).map(|round| signer.beacon(round)).collect();
+        let commitment = RandomnessCommitment::new(&beacons);
+        let root = commitment.root();
+        let proof = commitment.prove(2).unwrap();
+
+        assert!(!verify_proof(&root, 2, &beacons[2].randomness, &proof));
+        assert!(!verify_proof(&root, 3, &beacons[1].randomness, &proof));
+        assert!(!verify_proof(
+            &RandomnessCommitment::new(&beacons[..3]).root(),
+            2,
+            &beacons[1].randomness,
+            &proof
+        ));
+    }
+}
@@ -0,0 +1,161 @@
+//! # debug
+//!
+//! a transport decorator capturing the raw requests and responses of another transport, so
+//! users can attach exactly what a relay served to bug reports
+//!
+
+use crate::lock_unpoisoned;
+use crate::{AsyncTransport, Transport, TransportError};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// how many exchanges a `DebugTransport` keeps by default before dropping the oldest
+pub const DEFAULT_DEBUG_CAPACITY: usize = 100;
+
+/// a request made through a `DebugTransport` along with exactly what came back
+#[derive(Debug, PartialEq, Clone)]
+pub struct Exchange {
+    pub url: String,
+    pub response: Result<String, TransportError>,
+}
+
+/// a transport capturing the URL and the unmodified response of every fetch made with
+/// `transport` while it's enabled. `exists` checks aren't captured, as they have no body.
+/// Only the most recent exchanges are kept, so it can be left wrapped around a long-running
+/// watch loop without growing without bound.
+pub struct DebugTransport<T> {
+    transport: T,
+    enabled: AtomicBool,
+    capacity: usize,
+    exchanges: Mutex<VecDeque<Exchange>>,
+}
+
+/// create a transport capturing the last `DEFAULT_DEBUG_CAPACITY` requests made with
+/// `transport`, starting out enabled
+pub fn new_debug_transport<T>(transport: T) -> DebugTransport<T> {
+    DebugTransport {
+        transport,
+        enabled: AtomicBool::new(true),
+        capacity: DEFAULT_DEBUG_CAPACITY,
+        exchanges: Mutex::new(VecDeque::new()),
+    }
+}
+
+impl<T> DebugTransport<T> {
+    /// keep the last `capacity` exchanges rather than `DEFAULT_DEBUG_CAPACITY`. A capacity of
+    /// 0 is treated as 1.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// start or stop capturing requests. Requests already captured are kept.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Release);
+    }
+
+    /// the requests captured so far, oldest first
+    pub fn exchanges(&self) -> Vec<Exchange> {
+        lock_unpoisoned(&self.exchanges).iter().cloned().collect()
+    }
+
+    /// remove and return the requests captured so far, oldest first
+    pub fn take_exchanges(&self) -> Vec<Exchange> {
+        lock_unpoisoned(&self.exchanges).drain(..).collect()
+    }
+
+    fn capture(
+        &self,
+        url: &str,
+        response: Result<String, TransportError>,
+    ) -> Result<String, TransportError> {
+        if self.enabled.load(Ordering::Acquire) {
+            let mut exchanges = lock_unpoisoned(&self.exchanges);
+            if exchanges.len() == self.capacity {
+                exchanges.pop_front();
+            }
+            exchanges.push_back(Exchange {
+                url: url.to_string(),
+                response: response.clone(),
+            });
+        }
+        response
+    }
+}
+
+impl<T: Transport> Transport for DebugTransport<T> {
+    fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        self.capture(url, Transport::fetch(&self.transport, url))
+    }
+
+    fn exists(&self, url: &str) -> Result<bool, TransportError> {
+        self.transport.exists(url)
+    }
+
+    fn fetch_by_deadline(&self, url: &str, deadline: Instant) -> Result<String, TransportError> {
+        self.capture(url, self.transport.fetch_by_deadline(url, deadline))
+    }
+}
+
+impl<T: AsyncTransport> AsyncTransport for DebugTransport<T> {
+    async fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        self.capture(url, AsyncTransport::fetch(&self.transport, url).await)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::debug::{new_debug_transport, Exchange};
+    use crate::testing::InMemoryTransport;
+    use crate::{Transport, TransportError};
+
+    #[test]
+    fn captured_exchanges_match_what_was_served() {
+        let mut in_memory = InMemoryTransport::new();
+        in_memory.insert("https://example.org/public/1", r#"{"round":1}"#);
+        let transport = new_debug_transport(in_memory);
+
+        let _ = transport.fetch("https://example.org/public/1");
+        let _ = transport.fetch("https://example.org/public/2");
+        transport.set_enabled(false);
+        let _ = transport.fetch("https://example.org/public/3");
+
+        assert_eq!(
+            transport.take_exchanges(),
+            vec![
+                Exchange {
+                    url: "https://example.org/public/1".to_string(),
+                    response: Ok(r#"{"round":1}"#.to_string()),
+                },
+                Exchange {
+                    url: "https://example.org/public/2".to_string(),
+                    response: Err(TransportError::NotFound),
+                },
+            ]
+        );
+        assert!(transport.exchanges().is_empty());
+    }
+
+    #[test]
+    fn only_the_latest_exchanges_are_kept() {
+        let transport = new_debug_transport(InMemoryTransport::new()).with_capacity(2);
+        for round in 1..=5 {
+            let _ = transport.fetch(&format!("https://example.org/public/{round}"));
+        }
+
+        let urls: Vec<String> = transport
+            .exchanges()
+            .into_iter()
+            .map(|exchange| exchange.url)
+            .collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.org/public/4",
+                "https://example.org/public/5"
+            ]
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! # decode
+//!
+//! reading beacons from sources other than a relay, such as archives of newline-delimited
+//! JSON, and verifying them as they are read
+//!
+
+use crate::verify::{validate_signature, verify_beacon, Beacon, SchemeID, VerificationError};
+use std::io::{BufRead, Read};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum DecodeError {
+    #[error("failed to read input")]
+    ReadFailed,
+    #[error("invalid beacon JSON on line {line}")]
+    InvalidJson { line: usize },
+    #[error("beacon failed verification")]
+    FailedVerification(#[from] VerificationError),
+}
+
+impl Beacon {
+    /// parse beacons from newline-delimited JSON, one beacon per line, as written by
+    /// `DrandClient::archive_range`. Blank lines are skipped; line numbers in errors start at 1.
+    pub fn iter_jsonl(reader: impl BufRead) -> impl Iterator<Item = Result<Beacon, DecodeError>> {
+        reader
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !matches!(line, Ok(l) if l.trim().is_empty()))
+            .map(|(i, line)| {
+                let line = line.map_err(|_| DecodeError::ReadFailed)?;
+                serde_json::from_str(&line).map_err(|_| DecodeError::InvalidJson { line: i + 1 })
+            })
+    }
+
+    /// parse a beacon from JSON and check straight away that its signature is a valid point
+    /// for the scheme, rather than leaving garbage signatures to fail at verification.
+    /// The beacon is not verified against a public key; use `verify_beacon` for that.
+    pub fn deserialize_validated(json: &str, scheme_id: &SchemeID) -> Result<Beacon, DecodeError> {
+        let beacon: Beacon =
+            serde_json::from_str(json).map_err(|e| DecodeError::InvalidJson { line: e.line() })?;
+        validate_signature(scheme_id, &beacon.signature)?;
+        Ok(beacon)
+    }
+}
+
+/// read a single beacon as JSON from `reader`, e.g. stdin in a shell pipeline, and verify it
+/// for the given scheme and public key
+pub fn verify_reader(
+    mut reader: impl Read,
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+) -> Result<Beacon, DecodeError> {
+    let mut json = String::new();
+    reader
+        .read_to_string(&mut json)
+        .map_err(|_| DecodeError::ReadFailed)?;
+    let beacon: Beacon =
+        serde_json::from_str(&json).map_err(|e| DecodeError::InvalidJson { line: e.line() })?;
+    verify_beacon(scheme_id, public_key, &beacon)?;
+    Ok(beacon)
+}
+
+/// verify each beacon of a stream, e.g. from `Beacon::iter_jsonl`, passing through the ones
+/// that verify and turning the ones that don't into errors
+pub fn verify_stream<'a>(
+    beacons: impl Iterator<Item = Result<Beacon, DecodeError>> + 'a,
+    scheme_id: &'a SchemeID,
+    public_key: &'a [u8],
+) -> impl Iterator<Item = Result<Beacon, DecodeError>> + 'a {
+    beacons.map(move |beacon| {
+        let beacon = beacon?;
+        verify_beacon(scheme_id, public_key, &beacon)?;
+        Ok(beacon)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::decode::{verify_reader, verify_stream, DecodeError};
+    use crate::test_util::TestSigner;
+    use crate::verify::{Beacon, SchemeID, VerificationError};
+    use std::io::Cursor;
+
+    #[test]
+    fn jsonl_beacons_are_parsed_skipping_blank_lines() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let jsonl = format!(
+            "{}\n\n{}\n  \n{}\n",
+            serde_json::to_string(&signer.beacon(1)).unwrap(),
+            serde_json::to_string(&signer.beacon(2)).unwrap(),
+            serde_json::to_string(&signer.beacon(3)).unwrap(),
+        );
+
+        let beacons: Vec<_> = Beacon::iter_jsonl(Cursor::new(jsonl)).collect();
+
+        assert_eq!(
+            beacons,
+            vec![
+                Ok(signer.beacon(1)),
+                Ok(signer.beacon(2)),
+                Ok(signer.beacon(3))
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_jsonl_line_reports_line_number() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let jsonl = format!(
+            "{}\n\nnot json\n",
+            serde_json::to_string(&signer.beacon(1)).unwrap()
+        );
+
+        let beacons: Vec<_> = Beacon::iter_jsonl(Cursor::new(jsonl)).collect();
+
+        assert_eq!(beacons[1], Err(DecodeError::InvalidJson { line: 3 }));
+    }
+
+    #[test]
+    fn jsonl_dump_verifies() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let mut invalid = signer.beacon(3);
+        invalid.round_number = 4;
+        let jsonl = [signer.beacon(1), signer.beacon(2), invalid]
+            .iter()
+            .map(|b| serde_json::to_string(b).unwrap())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let public_key = signer.public_key();
+
+        let results: Vec<_> = verify_stream(
+            Beacon::iter_jsonl(Cursor::new(jsonl)),
+            &SchemeID::PedersenBlsUnchained,
+            &public_key,
+        )
+        .collect();
+
+        assert!(results[0].is_ok());
+        assert!(results[1].is_ok());
+        assert_eq!(
+            results[2],
+            Err(DecodeError::FailedVerification(
+                VerificationError::SignatureFailedVerification
+            ))
+        );
+    }
+
+    #[test]
+    fn validated_beacon_deserializes() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let json = serde_json::to_string(&signer.beacon(1)).unwrap();
+
+        assert_eq!(
+            Beacon::deserialize_validated(&json, &SchemeID::UnchainedOnG1RFC9380),
+            Ok(signer.beacon(1))
+        );
+    }
+
+    #[test]
+    fn validated_beacon_with_invalid_point_fails() {
+        // parseable hex of the right length, but not a compressed curve point
+        let json = format!(
+            r#"{{"round": 1, "randomness": "00", "signature": "{}"}}"#,
+            "11".repeat(96)
+        );
+
+        assert_eq!(
+            Beacon::deserialize_validated(&json, &SchemeID::PedersenBlsUnchained),
+            Err(DecodeError::FailedVerification(
+                VerificationError::InvalidSignaturePoint
+            ))
+        );
+        // the lenient deserializer still accepts it
+        assert!(serde_json::from_str::<Beacon>(&json).is_ok());
+    }
+
+    #[test]
+    fn validated_beacon_with_wrong_group_signature_fails() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let json = serde_json::to_string(&signer.beacon(1)).unwrap();
+
+        assert_eq!(
+            Beacon::deserialize_validated(&json, &SchemeID::PedersenBlsChained),
+            Err(DecodeError::FailedVerification(
+                VerificationError::WrongLength {
+                    field: "signature",
+                    scheme: "pedersen-bls-chained",
+                    expected: 96,
+                    actual: 48,
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn beacon_from_reader_verifies() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1);
+        let json = serde_json::to_string_pretty(&signer.beacon(7)).unwrap();
+
+        assert_eq!(
+            verify_reader(
+                Cursor::new(json),
+                &SchemeID::UnchainedOnG1,
+                &signer.public_key()
+            ),
+            Ok(signer.beacon(7))
+        );
+    }
+
+    #[test]
+    fn invalid_beacon_from_reader_fails() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1);
+        let mut beacon = signer.beacon(7);
+        beacon.round_number = 8;
+        let json = serde_json::to_string(&beacon).unwrap();
+
+        assert_eq!(
+            verify_reader(
+                Cursor::new(json),
+                &SchemeID::UnchainedOnG1,
+                &signer.public_key()
+            ),
+            Err(DecodeError::FailedVerification(
+                VerificationError::SignatureFailedVerification
+            ))
+        );
+        assert_eq!(
+            verify_reader(
+                Cursor::new("{\n  \"round\": 1,\n  oops"),
+                &SchemeID::UnchainedOnG1,
+                &signer.public_key()
+            ),
+            Err(DecodeError::InvalidJson { line: 3 })
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! # eip2537
+//!
+//! constructing beacons and public keys from the padded point encoding of Ethereum's EIP-2537
+//! BLS12-381 precompiles, for interop with EVM tooling
+//!
+
+use crate::verify::{expected_randomness, Beacon, SchemeID, VerificationError, VerifyingKey};
+use bls12_381::{G1Affine, G2Affine};
+
+/// the length of a field element in EIP-2537, a 48 byte big-endian element left-padded with
+/// 16 zero bytes
+const PADDED_ELEMENT_LENGTH: usize = 64;
+
+impl Beacon {
+    /// construct a beacon from an EIP-2537 encoded signature, and previous signature for
+    /// chained schemes, converting them to the compressed encoding drand uses. The randomness
+    /// is derived from the signature.
+    pub fn from_eip2537(
+        round_number: u64,
+        signature: &[u8],
+        previous_signature: Option<&[u8]>,
+    ) -> Result<Beacon, VerificationError> {
+        let signature =
+            compress_point(signature).ok_or(VerificationError::InvalidSignaturePoint)?;
+        let previous_signature = match previous_signature {
+            Some(previous_signature) => compress_point(previous_signature)
+                .ok_or(VerificationError::InvalidSignaturePoint)?,
+            None => Vec::new(),
+        };
+
+        Ok(Beacon {
+            round_number,
+            randomness: expected_randomness(&signature),
+            signature,
+            previous_signature,
+        })
+    }
+}
+
+impl VerifyingKey {
+    /// decode and validate an EIP-2537 encoded public key for the scheme
+    pub fn from_eip2537(
+        scheme_id: &SchemeID,
+        public_key: &[u8],
+    ) -> Result<Self, VerificationError> {
+        let public_key = compress_point(public_key).ok_or(VerificationError::InvalidPublicKey)?;
+        VerifyingKey::new(scheme_id, &public_key)
+    }
+}
+
+/// the compressed encoding of an EIP-2537 point: 128 bytes for g1, holding `x` and `y`, or
+/// 256 bytes for g2, holding the `c0` and `c1` components of `x` and then of `y`. `None` if
+/// the padding isn't zero or the point isn't in the subgroup.
+fn compress_point(bytes: &[u8]) -> Option<Vec<u8>> {
+    let elements = bytes
+        .chunks(PADDED_ELEMENT_LENGTH)
+        .map(strip_padding)
+        .collect::<Option<Vec<_>>>()?;
+
+    match *elements.as_slice() {
+        [x, y] if bytes.len() == 2 * PADDED_ELEMENT_LENGTH => {
+            let mut uncompressed = [0u8; 96];
+            uncompressed[..48].copy_from_slice(x);
+            uncompressed[48..].copy_from_slice(y);
+            Option::<G1Affine>::from(G1Affine::from_uncompressed(&uncompressed))
+                .map(|point| point.to_compressed().to_vec())
+        }
+        [x_c0, x_c1, y_c0, y_c1] if bytes.len() == 4 * PADDED_ELEMENT_LENGTH => {
+            // the uncompressed encoding puts the c1 component of each coordinate first
+            let mut uncompressed = [0u8; 192];
+            for (i, component) in [x_c1, x_c0, y_c1, y_c0].into_iter().enumerate() {
+                uncompressed[i * 48..(i + 1) * 48].copy_from_slice(component);
+            }
+            Option::<G2Affine>::from(G2Affine::from_uncompressed(&uncompressed))
+                .map(|point| point.to_compressed().to_vec())
+        }
+        _ => None,
+    }
+}
+
+/// the 48 byte field element of a padded one. The top three bits of an element below the
+/// modulus are never set, so elements setting them are rejected rather than read as the flags
+/// of the uncompressed encoding.
+fn strip_padding(padded: &[u8]) -> Option<&[u8]> {
+    if padded.len() != PADDED_ELEMENT_LENGTH {
+        return None;
+    }
+    let (padding, element) = padded.split_at(PADDED_ELEMENT_LENGTH - 48);
+    if padding.iter().any(|&byte| byte != 0) || element[0] & 0xe0 != 0 {
+        return None;
+    }
+    Some(element)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::TestSigner;
+    use crate::verify::{Beacon, SchemeID, VerificationError, VerifyingKey};
+    use bls12_381::{G1Affine, G2Affine};
+
+    fn pad(uncompressed: &[u8]) -> Vec<u8> {
+        uncompressed
+            .chunks(48)
+            .flat_map(|element| [0u8; 16].iter().chain(element))
+            .copied()
+            .collect()
+    }
+
+    fn g1_to_eip2537(compressed: &[u8]) -> Vec<u8> {
+        let point = G1Affine::from_compressed(compressed.try_into().unwrap()).unwrap();
+        pad(&point.to_uncompressed())
+    }
+
+    fn g2_to_eip2537(compressed: &[u8]) -> Vec<u8> {
+        let point = G2Affine::from_compressed(compressed.try_into().unwrap()).unwrap();
+        let uncompressed = point.to_uncompressed();
+        // swap each coordinate's components into EIP-2537's c0, c1 order
+        let swapped: Vec<u8> = [48..96, 0..48, 144..192, 96..144]
+            .into_iter()
+            .flat_map(|range| uncompressed[range].to_vec())
+            .collect();
+        pad(&swapped)
+    }
+
+    #[test]
+    fn eip2537_key_and_signature_verify() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsChained);
+        let expected = signer.beacon(9);
+        let key = VerifyingKey::from_eip2537(
+            &SchemeID::PedersenBlsChained,
+            &g1_to_eip2537(&signer.public_key()),
+        )
+        .unwrap();
+        let beacon = Beacon::from_eip2537(
+            9,
+            &g2_to_eip2537(&expected.signature),
+            Some(&g2_to_eip2537(&expected.previous_signature)),
+        )
+        .unwrap();
+
+        assert_eq!(beacon, expected);
+        assert_eq!(key.verify(&beacon), Ok(()));
+
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let expected = signer.beacon(9);
+        let key = VerifyingKey::from_eip2537(
+            &SchemeID::UnchainedOnG1RFC9380,
+            &g2_to_eip2537(&signer.public_key()),
+        )
+        .unwrap();
+        let beacon = Beacon::from_eip2537(9, &g1_to_eip2537(&expected.signature), None).unwrap();
+
+        assert_eq!(beacon, expected);
+        assert_eq!(key.verify(&beacon), Ok(()));
+    }
+
+    #[test]
+    fn malformed_eip2537_points_are_rejected() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let public_key = g1_to_eip2537(&signer.public_key());
+
+        let mut nonzero_padding = public_key.clone();
+        nonzero_padding[0] = 1;
+        let mut off_curve = public_key.clone();
+        off_curve[127] ^= 1;
+        for malformed in [
+            nonzero_padding,
+            off_curve,
+            public_key[..127].to_vec(),
+            signer.public_key(),
+            vec![0; 128],
+        ] {
+            assert_eq!(
+                VerifyingKey::from_eip2537(&SchemeID::PedersenBlsUnchained, &malformed),
+                Err(VerificationError::InvalidPublicKey)
+            );
+        }
+
+        // a valid key of the wrong group for the scheme
+        assert!(matches!(
+            VerifyingKey::from_eip2537(&SchemeID::UnchainedOnG1RFC9380, &public_key),
+            Err(VerificationError::WrongLength { .. })
+        ));
+        assert_eq!(
+            Beacon::from_eip2537(9, &signer.beacon(9).signature, None),
+            Err(VerificationError::InvalidSignaturePoint)
+        );
+    }
+}
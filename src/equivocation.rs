@@ -0,0 +1,101 @@
+//! # equivocation
+//!
+//! detecting relays that serve different beacons for the same round, e.g. when consuming
+//! beacons from several endpoints
+//!
+
+use crate::chain_info::ChainInfo;
+use crate::verify::Beacon;
+use crate::DrandClientError;
+use std::collections::HashMap;
+
+/// remembers the randomness of every beacon it has verified, and reports any later beacon
+/// that verifies but has different randomness for the same round.
+/// Beacons are verified before being compared, so an invalid beacon is reported as a failed
+/// verification rather than an equivocation.
+pub struct EquivocationDetector {
+    chain_info: ChainInfo,
+    seen: HashMap<u64, Vec<u8>>,
+}
+
+/// create a detector for beacons of the chain described by `chain_info`
+pub fn new_equivocation_detector(chain_info: ChainInfo) -> EquivocationDetector {
+    EquivocationDetector {
+        chain_info,
+        seen: HashMap::new(),
+    }
+}
+
+impl EquivocationDetector {
+    /// verify `beacon` and check it agrees with any beacon previously observed for its round
+    pub fn observe(&mut self, beacon: &Beacon) -> Result<(), DrandClientError> {
+        self.chain_info.verify(beacon)?;
+
+        match self.seen.get(&beacon.round_number) {
+            Some(randomness) if *randomness != beacon.randomness => {
+                Err(DrandClientError::Equivocation {
+                    round: beacon.round_number,
+                })
+            }
+            Some(_) => Ok(()),
+            None => {
+                self.seen
+                    .insert(beacon.round_number, beacon.randomness.clone());
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::equivocation::new_equivocation_detector;
+    use crate::test_util::TestSigner;
+    use crate::verify::{SchemeID, VerificationError};
+    use crate::DrandClientError;
+
+    #[test]
+    fn conflicting_valid_beacons_are_reported() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsChained);
+        let mut detector = new_equivocation_detector(signer.chain_info(1595431050, 30));
+        // chained beacons sign over the previous signature, so a relay can produce two
+        // beacons for the same round that both verify by chaining from different signatures
+        let beacon = signer.chained_beacon(2, &signer.sign(b"one"));
+        let conflicting = signer.chained_beacon(2, &signer.sign(b"other"));
+
+        assert_eq!(detector.observe(&beacon), Ok(()));
+        assert_eq!(detector.observe(&beacon), Ok(()));
+        assert_eq!(detector.observe(&signer.beacon(3)), Ok(()));
+        assert_eq!(
+            detector.observe(&conflicting),
+            Err(DrandClientError::Equivocation { round: 2 })
+        );
+    }
+
+    #[test]
+    fn beacons_verify_from_the_genesis_round() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let mut chain_info = signer.chain_info(1595431050, 30);
+        chain_info.genesis_round = 0;
+        let mut detector = new_equivocation_detector(chain_info);
+
+        assert_eq!(detector.observe(&signer.beacon(0)), Ok(()));
+    }
+
+    #[test]
+    fn invalid_beacons_are_not_remembered() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let mut detector = new_equivocation_detector(signer.chain_info(1595431050, 30));
+        let mut invalid = signer.beacon(2);
+        invalid.signature = signer.beacon(3).signature;
+        invalid.randomness = signer.beacon(3).randomness;
+
+        assert_eq!(
+            detector.observe(&invalid),
+            Err(DrandClientError::FailedVerification(
+                VerificationError::SignatureFailedVerification
+            ))
+        );
+        assert_eq!(detector.observe(&signer.beacon(2)), Ok(()));
+    }
+}
@@ -0,0 +1,494 @@
+//! # failover
+//!
+//! a transport that spreads requests across multiple drand relays, falling back to the next
+//! relay when one fails and optionally preferring whichever relay has been the fastest
+//!
+
+use crate::{AsyncTransport, Transport, TransportError};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// a transport that tries each of its endpoints in turn until one of them succeeds.
+/// The endpoints are prepended to the URL passed to `fetch`, so a client using this transport
+/// should be created with an empty `base_url`, e.g. `new_client(transport, "")`.
+/// It implements `AsyncTransport` as well when wrapping an `AsyncTransport`.
+pub struct FailoverTransport<T> {
+    transport: T,
+    latency_probing: Option<LatencyProbing>,
+    state: Mutex<Vec<Endpoint>>,
+    last_probe: Mutex<Option<Instant>>,
+}
+
+struct LatencyProbing {
+    warmup_requests: u32,
+    reprobe_interval: Duration,
+}
+
+#[derive(Clone)]
+struct Endpoint {
+    base_url: String,
+    samples: u32,
+    latency: Option<Duration>,
+    failed: bool,
+}
+
+/// create a transport that fails over between the given `endpoints` in order, using
+/// `transport` to perform the actual requests
+pub fn new_failover_transport<T>(transport: T, endpoints: &[&str]) -> FailoverTransport<T> {
+    FailoverTransport {
+        transport,
+        latency_probing: None,
+        state: Mutex::new(
+            endpoints
+                .iter()
+                .map(|base_url| Endpoint {
+                    base_url: base_url.to_string(),
+                    samples: 0,
+                    latency: None,
+                    failed: false,
+                })
+                .collect(),
+        ),
+        last_probe: Mutex::new(None),
+    }
+}
+
+impl<T> FailoverTransport<T> {
+    /// order requests by the observed latency of each endpoint rather than the order they were
+    /// provided in. Every endpoint is probed on each of the first `warmup_requests` requests,
+    /// and again each time `reprobe_interval` has elapsed since the last probe.
+    /// Latency is tracked as a moving average of the previous value and the newest sample.
+    pub fn prefer_fastest(mut self, warmup_requests: u32, reprobe_interval: Duration) -> Self {
+        self.latency_probing = Some(LatencyProbing {
+            warmup_requests,
+            reprobe_interval,
+        });
+        self
+    }
+
+    /// the base URLs of the endpoints in the order they will currently be tried
+    pub fn ordering(&self) -> Vec<String> {
+        self.state
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|e| e.base_url.clone())
+            .collect()
+    }
+
+    fn should_probe(&self, endpoints: &[Endpoint]) -> bool {
+        let Some(probing) = &self.latency_probing else {
+            return false;
+        };
+        if endpoints
+            .iter()
+            .any(|e| e.samples < probing.warmup_requests)
+        {
+            return true;
+        }
+        match *self.last_probe.lock().unwrap() {
+            None => true,
+            Some(last_probe) => last_probe.elapsed() >= probing.reprobe_interval,
+        }
+    }
+
+    fn record(&self, base_url: &str, latency: Option<Duration>) {
+        let mut endpoints = self.state.lock().unwrap();
+        if let Some(endpoint) = endpoints.iter_mut().find(|e| e.base_url == base_url) {
+            endpoint.samples += 1;
+            match latency {
+                Some(sample) => {
+                    endpoint.failed = false;
+                    endpoint.latency = Some(match endpoint.latency {
+                        Some(previous) => (previous + sample) / 2,
+                        None => sample,
+                    });
+                }
+                None => endpoint.failed = true,
+            }
+        }
+
+        if self.latency_probing.is_some() {
+            // failed endpoints go to the back, then the fastest first
+            endpoints.sort_by_key(|e| (e.failed, e.latency.unwrap_or(Duration::MAX)));
+        }
+    }
+
+    fn record_probe(&self) {
+        *self.last_probe.lock().unwrap() = Some(Instant::now());
+    }
+}
+
+impl<T: Transport> FailoverTransport<T> {
+    fn timed_fetch(
+        &self,
+        base_url: &str,
+        url: &str,
+        deadline: Option<Instant>,
+    ) -> (Result<String, TransportError>, Duration) {
+        let start = Instant::now();
+        let url = format!("{base_url}{url}");
+        let result = match deadline {
+            Some(deadline) => self.transport.fetch_by_deadline(&url, deadline),
+            None => Transport::fetch(&self.transport, &url),
+        };
+        (result, start.elapsed())
+    }
+
+    fn probe(
+        &self,
+        endpoints: &[Endpoint],
+        url: &str,
+        deadline: Option<Instant>,
+    ) -> Result<String, TransportError> {
+        let mut fastest: Option<(String, Duration)> = None;
+        let mut last_error = TransportError::Unexpected {
+            status: None,
+            body: String::new(),
+        };
+
+        for endpoint in endpoints {
+            if deadline_passed(deadline) {
+                last_error = TransportError::Timeout;
+                break;
+            }
+            let (result, latency) = self.timed_fetch(&endpoint.base_url, url, deadline);
+            match result {
+                Ok(body) => {
+                    self.record(&endpoint.base_url, Some(latency));
+                    if fastest.as_ref().is_none_or(|(_, l)| latency < *l) {
+                        fastest = Some((body, latency));
+                    }
+                }
+                Err(e) => {
+                    self.record(&endpoint.base_url, None);
+                    last_error = e;
+                }
+            }
+        }
+
+        self.record_probe();
+        fastest.map(|(body, _)| body).ok_or(last_error)
+    }
+
+    fn fetch_from_endpoints(
+        &self,
+        url: &str,
+        deadline: Option<Instant>,
+    ) -> Result<String, TransportError> {
+        let endpoints = self.state.lock().unwrap().clone();
+        if self.should_probe(&endpoints) {
+            return self.probe(&endpoints, url, deadline);
+        }
+
+        let mut last_error = TransportError::Unexpected {
+            status: None,
+            body: String::new(),
+        };
+        for endpoint in endpoints {
+            // endpoints that weren't tried aren't recorded as failed
+            if deadline_passed(deadline) {
+                return Err(TransportError::Timeout);
+            }
+            let (result, latency) = self.timed_fetch(&endpoint.base_url, url, deadline);
+            match result {
+                Ok(body) => {
+                    self.record(&endpoint.base_url, Some(latency));
+                    return Ok(body);
+                }
+                Err(e) => {
+                    self.record(&endpoint.base_url, None);
+                    last_error = e;
+                }
+            }
+        }
+        Err(last_error)
+    }
+}
+
+fn deadline_passed(deadline: Option<Instant>) -> bool {
+    deadline.is_some_and(|deadline| Instant::now() >= deadline)
+}
+
+impl<T: Transport> Transport for FailoverTransport<T> {
+    fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        self.fetch_from_endpoints(url, None)
+    }
+
+    /// no further endpoints are tried once `deadline` has passed
+    fn fetch_by_deadline(&self, url: &str, deadline: Instant) -> Result<String, TransportError> {
+        self.fetch_from_endpoints(url, Some(deadline))
+    }
+}
+
+impl<T: AsyncTransport> FailoverTransport<T> {
+    async fn timed_fetch_async(
+        &self,
+        base_url: &str,
+        url: &str,
+    ) -> (Result<String, TransportError>, Duration) {
+        let start = Instant::now();
+        let result = AsyncTransport::fetch(&self.transport, &format!("{base_url}{url}")).await;
+        (result, start.elapsed())
+    }
+
+    async fn probe_async(
+        &self,
+        endpoints: &[Endpoint],
+        url: &str,
+    ) -> Result<String, TransportError> {
+        let mut fastest: Option<(String, Duration)> = None;
+        let mut last_error = TransportError::Unexpected {
+            status: None,
+            body: String::new(),
+        };
+
+        for endpoint in endpoints {
+            let (result, latency) = self.timed_fetch_async(&endpoint.base_url, url).await;
+            match result {
+                Ok(body) => {
+                    self.record(&endpoint.base_url, Some(latency));
+                    if fastest.as_ref().is_none_or(|(_, l)| latency < *l) {
+                        fastest = Some((body, latency));
+                    }
+                }
+                Err(e) => {
+                    self.record(&endpoint.base_url, None);
+                    last_error = e;
+                }
+            }
+        }
+
+        self.record_probe();
+        fastest.map(|(body, _)| body).ok_or(last_error)
+    }
+}
+
+impl<T: AsyncTransport> AsyncTransport for FailoverTransport<T> {
+    async fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        let endpoints = self.state.lock().unwrap().clone();
+        if self.should_probe(&endpoints) {
+            return self.probe_async(&endpoints, url).await;
+        }
+
+        let mut last_error = TransportError::Unexpected {
+            status: None,
+            body: String::new(),
+        };
+        for endpoint in endpoints {
+            let (result, latency) = self.timed_fetch_async(&endpoint.base_url, url).await;
+            match result {
+                Ok(body) => {
+                    self.record(&endpoint.base_url, Some(latency));
+                    return Ok(body);
+                }
+                Err(e) => {
+                    self.record(&endpoint.base_url, None);
+                    last_error = e;
+                }
+            }
+        }
+        Err(last_error)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::failover::new_failover_transport;
+    use crate::retry::new_retry_transport;
+    use crate::test_util::block_on;
+    use crate::{AsyncTransport, Transport, TransportError};
+    use std::sync::{Arc, Mutex};
+    use std::thread::sleep;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn failover_tries_endpoints_in_order() {
+        let transport = MockTransport::new(&[("down", None), ("up", Some(0))]);
+        let failover = new_failover_transport(transport, &["down", "up"]);
+
+        assert_eq!(failover.fetch("/info").unwrap(), "up/info");
+        assert_eq!(failover.ordering(), vec!["down", "up"]);
+    }
+
+    #[test]
+    fn failover_all_endpoints_failing_errors() {
+        let transport = MockTransport::new(&[("down", None), ("also-down", None)]);
+        let failover = new_failover_transport(transport, &["down", "also-down"]);
+
+        assert!(failover.fetch("/info").is_err());
+    }
+
+    #[test]
+    fn fastest_endpoint_is_preferred_after_warmup() {
+        let transport =
+            MockTransport::new(&[("slow", Some(40)), ("medium", Some(20)), ("fast", Some(1))]);
+        let failover = new_failover_transport(transport, &["slow", "medium", "fast"])
+            .prefer_fastest(2, Duration::from_secs(3600));
+
+        // warm-up requests probe every endpoint
+        failover.fetch("/public/1").unwrap();
+        failover.fetch("/public/2").unwrap();
+        assert_eq!(failover.ordering(), vec!["fast", "medium", "slow"]);
+
+        // after warm-up only the fastest endpoint should be hit
+        failover.transport.requests.lock().unwrap().clear();
+        assert_eq!(failover.fetch("/public/3").unwrap(), "fast/public/3");
+        assert_eq!(
+            *failover.transport.requests.lock().unwrap(),
+            vec!["fast/public/3"]
+        );
+    }
+
+    #[test]
+    fn fastest_endpoint_is_reprobed_after_interval() {
+        let transport = MockTransport::new(&[("a", Some(1)), ("b", Some(1))]);
+        let failover = new_failover_transport(transport, &["a", "b"])
+            .prefer_fastest(1, Duration::from_millis(0));
+
+        failover.fetch("/public/1").unwrap();
+        failover.transport.requests.lock().unwrap().clear();
+        failover.fetch("/public/2").unwrap();
+
+        assert_eq!(failover.transport.requests.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn failover_stops_trying_endpoints_at_deadline() {
+        let transport = SlowFailingTransport {
+            delay: Duration::from_millis(100),
+            requests: Mutex::new(Vec::new()),
+        };
+        let failover = new_failover_transport(transport, &["a", "b", "c"]);
+
+        let deadline = Instant::now() + Duration::from_millis(150);
+        assert_eq!(
+            failover.fetch_by_deadline("/info", deadline),
+            Err(TransportError::Timeout)
+        );
+        assert_eq!(
+            *failover.transport.requests.lock().unwrap(),
+            vec!["a/info", "b/info"]
+        );
+    }
+
+    #[test]
+    fn async_failover_retries_next_endpoint_after_timeout() {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let transport = AsyncMockTransport {
+            endpoints: Mutex::new(vec![
+                ("timing-out".to_string(), u32::MAX, TransportError::Timeout),
+                (
+                    "flaky".to_string(),
+                    1,
+                    TransportError::Unexpected {
+                        status: None,
+                        body: String::new(),
+                    },
+                ),
+            ]),
+            requests: requests.clone(),
+        };
+        let failover =
+            new_failover_transport(new_retry_transport(transport, 1), &["timing-out", "flaky"]);
+
+        let body = block_on(AsyncTransport::fetch(&failover, "/info"));
+
+        assert_eq!(body.unwrap(), "flaky/info");
+        assert_eq!(
+            *requests.lock().unwrap(),
+            vec![
+                "timing-out/info",
+                "timing-out/info",
+                "flaky/info",
+                "flaky/info"
+            ]
+        );
+    }
+
+    struct AsyncMockTransport {
+        // the number of times each endpoint fails, and the error it fails with
+        endpoints: Mutex<Vec<(String, u32, TransportError)>>,
+        requests: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl AsyncTransport for AsyncMockTransport {
+        async fn fetch(&self, url: &str) -> Result<String, TransportError> {
+            self.requests.lock().unwrap().push(url.to_string());
+            let mut endpoints = self.endpoints.lock().unwrap();
+            let (_, failures, error) = endpoints
+                .iter_mut()
+                .find(|(endpoint, _, _)| url.starts_with(endpoint.as_str()))
+                .ok_or(TransportError::NotFound)?;
+
+            if *failures == 0 {
+                return Ok(url.to_string());
+            }
+            *failures -= 1;
+            Err(match error {
+                TransportError::Timeout => TransportError::Timeout,
+                _ => TransportError::Unexpected {
+                    status: None,
+                    body: String::new(),
+                },
+            })
+        }
+    }
+
+    /// fails every request after `delay`
+    struct SlowFailingTransport {
+        delay: Duration,
+        requests: Mutex<Vec<String>>,
+    }
+
+    impl Transport for SlowFailingTransport {
+        fn fetch(&self, url: &str) -> Result<String, TransportError> {
+            self.requests.lock().unwrap().push(url.to_string());
+            sleep(self.delay);
+            Err(TransportError::Unexpected {
+                status: None,
+                body: String::new(),
+            })
+        }
+    }
+
+    struct MockTransport {
+        // delay in millis per endpoint, or `None` if the endpoint is down
+        endpoints: Vec<(String, Option<u64>)>,
+        requests: Mutex<Vec<String>>,
+    }
+
+    impl MockTransport {
+        fn new(endpoints: &[(&str, Option<u64>)]) -> Self {
+            MockTransport {
+                endpoints: endpoints
+                    .iter()
+                    .map(|(url, delay)| (url.to_string(), *delay))
+                    .collect(),
+                requests: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl Transport for MockTransport {
+        fn fetch(&self, url: &str) -> Result<String, TransportError> {
+            self.requests.lock().unwrap().push(url.to_string());
+            let (_, delay) = self
+                .endpoints
+                .iter()
+                .find(|(endpoint, _)| url.starts_with(endpoint.as_str()))
+                .ok_or(TransportError::NotFound)?;
+
+            match delay {
+                None => Err(TransportError::Unexpected {
+                    status: None,
+                    body: String::new(),
+                }),
+                Some(millis) => {
+                    sleep(Duration::from_millis(*millis));
+                    Ok(url.to_string())
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//! # ffi
+//!
+//! a C ABI for verifying beacons, so drand beacons can be verified from C, C++ or any
+//! language that can call into a shared library, e.g. python via ctypes.
+//! Build the crate as a `cdylib` or `staticlib` with the `ffi` feature to use it.
+//!
+
+use crate::verify::{verify_beacon, Beacon, SchemeID};
+use std::panic::catch_unwind;
+use std::slice;
+
+/// returned when the beacon verified successfully
+pub const DRAND_OK: i32 = 0;
+/// returned when a pointer was null for a non-zero length, or the scheme ID wasn't UTF-8
+pub const DRAND_INVALID_ARGUMENT: i32 = -1;
+/// returned when the scheme ID isn't one of drand's schemes
+pub const DRAND_UNKNOWN_SCHEME: i32 = -2;
+/// returned when verification panicked; this is always a bug in the library
+pub const DRAND_INTERNAL_ERROR: i32 = -3;
+
+/// verify a beacon, returning [DRAND_OK] if it's valid, the positive
+/// [code](crate::verify::VerificationError::code) of the verification error if it's not, or one
+/// of the negative `DRAND_*` constants if the arguments couldn't be used.
+/// The scheme ID is its drand name, e.g. `bls-unchained-g1-rfc9380`, and isn't nul-terminated.
+/// Unchained beacons are passed with a zero `previous_signature_len`.
+///
+/// # Safety
+/// every pointer must either be valid for reads of its length in bytes, or have a length of
+/// zero, in which case it may be null
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn drand_verify_beacon(
+    scheme_id: *const u8,
+    scheme_id_len: usize,
+    public_key: *const u8,
+    public_key_len: usize,
+    round: u64,
+    signature: *const u8,
+    signature_len: usize,
+    previous_signature: *const u8,
+    previous_signature_len: usize,
+    randomness: *const u8,
+    randomness_len: usize,
+) -> i32 {
+    let args = (
+        bytes(scheme_id, scheme_id_len),
+        bytes(public_key, public_key_len),
+        bytes(signature, signature_len),
+        bytes(previous_signature, previous_signature_len),
+        bytes(randomness, randomness_len),
+    );
+    let (
+        Some(scheme_id),
+        Some(public_key),
+        Some(signature),
+        Some(previous_signature),
+        Some(randomness),
+    ) = args
+    else {
+        return DRAND_INVALID_ARGUMENT;
+    };
+
+    catch_unwind(|| {
+        let Ok(scheme_name) = std::str::from_utf8(scheme_id) else {
+            return DRAND_INVALID_ARGUMENT;
+        };
+        let Some(scheme_id) = SchemeID::from_name(scheme_name) else {
+            return DRAND_UNKNOWN_SCHEME;
+        };
+        let beacon = Beacon {
+            round_number: round,
+            randomness: randomness.to_vec(),
+            signature: signature.to_vec(),
+            previous_signature: previous_signature.to_vec(),
+        };
+        match verify_beacon(&scheme_id, public_key, &beacon) {
+            Ok(()) => DRAND_OK,
+            Err(e) => e.code() as i32,
+        }
+    })
+    .unwrap_or(DRAND_INTERNAL_ERROR)
+}
+
+/// # Safety
+/// `ptr` must be valid for reads of `len` bytes, unless `len` is zero
+unsafe fn bytes<'a>(ptr: *const u8, len: usize) -> Option<&'a [u8]> {
+    if len == 0 {
+        return Some(&[]);
+    }
+    if ptr.is_null() {
+        return None;
+    }
+    Some(slice::from_raw_parts(ptr, len))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ffi::{drand_verify_beacon, DRAND_INVALID_ARGUMENT, DRAND_OK, DRAND_UNKNOWN_SCHEME};
+    use crate::test_util::TestSigner;
+    use crate::verify::{Beacon, SchemeID, VerificationError};
+    use std::ptr;
+
+    fn verify(scheme_name: &str, public_key: &[u8], beacon: &Beacon) -> i32 {
+        unsafe {
+            drand_verify_beacon(
+                scheme_name.as_ptr(),
+                scheme_name.len(),
+                public_key.as_ptr(),
+                public_key.len(),
+                beacon.round_number,
+                beacon.signature.as_ptr(),
+                beacon.signature.len(),
+                beacon.previous_signature.as_ptr(),
+                beacon.previous_signature.len(),
+                beacon.randomness.as_ptr(),
+                beacon.randomness.len(),
+            )
+        }
+    }
+
+    #[test]
+    fn valid_beacons_verify() {
+        for scheme_id in [
+            SchemeID::PedersenBlsChained,
+            SchemeID::PedersenBlsUnchained,
+            SchemeID::UnchainedOnG1,
+            SchemeID::UnchainedOnG1RFC9380,
+        ] {
+            let signer = TestSigner::new(scheme_id.clone());
+
+            assert_eq!(
+                verify(scheme_id.name(), &signer.public_key(), &signer.beacon(3)),
+                DRAND_OK
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_beacons_return_the_error_code() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let mut beacon = signer.beacon(3);
+        beacon.round_number = 4;
+
+        assert_eq!(
+            verify("bls-unchained-g1-rfc9380", &signer.public_key(), &beacon),
+            VerificationError::SignatureFailedVerification.code() as i32
+        );
+        assert_eq!(
+            verify("bls-unchained-g1-rfc9380", &[], &beacon),
+            VerificationError::WrongLength {
+                field: "public_key",
+                scheme: "bls-unchained-g1-rfc9380",
+                expected: 96,
+                actual: 0,
+            }
+            .code() as i32
+        );
+    }
+
+    #[test]
+    fn unusable_arguments_are_rejected() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let public_key = signer.public_key();
+        let beacon = signer.beacon(3);
+
+        assert_eq!(
+            verify("pedersen-bls-unknown", &public_key, &beacon),
+            DRAND_UNKNOWN_SCHEME
+        );
+        assert_eq!(verify("", &public_key, &beacon), DRAND_UNKNOWN_SCHEME);
+
+        let scheme_name = "pedersen-bls-unchained";
+        let null_signature = unsafe {
+            drand_verify_beacon(
+                scheme_name.as_ptr(),
+                scheme_name.len(),
+                public_key.as_ptr(),
+                public_key.len(),
+                3,
+                ptr::null(),
+                96,
+                ptr::null(),
+                0,
+                beacon.randomness.as_ptr(),
+                beacon.randomness.len(),
+            )
+        };
+        assert_eq!(null_signature, DRAND_INVALID_ARGUMENT);
+
+        let invalid_utf8 = [0xff, 0xfe];
+        let invalid_scheme = unsafe {
+            drand_verify_beacon(
+                invalid_utf8.as_ptr(),
+                invalid_utf8.len(),
+                ptr::null(),
+                0,
+                3,
+                ptr::null(),
+                0,
+                ptr::null(),
+                0,
+                ptr::null(),
+                0,
+            )
+        };
+        assert_eq!(invalid_scheme, DRAND_INVALID_ARGUMENT);
+    }
+}
@@ -0,0 +1,54 @@
+//! # fn_transport
+//!
+//! a transport made from a closure, for routing fetches through an existing HTTP stack, a
+//! cache or a test stub without implementing `Transport`
+//!
+
+use crate::{Transport, TransportError};
+
+/// a transport fetching each URL by calling `fetch`
+pub struct FnTransport<F> {
+    fetch: F,
+}
+
+/// create a transport fetching each URL by calling `fetch` with it
+pub fn new_fn_transport<F>(fetch: F) -> FnTransport<F>
+where
+    F: Fn(&str) -> Result<String, TransportError> + Send + Sync,
+{
+    FnTransport { fetch }
+}
+
+impl<F> Transport for FnTransport<F>
+where
+    F: Fn(&str) -> Result<String, TransportError> + Send + Sync,
+{
+    fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        (self.fetch)(url)
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test {
+    use crate::fn_transport::new_fn_transport;
+    use crate::test_util::TestSigner;
+    use crate::verify::SchemeID;
+    use crate::{new_client, TransportError};
+
+    #[test]
+    fn client_can_be_built_from_a_closure() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let transport = new_fn_transport(|url: &str| match url {
+            "https://example.org/info" => {
+                Ok(serde_json::to_string(&signer.chain_info(1692803367, 3)).unwrap())
+            }
+            "https://example.org/public/7" => Ok(serde_json::to_string(&signer.beacon(7)).unwrap()),
+            _ => Err(TransportError::NotFound),
+        });
+
+        let client = new_client(transport, "https://example.org").unwrap();
+
+        assert_eq!(client.randomness(7), Ok(signer.beacon(7)));
+        assert!(client.randomness(8).is_err());
+    }
+}
@@ -0,0 +1,409 @@
+//! # group
+//!
+//! parsing drand's group file, the output of distributed key generation, so operators of a
+//! private network can verify its beacons without a relay serving the chain info
+//!
+
+use crate::chain_info::{legacy_scheme_id, ChainInfo, ChainInfoMetadata};
+use crate::verify::SchemeID;
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::path::Path;
+use thiserror::Error;
+
+/// the parts of a drand group file needed to verify the group's beacons
+#[derive(Debug, PartialEq, Clone)]
+pub struct GroupFile {
+    pub scheme_id: SchemeID,
+    pub public_key: Vec<u8>,
+    pub genesis_time: u64,
+    pub genesis_seed: Vec<u8>,
+    pub period_seconds: usize,
+    pub beacon_id: String,
+    pub threshold: usize,
+    /// the number of nodes in the group
+    pub node_count: usize,
+    /// the number of coefficients of the group's public polynomial, the first of which is the
+    /// group's public key
+    pub coefficient_count: usize,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum GroupFileError {
+    #[error("failed to read group file")]
+    ReadFailed,
+    #[error("invalid group file")]
+    InvalidFormat,
+    #[error("invalid period `{0}`")]
+    InvalidPeriod(String),
+    #[error("group file has no public key")]
+    MissingPublicKey,
+    #[error("`{field}` is not valid hex")]
+    InvalidHex { field: &'static str },
+    #[error("threshold {threshold} is invalid for a group of {nodes} nodes")]
+    InvalidThreshold { threshold: usize, nodes: usize },
+    #[error("expected {threshold} public key coefficients but found {coefficients}")]
+    CoefficientCountMismatch {
+        threshold: usize,
+        coefficients: usize,
+    },
+}
+
+/// the group file as drand writes it, e.g. `group.toml`
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RawGroupFile {
+    threshold: usize,
+    period: String,
+    genesis_time: u64,
+    genesis_seed: String,
+    #[serde(rename = "SchemeID", default = "legacy_scheme_id")]
+    scheme_id: SchemeID,
+    #[serde(rename = "ID", default)]
+    id: String,
+    #[serde(default)]
+    nodes: Vec<IgnoredAny>,
+    public_key: RawPublicKey,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RawPublicKey {
+    coefficients: Vec<String>,
+}
+
+impl GroupFile {
+    /// read a group file in drand's TOML format from `path`
+    pub fn from_toml(path: impl AsRef<Path>) -> Result<GroupFile, GroupFileError> {
+        let toml = std::fs::read_to_string(path).map_err(|_| GroupFileError::ReadFailed)?;
+        Self::from_toml_str(&toml)
+    }
+
+    /// read a group file in drand's JSON format from `path`
+    pub fn from_json(path: impl AsRef<Path>) -> Result<GroupFile, GroupFileError> {
+        let json = std::fs::read_to_string(path).map_err(|_| GroupFileError::ReadFailed)?;
+        Self::from_json_str(&json)
+    }
+
+    pub fn from_toml_str(toml: &str) -> Result<GroupFile, GroupFileError> {
+        let raw: RawGroupFile = toml::from_str(toml).map_err(|_| GroupFileError::InvalidFormat)?;
+        raw.try_into()
+    }
+
+    pub fn from_json_str(json: &str) -> Result<GroupFile, GroupFileError> {
+        let raw: RawGroupFile =
+            serde_json::from_str(json).map_err(|_| GroupFileError::InvalidFormat)?;
+        raw.try_into()
+    }
+
+    /// check the group's threshold is consistent with its size and public key, to catch
+    /// corrupted group files before trusting them: a threshold of `t` needs at least `t` nodes
+    /// and a public polynomial of degree `t - 1`, i.e. with `t` coefficients
+    pub fn validate(&self) -> Result<(), GroupFileError> {
+        if self.threshold == 0 || self.threshold > self.node_count {
+            return Err(GroupFileError::InvalidThreshold {
+                threshold: self.threshold,
+                nodes: self.node_count,
+            });
+        }
+        if self.coefficient_count != self.threshold {
+            return Err(GroupFileError::CoefficientCountMismatch {
+                threshold: self.threshold,
+                coefficients: self.coefficient_count,
+            });
+        }
+        Ok(())
+    }
+
+    /// the chain info of the group's chain, including the chain hash derived the same way
+    /// drand derives it. Fails if the period is too long for drand to hash.
+    pub fn chain_info(&self) -> Result<ChainInfo, GroupFileError> {
+        let mut chain_info = ChainInfo {
+            scheme_id: self.scheme_id.clone(),
+            public_key: self.public_key.clone(),
+            chain_hash: Vec::new(),
+            group_hash: self.genesis_seed.clone(),
+            genesis_time: self.genesis_time,
+            period_seconds: self.period_seconds,
+            genesis_round: 1,
+            metadata: ChainInfoMetadata {
+                beacon_id: self.beacon_id.clone(),
+            },
+        };
+        chain_info.chain_hash = chain_info
+            .compute_chain_hash()
+            .map_err(|_| GroupFileError::InvalidPeriod(format!("{}s", self.period_seconds)))?;
+        Ok(chain_info)
+    }
+}
+
+impl TryFrom<RawGroupFile> for GroupFile {
+    type Error = GroupFileError;
+
+    fn try_from(raw: RawGroupFile) -> Result<Self, Self::Error> {
+        let public_key = raw
+            .public_key
+            .coefficients
+            .first()
+            .ok_or(GroupFileError::MissingPublicKey)?;
+        Ok(GroupFile {
+            scheme_id: raw.scheme_id,
+            public_key: decode_hex("PublicKey", public_key)?,
+            genesis_time: raw.genesis_time,
+            genesis_seed: decode_hex("GenesisSeed", &raw.genesis_seed)?,
+            period_seconds: parse_period(&raw.period)?,
+            beacon_id: if raw.id.is_empty() {
+                "default".to_string()
+            } else {
+                raw.id
+            },
+            threshold: raw.threshold,
+            node_count: raw.nodes.len(),
+            coefficient_count: raw.public_key.coefficients.len(),
+        })
+    }
+}
+
+fn decode_hex(field: &'static str, value: &str) -> Result<Vec<u8>, GroupFileError> {
+    hex::decode(value).map_err(|_| GroupFileError::InvalidHex { field })
+}
+
+/// parse a whole number of seconds from a Go duration string, e.g. `30s` or `1m0s`, of at most
+/// `u32::MAX` seconds, as drand hashes the period as a `u32`
+fn parse_period(period: &str) -> Result<usize, GroupFileError> {
+    let invalid = || GroupFileError::InvalidPeriod(period.to_string());
+    let mut seconds: usize = 0;
+    let mut digits = String::new();
+    for c in period.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+        let value: usize = digits.parse().map_err(|_| invalid())?;
+        seconds = value
+            .checked_mul(unit)
+            .and_then(|value| seconds.checked_add(value))
+            .ok_or_else(invalid)?;
+        digits.clear();
+    }
+
+    if !digits.is_empty() || seconds == 0 || u32::try_from(seconds).is_err() {
+        return Err(invalid());
+    }
+    Ok(seconds)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::group::{parse_period, GroupFile, GroupFileError};
+    use crate::test_util::TestSigner;
+    use crate::verify::{verify_beacon, SchemeID};
+
+    const QUICKNET_GROUP: &str = r#"
+Threshold = 12
+Period = "3s"
+CatchupPeriod = "0s"
+GenesisTime = 1692803367
+TransitionTime = 1692803367
+GenesisSeed = "f477d5c89f21a17c863a7f937c6a6d15859414d2be09cd448d4279af331c5d3e"
+SchemeID = "bls-unchained-g1-rfc9380"
+ID = "quicknet"
+
+[[Nodes]]
+  Address = "pl1-rpc.testnet.drand.sh:443"
+  Key = "8d91ae0f4e3cd277cfc46aba26680232b0d5bb4444602cdb23442d62e17f43cdffb1104909e535430c10a6a1ce680a65"
+  TLS = true
+  Index = 0
+
+[PublicKey]
+  Coefficients = ["83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a"]
+"#;
+
+    #[test]
+    fn group_file_parses_to_chain_info() {
+        let group = GroupFile::from_toml_str(QUICKNET_GROUP).unwrap();
+        let info = group.chain_info().unwrap();
+
+        assert_eq!(info.scheme_id, SchemeID::UnchainedOnG1RFC9380);
+        assert_eq!(info.period_seconds, 3);
+        assert_eq!(info.genesis_time, 1692803367);
+        assert_eq!(info.metadata.beacon_id, "quicknet");
+        assert_eq!(
+            hex::encode(info.chain_hash),
+            "52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971"
+        );
+    }
+
+    #[test]
+    fn beacon_verifies_against_group_file_key() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let json = format!(
+            r#"{{
+                "Threshold": 2,
+                "Period": "1m0s",
+                "GenesisTime": 1595431050,
+                "GenesisSeed": "176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a",
+                "SchemeID": "pedersen-bls-unchained",
+                "PublicKey": {{ "Coefficients": ["{}", "00"] }}
+            }}"#,
+            hex::encode(signer.public_key())
+        );
+        let path = std::env::temp_dir().join("drand-client-rs-group.json");
+        std::fs::write(&path, json).unwrap();
+
+        let group = GroupFile::from_json(&path).unwrap();
+        let info = group.chain_info().unwrap();
+
+        assert_eq!(info.period_seconds, 60);
+        assert_eq!(info.metadata.beacon_id, "default");
+        assert!(verify_beacon(&info.scheme_id, &info.public_key, &signer.beacon(5)).is_ok());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn legacy_group_file_chain_hash_matches_mainnet() {
+        let toml = r#"
+Threshold = 12
+Period = "30s"
+GenesisTime = 1595431050
+GenesisSeed = "176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a"
+
+[PublicKey]
+  Coefficients = ["868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31"]
+"#;
+        let info = GroupFile::from_toml_str(toml)
+            .unwrap()
+            .chain_info()
+            .unwrap();
+
+        assert_eq!(info.scheme_id, SchemeID::PedersenBlsChained);
+        assert_eq!(
+            hex::encode(info.chain_hash),
+            "8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce"
+        );
+    }
+
+    #[test]
+    fn invalid_group_files_fail() {
+        assert_eq!(
+            GroupFile::from_toml_str("Threshold = 1"),
+            Err(GroupFileError::InvalidFormat)
+        );
+        assert_eq!(
+            GroupFile::from_toml("/does/not/exist.toml"),
+            Err(GroupFileError::ReadFailed)
+        );
+        assert_eq!(
+            parse_period("30"),
+            Err(GroupFileError::InvalidPeriod("30".into()))
+        );
+        assert_eq!(
+            parse_period("9999999999999999999h"),
+            Err(GroupFileError::InvalidPeriod("9999999999999999999h".into()))
+        );
+        assert_eq!(
+            parse_period("18446744073709551615s1s"),
+            Err(GroupFileError::InvalidPeriod(
+                "18446744073709551615s1s".into()
+            ))
+        );
+        assert_eq!(
+            parse_period("5000000000s"),
+            Err(GroupFileError::InvalidPeriod("5000000000s".into()))
+        );
+        assert_eq!(parse_period("4294967295s"), Ok(u32::MAX as usize));
+        assert_eq!(parse_period("1m30s"), Ok(90));
+    }
+
+    /// a group file with a `threshold`, `nodes` nodes and `coefficients` public key coefficients
+    fn group_with(threshold: usize, nodes: usize, coefficients: usize) -> String {
+        let mut toml = format!(
+            r#"
+Threshold = {threshold}
+Period = "3s"
+GenesisTime = 1692803367
+GenesisSeed = "f477d5c89f21a17c863a7f937c6a6d15859414d2be09cd448d4279af331c5d3e"
+SchemeID = "bls-unchained-g1-rfc9380"
+"#
+        );
+        for index in 0..nodes {
+            toml += &format!(
+                "\n[[Nodes]]\n  Address = \"node{index}.example.org:443\"\n  Index = {index}\n"
+            );
+        }
+        let coefficients = vec!["\"00\""; coefficients].join(", ");
+        toml += &format!("\n[PublicKey]\n  Coefficients = [{coefficients}]\n");
+        toml
+    }
+
+    #[test]
+    fn consistent_group_validates() {
+        let group = GroupFile::from_toml_str(&group_with(2, 3, 2)).unwrap();
+
+        assert_eq!(group.node_count, 3);
+        assert_eq!(group.coefficient_count, 2);
+        assert_eq!(group.validate(), Ok(()));
+        assert_eq!(
+            GroupFile::from_toml_str(&group_with(3, 3, 3))
+                .unwrap()
+                .validate(),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn inconsistent_threshold_fails_validation() {
+        assert_eq!(
+            GroupFile::from_toml_str(&group_with(4, 3, 4))
+                .unwrap()
+                .validate(),
+            Err(GroupFileError::InvalidThreshold {
+                threshold: 4,
+                nodes: 3
+            })
+        );
+        assert_eq!(
+            GroupFile::from_toml_str(&group_with(0, 3, 1))
+                .unwrap()
+                .validate(),
+            Err(GroupFileError::InvalidThreshold {
+                threshold: 0,
+                nodes: 3
+            })
+        );
+        assert_eq!(
+            GroupFile::from_toml_str(&group_with(2, 3, 3))
+                .unwrap()
+                .validate(),
+            Err(GroupFileError::CoefficientCountMismatch {
+                threshold: 2,
+                coefficients: 3
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_hex_in_group_file_fails() {
+        let public_key = "83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a";
+        let genesis_seed = "f477d5c89f21a17c863a7f937c6a6d15859414d2be09cd448d4279af331c5d3e";
+
+        for invalid in ["abc", "zz", "0x00"] {
+            assert_eq!(
+                GroupFile::from_toml_str(&QUICKNET_GROUP.replace(public_key, invalid)),
+                Err(GroupFileError::InvalidHex { field: "PublicKey" })
+            );
+            assert_eq!(
+                GroupFile::from_toml_str(&QUICKNET_GROUP.replace(genesis_seed, invalid)),
+                Err(GroupFileError::InvalidHex {
+                    field: "GenesisSeed"
+                })
+            );
+        }
+    }
+}
@@ -4,9 +4,14 @@
 //!
 
 use crate::{Transport, TransportError};
-use reqwest::blocking::Client;
+use reqwest::blocking::{Client, RequestBuilder, Response};
 
-use reqwest::StatusCode;
+use reqwest::{Certificate, Proxy, StatusCode};
+use std::io::Read;
+use std::time::{Duration, Instant};
+
+/// the most bytes of an error response's body kept in `TransportError::Unexpected`
+const MAX_ERROR_BODY_LEN: u64 = 4096;
 
 pub struct HttpTransport {
     pub client: Client,
@@ -14,18 +19,74 @@ pub struct HttpTransport {
 
 impl Transport for HttpTransport {
     fn fetch(&self, url: &str) -> Result<String, TransportError> {
-        let res = self
-            .client
-            .get(url)
-            .send()
-            .map_err(|_| TransportError::Unexpected)?;
+        fetch_request(self.client.get(url))
+    }
+
+    /// bounds the request with a timeout of the time left until `deadline`
+    fn fetch_by_deadline(&self, url: &str, deadline: Instant) -> Result<String, TransportError> {
+        let timeout = deadline
+            .checked_duration_since(Instant::now())
+            .filter(|timeout| !timeout.is_zero())
+            .ok_or(TransportError::Timeout)?;
+        fetch_request(self.client.get(url).timeout(timeout))
+    }
+
+    /// checks with a `HEAD` request, so the body isn't downloaded
+    fn exists(&self, url: &str) -> Result<bool, TransportError> {
+        let res = self.client.head(url).send().map_err(send_error)?;
 
         match res.status() {
-            StatusCode::OK => res.text().map_err(|_| TransportError::Unexpected),
+            StatusCode::OK => Ok(true),
+            StatusCode::NOT_FOUND => Ok(false),
+            status => Err(TransportError::Unexpected {
+                status: Some(status.as_u16()),
+                body: String::new(),
+            }),
+        }
+    }
+}
+
+fn fetch_request(request: RequestBuilder) -> Result<String, TransportError> {
+    let res = request.send().map_err(send_error)?;
+
+    match res.status() {
+        StatusCode::OK => {
+            let body = res.text().map_err(|_| TransportError::Unexpected {
+                status: None,
+                body: String::new(),
+            })?;
+            if body.trim().is_empty() {
+                return Err(TransportError::EmptyBody);
+            }
+            Ok(body)
+        }
+
+        StatusCode::NOT_FOUND => Err(TransportError::NotFound),
+
+        status => Err(TransportError::Unexpected {
+            status: Some(status.as_u16()),
+            body: read_error_body(res),
+        }),
+    }
+}
 
-            StatusCode::NOT_FOUND => Err(TransportError::NotFound),
+/// the start of the body of an error response, which relays often use to explain the error.
+/// Reading stops at `MAX_ERROR_BODY_LEN` bytes, so a misbehaving relay can't make the client
+/// buffer an arbitrarily large body just to report an error.
+fn read_error_body(res: Response) -> String {
+    let mut body = Vec::new();
+    // the body is only used to explain the error, so it's fine to lose it if reading fails
+    let _ = res.take(MAX_ERROR_BODY_LEN).read_to_end(&mut body);
+    String::from_utf8_lossy(&body).trim().to_string()
+}
 
-            _ => Err(TransportError::Unexpected),
+fn send_error(e: reqwest::Error) -> TransportError {
+    if e.is_timeout() {
+        TransportError::Timeout
+    } else {
+        TransportError::Unexpected {
+            status: None,
+            body: String::new(),
         }
     }
 }
@@ -36,3 +97,434 @@ pub fn new_http_transport() -> HttpTransport {
         client: Client::new(),
     }
 }
+
+/// create a builder for an `HttpTransport` with custom connection options, e.g. for relays
+/// behind corporate proxies or using private certificate authorities
+pub fn http_transport_builder() -> HttpTransportBuilder {
+    HttpTransportBuilder::default()
+}
+
+/// configures the `reqwest` client used by an `HttpTransport`. Certificates are verified
+/// strictly unless explicitly disabled.
+#[derive(Default)]
+pub struct HttpTransportBuilder {
+    root_certificates: Vec<Certificate>,
+    accept_invalid_certs: bool,
+    http2_prior_knowledge: bool,
+    pool_idle_timeout: Option<Option<Duration>>,
+    pool_max_idle_per_host: Option<usize>,
+    tcp_keepalive: Option<Duration>,
+    proxy: Option<Proxy>,
+    ignore_env_proxy: bool,
+}
+
+impl HttpTransportBuilder {
+    /// trust an additional root certificate on top of the system ones
+    pub fn add_root_certificate(mut self, certificate: Certificate) -> Self {
+        self.root_certificates.push(certificate);
+        self
+    }
+
+    /// trust an additional PEM encoded root certificate on top of the system ones
+    pub fn add_root_certificate_pem(self, pem: &[u8]) -> Result<Self, TransportError> {
+        let certificate =
+            Certificate::from_pem(pem).map_err(|_| TransportError::InvalidConfiguration)?;
+        Ok(self.add_root_certificate(certificate))
+    }
+
+    /// disable TLS certificate verification entirely. Anybody able to intercept the
+    /// connection can then impersonate the relay, so only use this for private relays
+    /// you control. Beacons are still verified against the chain's public key.
+    pub fn danger_accept_invalid_certs(mut self) -> Self {
+        self.accept_invalid_certs = true;
+        self
+    }
+
+    /// speak HTTP/2 from the start of each connection, so concurrent requests to the same
+    /// relay are multiplexed over one connection. Only use this for relays known to support
+    /// HTTP/2, as requests to relays that don't will fail.
+    pub fn http2_prior_knowledge(mut self) -> Self {
+        self.http2_prior_knowledge = true;
+        self
+    }
+
+    /// how long idle connections are kept open for reuse, or `None` to keep them indefinitely
+    pub fn pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// the maximum number of idle connections kept open to each relay
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// send TCP keep-alive probes on open connections at the given interval
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// send all requests through the HTTP(S) proxy at `url`. Without one, the proxies set in
+    /// the `HTTP_PROXY`/`HTTPS_PROXY` environment variables are used.
+    pub fn proxy(mut self, url: &str) -> Result<Self, TransportError> {
+        self.proxy = Some(Proxy::all(url).map_err(|_| TransportError::InvalidConfiguration)?);
+        Ok(self)
+    }
+
+    /// like `proxy`, authenticating to the proxy with basic auth
+    pub fn proxy_with_credentials(
+        mut self,
+        url: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<Self, TransportError> {
+        let proxy = Proxy::all(url).map_err(|_| TransportError::InvalidConfiguration)?;
+        self.proxy = Some(proxy.basic_auth(username, password));
+        Ok(self)
+    }
+
+    /// send all requests through the SOCKS5 proxy at `url`, e.g. `socks5h://127.0.0.1:9050` for
+    /// a local Tor instance, so relays don't learn the client's IP address. With `socks5h`
+    /// relays' hostnames are also resolved by the proxy, which Tor needs to avoid leaking DNS
+    /// lookups; with `socks5` they are resolved locally.
+    #[cfg(feature = "socks")]
+    pub fn socks5_proxy(mut self, url: &str) -> Result<Self, TransportError> {
+        if !(url.starts_with("socks5://") || url.starts_with("socks5h://")) {
+            return Err(TransportError::InvalidConfiguration);
+        }
+        self.proxy = Some(Proxy::all(url).map_err(|_| TransportError::InvalidConfiguration)?);
+        Ok(self)
+    }
+
+    /// ignore the proxies set in the `HTTP_PROXY`/`HTTPS_PROXY` environment variables
+    pub fn ignore_env_proxy(mut self) -> Self {
+        self.ignore_env_proxy = true;
+        self
+    }
+
+    pub fn build(self) -> Result<HttpTransport, TransportError> {
+        let mut builder = Client::builder().danger_accept_invalid_certs(self.accept_invalid_certs);
+        for certificate in self.root_certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+        // anything not set is left at reqwest's defaults
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        if self.ignore_env_proxy {
+            builder = builder.no_proxy();
+        }
+        if let Some(proxy) = self.proxy {
+            builder = builder.proxy(proxy);
+        }
+
+        let client = builder
+            .build()
+            .map_err(|_| TransportError::InvalidConfiguration)?;
+        Ok(HttpTransport { client })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::http::{http_transport_builder, new_http_transport, MAX_ERROR_BODY_LEN};
+    use crate::{Transport, TransportError};
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn ok_response_returns_body() {
+        let url = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}");
+        assert_eq!(new_http_transport().fetch(&url).unwrap(), "{}");
+    }
+
+    #[test]
+    fn empty_ok_response_fails() {
+        let url = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n");
+        assert!(matches!(
+            new_http_transport().fetch(&url),
+            Err(TransportError::EmptyBody)
+        ));
+    }
+
+    #[test]
+    fn whitespace_ok_response_fails() {
+        let url = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\n \r\n");
+        assert!(matches!(
+            new_http_transport().fetch(&url),
+            Err(TransportError::EmptyBody)
+        ));
+    }
+
+    #[test]
+    fn not_found_response_fails() {
+        let url = serve_once("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n");
+        assert!(matches!(
+            new_http_transport().fetch(&url),
+            Err(TransportError::NotFound)
+        ));
+    }
+
+    // a self-signed CA certificate generated with `openssl req -x509 -newkey ec`
+    const ROOT_CERTIFICATE: &str = "-----BEGIN CERTIFICATE-----
+MIIBmzCCAUGgAwIBAgIUFkeUp02ozyDazRmsOgag8RxqivkwCgYIKoZIzj0EAwIw
+IjEgMB4GA1UEAwwXZHJhbmQtY2xpZW50LXJzIHRlc3QgQ0EwIBcNMjYxMDE2MDk1
+NTQzWhgPMjEyNjA5MjIwOTU1NDNaMCIxIDAeBgNVBAMMF2RyYW5kLWNsaWVudC1y
+cyB0ZXN0IENBMFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAE2HfUs7rkgGBdbrEH
+QGQeIqxdE3FqNhursyyiLyg05x+QdOuEYx3CmzhiJ+FxgHPVPyrsFoeF+pnColDo
+QTySSqNTMFEwHQYDVR0OBBYEFI2MKtNglIrVuz+OFJsJ57kXXJckMB8GA1UdIwQY
+MBaAFI2MKtNglIrVuz+OFJsJ57kXXJckMA8GA1UdEwEB/wQFMAMBAf8wCgYIKoZI
+zj0EAwIDSAAwRQIhAJUPLTAwefvU29xUKGcX8yV51xIKPmnTwJxRPS2DIAkCAiBO
+V/DCzWWfoNQDL6kvUn9XfKvdmnhLQOBJOKEl4ViW0Q==
+-----END CERTIFICATE-----
+";
+
+    #[test]
+    fn transport_with_custom_root_certificate_builds() {
+        let transport = http_transport_builder()
+            .add_root_certificate_pem(ROOT_CERTIFICATE.as_bytes())
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let url = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}");
+        assert_eq!(transport.fetch(&url).unwrap(), "{}");
+    }
+
+    #[test]
+    fn invalid_root_certificate_fails() {
+        assert!(matches!(
+            http_transport_builder().add_root_certificate_pem(b"not a certificate"),
+            Err(TransportError::InvalidConfiguration)
+        ));
+    }
+
+    #[test]
+    fn transport_accepting_invalid_certs_builds() {
+        assert!(http_transport_builder()
+            .danger_accept_invalid_certs()
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn http2_prior_knowledge_is_used_when_enabled() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut preface = [0u8; 14];
+            stream.read_exact(&mut preface).unwrap();
+            sender.send(preface).unwrap();
+        });
+
+        let transport = http_transport_builder()
+            .http2_prior_knowledge()
+            .pool_idle_timeout(Some(Duration::from_secs(30)))
+            .pool_max_idle_per_host(4)
+            .tcp_keepalive(Duration::from_secs(60))
+            .build()
+            .unwrap();
+        // the server hangs up after reading the preface, so the request itself fails
+        let _ = transport.fetch(&format!("http://{address}/public/latest"));
+
+        assert_eq!(&receiver.recv().unwrap(), b"PRI * HTTP/2.0");
+    }
+
+    #[test]
+    fn keep_alive_options_still_speak_http1() {
+        let url = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}");
+        let transport = http_transport_builder()
+            .pool_idle_timeout(None)
+            .pool_max_idle_per_host(1)
+            .build()
+            .unwrap();
+
+        assert_eq!(transport.fetch(&url).unwrap(), "{}");
+    }
+
+    #[test]
+    fn requests_go_through_configured_proxy() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let n = stream.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}")
+                .unwrap();
+            sender.send(String::from_utf8(request).unwrap()).unwrap();
+        });
+
+        let transport = http_transport_builder()
+            .ignore_env_proxy()
+            .proxy_with_credentials(&format!("http://{address}"), "drand", "secret")
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            transport
+                .fetch("http://relay.invalid/public/latest")
+                .unwrap(),
+            "{}"
+        );
+        let request = receiver.recv().unwrap();
+        assert!(request.starts_with("GET http://relay.invalid/public/latest HTTP/1.1"));
+        // base64 of `drand:secret`
+        assert!(request
+            .to_lowercase()
+            .contains("proxy-authorization: basic zhjhbmq6c2vjcmv0"));
+    }
+
+    #[cfg(feature = "socks")]
+    #[test]
+    fn requests_go_through_configured_socks_proxy() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            // a SOCKS5 greeting offering the "no authentication" method
+            let mut greeting = [0u8; 3];
+            stream.read_exact(&mut greeting).unwrap();
+            sender.send(greeting).unwrap();
+        });
+
+        let transport = http_transport_builder()
+            .ignore_env_proxy()
+            .socks5_proxy(&format!("socks5h://{address}"))
+            .unwrap()
+            .build()
+            .unwrap();
+        // the proxy hangs up after the greeting, so the request itself fails
+        let _ = transport.fetch("http://relay.invalid/public/latest");
+
+        assert_eq!(receiver.recv().unwrap(), [5, 1, 0]);
+        assert!(matches!(
+            http_transport_builder().socks5_proxy("http://127.0.0.1:9050"),
+            Err(TransportError::InvalidConfiguration)
+        ));
+    }
+
+    #[test]
+    fn error_response_body_is_surfaced() {
+        let url = serve_once(
+            "HTTP/1.1 500 Internal Server Error\r\nContent-Type: application/json\r\nContent-Length: 33\r\n\r\n{\"error\":\"beacon not yet signed\"}",
+        );
+        let error = new_http_transport().fetch(&url).unwrap_err();
+
+        assert_eq!(
+            error,
+            TransportError::Unexpected {
+                status: Some(500),
+                body: "{\"error\":\"beacon not yet signed\"}".to_string(),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "unexpected response with status 500: {\"error\":\"beacon not yet signed\"}"
+        );
+    }
+
+    #[test]
+    fn long_error_response_body_is_truncated() {
+        let response = format!(
+            "HTTP/1.1 502 Bad Gateway\r\nContent-Length: 10000\r\n\r\n{}",
+            "x".repeat(10000)
+        );
+        let url = serve_once(response);
+
+        match new_http_transport().fetch(&url) {
+            Err(TransportError::Unexpected {
+                status: Some(502),
+                body,
+            }) => assert_eq!(body.len() as u64, MAX_ERROR_BODY_LEN),
+            other => panic!("expected an unexpected response error but got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn exists_sends_head_request() {
+        let (url, requests) = serve_once_recording("HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n");
+        assert_eq!(new_http_transport().exists(&url), Ok(true));
+        assert!(requests
+            .recv()
+            .unwrap()
+            .starts_with("HEAD /public/latest HTTP/1.1"));
+
+        let (url, requests) =
+            serve_once_recording("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n");
+        assert_eq!(new_http_transport().exists(&url), Ok(false));
+        assert!(requests.recv().unwrap().starts_with("HEAD "));
+
+        let url = serve_once("HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\n\r\n");
+        assert_eq!(
+            new_http_transport().exists(&url),
+            Err(TransportError::Unexpected {
+                status: Some(500),
+                body: String::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_proxy_url_fails() {
+        assert!(matches!(
+            http_transport_builder().proxy("not a url"),
+            Err(TransportError::InvalidConfiguration)
+        ));
+    }
+
+    /// serve a single canned HTTP `response` on a local port, returning the URL to request
+    fn serve_once(response: impl Into<String>) -> String {
+        serve_once_recording(response).0
+    }
+
+    /// like `serve_once`, but also returns the request the server received
+    fn serve_once_recording(response: impl Into<String>) -> (String, mpsc::Receiver<String>) {
+        let response = response.into();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let n = stream.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            stream.write_all(response.as_bytes()).unwrap();
+            let _ = sender.send(String::from_utf8_lossy(&request).into_owned());
+        });
+        (format!("http://{address}/public/latest"), receiver)
+    }
+}
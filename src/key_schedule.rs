@@ -0,0 +1,102 @@
+//! # key_schedule
+//!
+//! verifying beacons of networks whose key changes over time, e.g. after a reshare
+//!
+
+use crate::chain_info::ChainInfo;
+use crate::verify::{Beacon, VerificationError};
+
+/// a sequence of chain info snapshots, each used for the beacons from its effective round
+/// until the effective round of the next snapshot
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeySchedule {
+    entries: Vec<(u64, ChainInfo)>,
+}
+
+/// create a schedule where `chain_info` applies from the first round onwards
+pub fn new_key_schedule(chain_info: ChainInfo) -> KeySchedule {
+    KeySchedule {
+        entries: vec![(1, chain_info)],
+    }
+}
+
+impl KeySchedule {
+    /// use `chain_info` for every beacon from `effective_from` onwards, until a later snapshot
+    /// takes over. A snapshot with the same `effective_from` as an existing one replaces it.
+    pub fn add(&mut self, effective_from: u64, chain_info: ChainInfo) {
+        match self
+            .entries
+            .binary_search_by_key(&effective_from, |(round, _)| *round)
+        {
+            Ok(i) => self.entries[i].1 = chain_info,
+            Err(i) => self.entries.insert(i, (effective_from, chain_info)),
+        }
+    }
+
+    /// the chain info whose effective range contains `round`.
+    /// Rounds before the earliest snapshot use the earliest snapshot.
+    pub fn chain_info_for_round(&self, round: u64) -> &ChainInfo {
+        let i = self
+            .entries
+            .partition_point(|(effective_from, _)| *effective_from <= round);
+        &self.entries[i.saturating_sub(1)].1
+    }
+
+    /// verify `beacon` with the key in effect for its round
+    pub fn verify(&self, beacon: &Beacon) -> Result<(), VerificationError> {
+        self.chain_info_for_round(beacon.round_number)
+            .verify(beacon)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::key_schedule::new_key_schedule;
+    use crate::test_util::TestSigner;
+    use crate::verify::{SchemeID, VerificationError};
+
+    #[test]
+    fn beacons_verify_against_key_in_effect_for_their_round() {
+        let before = TestSigner::with_secret(SchemeID::UnchainedOnG1RFC9380, 1);
+        let after = TestSigner::with_secret(SchemeID::UnchainedOnG1RFC9380, 2);
+        let mut schedule = new_key_schedule(before.chain_info(1692803367, 3));
+        schedule.add(100, after.chain_info(1692803367, 3));
+
+        assert_eq!(schedule.verify(&before.beacon(1)), Ok(()));
+        assert_eq!(schedule.verify(&before.beacon(99)), Ok(()));
+        assert_eq!(schedule.verify(&after.beacon(100)), Ok(()));
+        assert_eq!(schedule.verify(&after.beacon(5000)), Ok(()));
+
+        assert_eq!(
+            schedule.verify(&before.beacon(100)),
+            Err(VerificationError::SignatureFailedVerification)
+        );
+        assert_eq!(
+            schedule.verify(&after.beacon(99)),
+            Err(VerificationError::SignatureFailedVerification)
+        );
+    }
+
+    #[test]
+    fn snapshots_can_be_added_out_of_order() {
+        let first = TestSigner::with_secret(SchemeID::PedersenBlsUnchained, 1);
+        let second = TestSigner::with_secret(SchemeID::PedersenBlsUnchained, 2);
+        let third = TestSigner::with_secret(SchemeID::PedersenBlsUnchained, 3);
+        let mut schedule = new_key_schedule(first.chain_info(1595431050, 30));
+        schedule.add(200, third.chain_info(1595431050, 30));
+        schedule.add(100, second.chain_info(1595431050, 30));
+
+        assert_eq!(
+            schedule.chain_info_for_round(99),
+            &first.chain_info(1595431050, 30)
+        );
+        assert_eq!(
+            schedule.chain_info_for_round(150),
+            &second.chain_info(1595431050, 30)
+        );
+        assert_eq!(
+            schedule.chain_info_for_round(200),
+            &third.chain_info(1595431050, 30)
+        );
+    }
+}
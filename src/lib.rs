@@ -3,54 +3,266 @@
 //! `drand_client_rs` is a small rust library for retrieving random numbers from the [drand network](https://drand.love).
 //!
 
+// the only unsafe code is the C ABI of the `ffi` module, so the rest of the crate forbids it
+#![cfg_attr(not(feature = "ffi"), forbid(unsafe_code))]
+#![cfg_attr(feature = "ffi", deny(unsafe_code))]
+
 extern crate core;
 
+#[cfg(feature = "base58")]
+pub mod base58;
+pub mod cache;
 pub mod chain_info;
+pub mod circuit_breaker;
+pub mod commitment;
+pub mod debug;
+#[cfg(feature = "serde")]
+pub mod decode;
+pub mod eip2537;
+pub mod equivocation;
+pub mod failover;
+#[cfg(feature = "ffi")]
+#[allow(unsafe_code)]
+pub mod ffi;
+pub mod fn_transport;
+#[cfg(feature = "serde")]
+pub mod group;
+#[cfg(feature = "serde")]
 pub mod http;
+pub mod key_schedule;
+pub mod migration;
+#[cfg(feature = "object_store")]
+pub mod object_storage;
+pub mod randomness;
+pub mod reshare;
+pub mod retry;
+#[cfg(feature = "protobuf")]
+pub mod sync;
+#[cfg(test)]
+mod test_util;
+pub mod testing;
+pub mod throttle;
 pub mod verify;
+#[cfg(feature = "serde")]
+pub mod watch;
 
+#[cfg(feature = "serde")]
+use crate::cache::{BeaconCache, VerificationCache};
 use crate::chain_info::ChainInfo;
+#[cfg(feature = "serde")]
 use crate::http::{new_http_transport, HttpTransport};
-use crate::verify::{verify_beacon, Beacon};
+#[cfg(feature = "serde")]
+use crate::key_schedule::KeySchedule;
+use crate::verify::{Beacon, VerificationError};
+#[cfg(feature = "serde")]
+use crate::watch::{new_latest_round, LatestRound};
+#[cfg(feature = "serde")]
 use crate::DrandClientError::{InvalidChainInfo, InvalidRound};
-use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+#[cfg(feature = "serde")]
+use std::io::Write;
+#[cfg(feature = "serde")]
+use std::ops::{ControlFlow, RangeInclusive};
+#[cfg(feature = "serde")]
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering::Relaxed};
+#[cfg(feature = "serde")]
+use std::sync::mpsc::Sender;
+#[cfg(feature = "serde")]
+use std::sync::Arc;
+use std::sync::{Mutex, MutexGuard};
+#[cfg(feature = "serde")]
+use std::thread::{self, sleep};
+#[cfg(feature = "serde")]
+use std::time::Duration;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
+#[cfg(feature = "serde")]
 /// a struct encapsulating all the necessary state for retrieving and validating drand beacons.
 pub struct DrandClient<'a, T: Transport> {
     transport: T,
     base_url: &'a str,
     chain_info: ChainInfo,
+    reject_future_rounds: bool,
+    url_template: UrlTemplate,
+    key_schedule: Option<KeySchedule>,
+    verification_cache: Option<Arc<VerificationCache>>,
+    beacon_cache: Option<BeaconCache>,
+    latest_round: LatestRound,
 }
 
+#[cfg(feature = "serde")]
 /// create a new instance of the client with an HTTP transport for a given `base_url`.
 /// Supported `base_url`s include: "<https://api.drand.sh>", "<https://drand.cloudflare.com>" and "<https://api.drand.secureweb3.com:6875>".
 /// A full list can be found at <https://drand.love/developer/>
-pub fn new_http_client(base_url: &str) -> Result<DrandClient<HttpTransport>, DrandClientError> {
-    let http_transport = new_http_transport();
-    let chain_info = fetch_chain_info(&http_transport, base_url)?;
+pub fn new_http_client(base_url: &str) -> Result<DrandClient<'_, HttpTransport>, DrandClientError> {
+    new_client(new_http_transport(), base_url)
+}
+
+#[cfg(feature = "serde")]
+/// create a new instance of the client with a custom `transport` for a given `base_url`.
+/// The chain info is fetched once using the transport and used to verify all subsequent beacons.
+pub fn new_client<T: Transport>(
+    transport: T,
+    base_url: &str,
+) -> Result<DrandClient<'_, T>, DrandClientError> {
+    new_client_with_url_template(transport, base_url, UrlTemplate::ChainHash)
+}
+
+#[cfg(feature = "serde")]
+/// create a new instance of the client with a custom `transport` for a given `base_url`,
+/// building the URLs of the relay's endpoints with `url_template`, e.g. to pick one of the
+/// beacons served by a multi-beacon node
+pub fn new_client_with_url_template<T: Transport>(
+    transport: T,
+    base_url: &str,
+    url_template: UrlTemplate,
+) -> Result<DrandClient<'_, T>, DrandClientError> {
+    let chain_info = fetch_chain_info_from(&transport, &url_template.info_url(base_url))?;
+    if let UrlTemplate::BeaconId(beacon_id) = &url_template {
+        if chain_info.metadata.beacon_id != *beacon_id {
+            return Err(DrandClientError::BeaconIdMismatch {
+                expected: beacon_id.clone(),
+                actual: chain_info.metadata.beacon_id,
+            });
+        }
+    }
     Ok(DrandClient {
         base_url,
-        transport: http_transport,
+        transport,
         chain_info,
+        reject_future_rounds: false,
+        url_template,
+        key_schedule: None,
+        verification_cache: None,
+        beacon_cache: None,
+        latest_round: new_latest_round(),
     })
 }
 
+#[cfg(feature = "serde")]
+/// fetch and verify the latest beacon of the chain with `chain_hash`, given in hex, from the
+/// relay at `base_url`, e.g. `verify_latest("https://api.drand.sh", "52db9b...")`. The chain info
+/// served by the relay is only trusted if it hashes to `chain_hash`.
+pub fn verify_latest(base_url: &str, chain_hash: &str) -> Result<Beacon, DrandClientError> {
+    verify_latest_with(new_http_transport(), base_url, chain_hash)
+}
+
+#[cfg(feature = "serde")]
+/// like [verify_latest], but fetching with a custom `transport`
+pub fn verify_latest_with<T: Transport>(
+    transport: T,
+    base_url: &str,
+    chain_hash: &str,
+) -> Result<Beacon, DrandClientError> {
+    let expected = hex::decode(chain_hash).map_err(|_| InvalidChainInfo)?;
+    let chain_url = format!(
+        "{}/{}",
+        base_url.trim_end_matches('/'),
+        hex::encode(&expected)
+    );
+    let client = new_client(transport, &chain_url)?;
+
+    let actual = client.chain_info.compute_chain_hash()?;
+    if actual != expected || client.chain_info.chain_hash != expected {
+        return Err(DrandClientError::ChainHashMismatch {
+            expected: hex::encode(expected),
+            actual: hex::encode(actual),
+        });
+    }
+    client.latest_randomness()
+}
+
+/// how the URLs of a relay's endpoints are built from its `base_url`
+#[derive(Debug, PartialEq, Clone, Default)]
+pub enum UrlTemplate {
+    /// `{base_url}/info` and `{base_url}/public/{round}`, where relays serving several chains
+    /// include the chain hash in the `base_url`
+    #[default]
+    ChainHash,
+    /// drand's v2 API for nodes serving several beacons, addressed by their beacon ID:
+    /// `{base_url}/v2/beacons/{beacon_id}/info` and
+    /// `{base_url}/v2/beacons/{beacon_id}/rounds/{round}`
+    BeaconId(String),
+}
+
+impl UrlTemplate {
+    /// the URL of the chain info
+    pub fn info_url(&self, base_url: &str) -> String {
+        match self {
+            UrlTemplate::ChainHash => format!("{base_url}/info"),
+            UrlTemplate::BeaconId(beacon_id) => format!("{base_url}/v2/beacons/{beacon_id}/info"),
+        }
+    }
+
+    /// the URL of the beacon for `tag`, i.e. a round number or `latest`
+    pub fn beacon_url(&self, base_url: &str, tag: &str) -> String {
+        match self {
+            UrlTemplate::ChainHash => format!("{base_url}/public/{tag}"),
+            UrlTemplate::BeaconId(beacon_id) => {
+                format!("{base_url}/v2/beacons/{beacon_id}/rounds/{tag}")
+            }
+        }
+    }
+}
+
 /// represents a transport on which to connect to the drand network. This crate provides an
-/// HTTP transport out of the box, which can be created by calling `new_http_transport()`
-pub trait Transport {
+/// HTTP transport out of the box, which can be created by calling `new_http_transport()`.
+/// Transports must be `Send + Sync` so that a client can be shared between threads.
+pub trait Transport: Send + Sync {
     fn fetch(&self, url: &str) -> Result<String, TransportError>;
+
+    /// whether `url` exists, without needing its body. The default implementation fetches
+    /// `url` and discards the body; transports that can check more cheaply, e.g. with an HTTP
+    /// `HEAD` request, should override it.
+    fn exists(&self, url: &str) -> Result<bool, TransportError> {
+        match self.fetch(url) {
+            Ok(_) => Ok(true),
+            Err(TransportError::NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// like `fetch`, but failing with `TransportError::Timeout` once `deadline` has passed.
+    /// The default implementation only checks the deadline before fetching, so a slow fetch
+    /// can overrun it; transports that can bound their requests, e.g. with an HTTP timeout,
+    /// should override it.
+    fn fetch_by_deadline(&self, url: &str, deadline: Instant) -> Result<String, TransportError> {
+        if Instant::now() >= deadline {
+            return Err(TransportError::Timeout);
+        }
+        self.fetch(url)
+    }
+}
+
+/// the async counterpart of `Transport`, for users fetching beacons from an async runtime.
+/// The crate's transport decorators, e.g. `FailoverTransport` and `RetryTransport`, implement
+/// this trait whenever the transport they wrap does.
+pub trait AsyncTransport: Send + Sync {
+    fn fetch(&self, url: &str) -> impl Future<Output = Result<String, TransportError>> + Send;
 }
 
+#[cfg(feature = "serde")]
 /// fetch the chain info for a given URL. The chain info contains the public key (used to
 /// verify beacons) and the genesis time (used to calculate the time for given rounds).
 pub fn fetch_chain_info(
-    transport: &HttpTransport,
+    transport: &impl Transport,
     base_url: &str,
 ) -> Result<ChainInfo, DrandClientError> {
-    let url = format!("{base_url}/info");
-    match transport.fetch(&url) {
-        Err(_) => Err(DrandClientError::NotResponding),
+    fetch_chain_info_from(transport, &UrlTemplate::ChainHash.info_url(base_url))
+}
+
+#[cfg(feature = "serde")]
+fn fetch_chain_info_from(
+    transport: &impl Transport,
+    url: &str,
+) -> Result<ChainInfo, DrandClientError> {
+    match transport.fetch(url) {
+        Err(TransportError::EmptyBody) => Err(DrandClientError::EmptyResponse),
+        Err(e) => Err(DrandClientError::NotResponding(e)),
+        Ok(body) if body.trim().is_empty() => Err(DrandClientError::EmptyResponse),
         Ok(body) => serde_json::from_str(&body).map_err(|e| {
             println!("{}", e);
             InvalidChainInfo
@@ -58,12 +270,13 @@ pub fn fetch_chain_info(
     }
 }
 
+#[cfg(feature = "serde")]
 /// an implementation of the logic for retrieving randomness
 impl<'a, T: Transport> DrandClient<'a, T> {
     /// fetch the latest available randomness beacon
     pub fn latest_randomness(&self) -> Result<Beacon, DrandClientError> {
         let expected_round = round_for_time(&self.chain_info, SystemTime::now())?;
-        let beacon = self.fetch_beacon_tag("latest")?;
+        let beacon = self.fetch_beacon_tag("latest", None)?;
 
         // it could take some time to aggregate beacons, so we tolerate one round early for latest
         if beacon.round_number < expected_round - 1 {
@@ -73,12 +286,116 @@ impl<'a, T: Transport> DrandClient<'a, T> {
         Ok(beacon)
     }
 
+    /// reject beacons for rounds more than one round past the current round, as calculated
+    /// from the chain info and the system clock, as they are almost certainly bogus.
+    /// One round of slack is allowed for clock skew.
+    pub fn reject_future_rounds(mut self) -> Self {
+        self.reject_future_rounds = true;
+        self
+    }
+
+    /// verify beacons against the key in effect for their round in `key_schedule`, rather
+    /// than the key from the chain info fetched when the client was created
+    pub fn with_key_schedule(mut self, key_schedule: KeySchedule) -> Self {
+        self.key_schedule = Some(key_schedule);
+        self
+    }
+
+    /// skip verifying beacons already verified through `verification_cache`, which can be
+    /// shared with other clients
+    pub fn with_verification_cache(mut self, verification_cache: Arc<VerificationCache>) -> Self {
+        self.verification_cache = Some(verification_cache);
+        self
+    }
+
+    /// keep the beacons the client fetches and verifies in `beacon_cache`, so requests for a
+    /// round it already has are answered without going to the network
+    pub fn with_beacon_cache(mut self, beacon_cache: BeaconCache) -> Self {
+        self.beacon_cache = Some(beacon_cache);
+        self
+    }
+
+    /// the highest round the client has already fetched and verified, without going to the
+    /// network. This is always `None` unless the client has a beacon cache.
+    pub fn try_latest(&self) -> Option<Beacon> {
+        self.beacon_cache.as_ref()?.latest()
+    }
+
+    /// the handle the client's watch loop updates with each new beacon it verifies, which
+    /// consumers can share to follow the chain head without polling
+    pub fn latest_round(&self) -> LatestRound {
+        self.latest_round.clone()
+    }
+
     /// fetch a randomness beacon for a specific round
     pub fn randomness(&self, round_number: u64) -> Result<Beacon, DrandClientError> {
-        if round_number == 0 {
+        self.randomness_by(round_number, None)
+    }
+
+    /// fetch and verify the beacons for each of `rounds` in parallel, with at most
+    /// `concurrency` requests in flight at once (and at least one). The results are in the
+    /// same order as `rounds`, and a round failing doesn't stop the others from being fetched.
+    pub fn get_many(
+        &self,
+        rounds: &[u64],
+        concurrency: usize,
+    ) -> Vec<Result<Beacon, DrandClientError>> {
+        let next = AtomicUsize::new(0);
+        let workers = concurrency.clamp(1, rounds.len().max(1));
+        let mut results: Vec<(usize, Result<Beacon, DrandClientError>)> = thread::scope(|s| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut fetched = Vec::new();
+                        // each worker takes the next round nobody has started yet
+                        loop {
+                            let index = next.fetch_add(1, Relaxed);
+                            let Some(&round_number) = rounds.get(index) else {
+                                return fetched;
+                            };
+                            fetched.push((index, self.randomness(round_number)));
+                        }
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+
+    /// fetch a randomness beacon for a specific round, giving up once `deadline` has passed,
+    /// including while the transport fails over between relays. A beacon that arrives but
+    /// isn't verified by the deadline is discarded.
+    pub fn get_by_deadline(
+        &self,
+        round_number: u64,
+        deadline: Instant,
+    ) -> Result<Beacon, DrandClientError> {
+        let result = self.randomness_by(round_number, Some(deadline));
+        if Instant::now() >= deadline {
+            return Err(DrandClientError::DeadlineExceeded);
+        }
+        result
+    }
+
+    fn randomness_by(
+        &self,
+        round_number: u64,
+        deadline: Option<Instant>,
+    ) -> Result<Beacon, DrandClientError> {
+        if round_number < self.chain_info.genesis_round {
             Err(InvalidRound)
         } else {
-            let beacon = self.fetch_beacon_tag(&format!("{round_number}"))?;
+            self.check_round_plausible(round_number)?;
+            if let Some(beacon) = self.beacon_cache.as_ref().and_then(|c| c.get(round_number)) {
+                return Ok(beacon);
+            }
+            let beacon = self.fetch_beacon_tag(&format!("{round_number}"), deadline)?;
             if beacon.round_number != round_number {
                 return Err(DrandClientError::InvalidBeacon);
             }
@@ -86,26 +403,444 @@ impl<'a, T: Transport> DrandClient<'a, T> {
         }
     }
 
-    fn fetch_beacon_tag(&self, tag: &str) -> Result<Beacon, DrandClientError> {
-        let url = format!("{}/public/{}", self.base_url, tag);
+    /// whether the relay has a beacon for a specific round, answered from the beacon cache when
+    /// possible and otherwise without downloading the beacon. The beacon isn't verified.
+    pub fn is_round_available(&self, round_number: u64) -> Result<bool, DrandClientError> {
+        if round_number < self.chain_info.genesis_round {
+            return Err(InvalidRound);
+        }
+        if let Some(cache) = &self.beacon_cache {
+            if cache.get(round_number).is_some() {
+                return Ok(true);
+            }
+        }
+        let url = self
+            .url_template
+            .beacon_url(self.base_url, &format!("{round_number}"));
+        self.transport
+            .exists(&url)
+            .map_err(DrandClientError::NotResponding)
+    }
+
+    /// fetch and verify a randomness beacon for a specific round, and check its randomness is
+    /// `expected_randomness`, e.g. a value for the round received out of band
+    pub fn get_expecting(
+        &self,
+        round_number: u64,
+        expected_randomness: &[u8],
+    ) -> Result<Beacon, DrandClientError> {
+        let beacon = self.randomness(round_number)?;
+        if beacon.randomness != expected_randomness {
+            return Err(DrandClientError::RandomnessMismatch {
+                expected: hex::encode(expected_randomness),
+                actual: hex::encode(&beacon.randomness),
+            });
+        }
+        Ok(beacon)
+    }
+
+    /// fetch a randomness beacon for a specific round, bundled with the chain info needed to
+    /// verify it, so it can be passed on to a third party who can verify it independently
+    pub fn get_with_proof(&self, round_number: u64) -> Result<VerifiableBeacon, DrandClientError> {
+        Ok(VerifiableBeacon {
+            beacon: self.randomness(round_number)?,
+            chain_info: self.chain_info_for_round(round_number).clone(),
+        })
+    }
+
+    /// fetch and verify a randomness beacon for a specific round, describing it as a multi-line
+    /// report for humans, e.g. for a CLI or debugging. Fails as
+    /// [randomness](DrandClient::randomness) does, so only verified beacons are described.
+    pub fn describe(&self, round_number: u64) -> Result<String, DrandClientError> {
+        let beacon = self.randomness(round_number)?;
+        let chain_info = self.chain_info_for_round(round_number);
+        let emitted_at = chain_info
+            .time_of_round(round_number)
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| DrandClientError::UnexpectedError)?
+            .as_secs();
+        Ok(format!(
+            "round: {}\n\
+             emitted at: {} (unix time {})\n\
+             randomness: {}\n\
+             signature: {}\n\
+             scheme: {}\n\
+             result: verified\n",
+            beacon.round_number,
+            format_utc(emitted_at),
+            emitted_at,
+            hex::encode(&beacon.randomness),
+            hex::encode(&beacon.signature),
+            chain_info.scheme_id.name(),
+        ))
+    }
+
+    /// fetch and verify each round in `rounds`, writing the beacons to `writer` as
+    /// newline-delimited JSON in the same format the drand HTTP API serves them.
+    /// The writer is flushed every `ARCHIVE_FLUSH_INTERVAL` beacons and at the end.
+    /// Archiving stops at the first beacon that can't be fetched or verified.
+    /// Returns the number of beacons written.
+    pub fn archive_range(
+        &self,
+        rounds: RangeInclusive<u64>,
+        mut writer: impl Write,
+    ) -> Result<u64, DrandClientError> {
+        let mut written = 0;
+        for round_number in rounds {
+            let beacon = self.randomness(round_number)?;
+            serde_json::to_writer(&mut writer, &beacon)
+                .map_err(|_| DrandClientError::WriteFailed)?;
+            writer
+                .write_all(b"\n")
+                .map_err(|_| DrandClientError::WriteFailed)?;
+
+            written += 1;
+            if written % ARCHIVE_FLUSH_INTERVAL == 0 {
+                writer.flush().map_err(|_| DrandClientError::WriteFailed)?;
+            }
+        }
+        writer.flush().map_err(|_| DrandClientError::WriteFailed)?;
+        Ok(written)
+    }
+
+    /// fetch and verify each round from `start_round` up to the current round, as calculated
+    /// from the chain info and the system clock when called, e.g. to resume syncing from the
+    /// round after the last one stored. The iterator ends after the current round, or after
+    /// the first beacon that can't be fetched or verified.
+    pub fn backfill_from(
+        &self,
+        start_round: u64,
+    ) -> impl Iterator<Item = Result<Beacon, DrandClientError>> + '_ {
+        // an error working out the current round is yielded first, which ends the iterator
+        let (current_round, error) = match round_for_time(&self.chain_info, SystemTime::now()) {
+            Ok(current_round) => (current_round, None),
+            Err(e) => (0, Some(Err(e))),
+        };
+
+        error
+            .into_iter()
+            .chain((start_round..=current_round).map(|round_number| self.randomness(round_number)))
+            .scan(false, |failed, result| {
+                if *failed {
+                    return None;
+                }
+                *failed = result.is_err();
+                Some(result)
+            })
+    }
+
+    /// fetch and verify each round from `start` onwards, calling `f` with each verified beacon
+    /// until it returns `ControlFlow::Break`. Rounds already emitted are fetched straight away,
+    /// then each later round as soon as it's emitted, as [watch](DrandClient::watch) does, and
+    /// each beacon is also published to the client's [latest_round](DrandClient::latest_round).
+    /// Returns the error of the first round that can't be fetched or verified.
+    pub fn for_each_verified(
+        &self,
+        start: u64,
+        mut f: impl FnMut(&Beacon) -> ControlFlow<()>,
+    ) -> Result<(), DrandClientError> {
+        let mut round_number = start.max(self.chain_info.genesis_round);
+        loop {
+            if let Ok(wait) = self
+                .chain_info
+                .time_of_round(round_number)
+                .duration_since(SystemTime::now())
+            {
+                sleep(wait);
+            }
+            let beacon = self.fetch_when_emitted(round_number, None)?;
+            self.latest_round.publish(&beacon);
+            if f(&beacon).is_break() {
+                return Ok(());
+            }
+            let Some(next_round) = round_number.checked_add(1) else {
+                return Ok(());
+            };
+            round_number = next_round;
+        }
+    }
+
+    /// follow the chain head, fetching and verifying each round from the current one onwards as
+    /// soon as it's emitted. Each verified beacon is also published to the client's
+    /// [latest_round](DrandClient::latest_round). The iterator never ends by itself: a round
+    /// that can't be fetched or verified is yielded as an error before moving on to the next.
+    /// Use [watch_until](DrandClient::watch_until) to be able to stop it.
+    pub fn watch(&self) -> impl Iterator<Item = Result<Beacon, DrandClientError>> + '_ {
+        self.watch_until(Arc::new(AtomicBool::new(false)))
+    }
+
+    /// like [watch](DrandClient::watch), but ending once `stop` is set, e.g. from a signal
+    /// handler or another thread. The flag is checked while waiting for each round, so the
+    /// iterator ends within `WATCH_RETRY_INTERVAL` of it being set, and a round being fetched
+    /// when it's set is dropped rather than yielded. Once ended, it stays ended.
+    pub fn watch_until(
+        &self,
+        stop: Arc<AtomicBool>,
+    ) -> impl Iterator<Item = Result<Beacon, DrandClientError>> + '_ {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since_epoch| since_epoch.as_secs())
+            .unwrap_or_default();
+        // an invalid chain info is yielded once, which ends the iterator
+        let (mut next_round, mut error) = match self.chain_info.round_at(now) {
+            Ok(current_round) => (Some(current_round.max(self.chain_info.genesis_round)), None),
+            Err(e) => (None, Some(e)),
+        };
+
+        std::iter::from_fn(move || {
+            // once stopped, stay stopped rather than fetching another round
+            if stop.load(Relaxed) {
+                return None;
+            }
+            let Some(round_number) = next_round else {
+                return error.take().map(Err);
+            };
+            next_round = round_number.checked_add(1);
+
+            sleep_until(self.chain_info.time_of_round(round_number), &stop);
+            let result = self.fetch_when_emitted(round_number, Some(&stop));
+            if stop.load(Relaxed) {
+                return None;
+            }
+            if let Ok(beacon) = &result {
+                self.latest_round.publish(beacon);
+            }
+            Some(result)
+        })
+    }
+
+    /// run the watch loop, sending each verified beacon to `sender`, e.g. to fan beacons out to
+    /// other parts of an application. Rounds that can't be fetched or verified are skipped.
+    /// Returns once the receiver has been dropped, which is noticed when the next beacon is
+    /// sent, or if the watch loop ends because of invalid chain info.
+    pub fn watch_into(&self, sender: Sender<Beacon>) {
+        for beacon in self.watch().flatten() {
+            if sender.send(beacon).is_err() {
+                return;
+            }
+        }
+    }
+
+    /// relays can take a moment to aggregate a round after it's due, so keep asking for it
+    /// until the next round is due, or `stop` is set
+    fn fetch_when_emitted(
+        &self,
+        round_number: u64,
+        stop: Option<&AtomicBool>,
+    ) -> Result<Beacon, DrandClientError> {
+        let stopped = || stop.is_some_and(|stop| stop.load(Relaxed));
+        let give_up_at = self
+            .chain_info
+            .time_of_round(round_number.saturating_add(1));
+        loop {
+            match self.randomness(round_number) {
+                Err(
+                    DrandClientError::RoundNotYetAvailable
+                    | DrandClientError::NotResponding(TransportError::NotFound),
+                ) if SystemTime::now() < give_up_at && !stopped() => sleep(WATCH_RETRY_INTERVAL),
+                result => return result,
+            }
+        }
+    }
+
+    fn fetch_beacon_tag(
+        &self,
+        tag: &str,
+        deadline: Option<Instant>,
+    ) -> Result<Beacon, DrandClientError> {
+        let url = self.url_template.beacon_url(self.base_url, tag);
+        let response = match deadline {
+            Some(deadline) => self.transport.fetch_by_deadline(&url, deadline),
+            None => self.transport.fetch(&url),
+        };
 
-        match self.transport.fetch(&url) {
-            Err(_) => Err(DrandClientError::NotResponding),
+        match response {
+            Err(TransportError::EmptyBody) => Err(DrandClientError::EmptyResponse),
+            Err(e) => Err(DrandClientError::NotResponding(e)),
 
-            Ok(body) => match serde_json::from_str::<Beacon>(&body) {
-                Ok(beacon) => {
-                    verify_beacon(
-                        &self.chain_info.scheme_id,
-                        &self.chain_info.public_key,
-                        &beacon,
-                    )
-                    .map_err(|_| DrandClientError::FailedVerification)?;
+            Ok(body) if body.trim().is_empty() => Err(DrandClientError::EmptyResponse),
+            Ok(body) => match serde_json::from_str::<BeaconResponse>(&body) {
+                Ok(BeaconResponse { beacon, .. }) if beacon.signature.is_empty() => {
+                    Err(DrandClientError::RoundNotYetAvailable)
+                }
+                Ok(BeaconResponse { beacon, metadata }) => {
+                    self.check_metadata(metadata)?;
+                    self.check_round_plausible(beacon.round_number)?;
+                    let chain_info = self.chain_info_for_round(beacon.round_number);
+                    match &self.verification_cache {
+                        Some(cache) => cache.verify_for_chain(chain_info, &beacon)?,
+                        None => chain_info.verify(&beacon)?,
+                    }
+                    if let Some(cache) = &self.beacon_cache {
+                        cache.insert(beacon.clone());
+                    }
                     Ok(beacon)
                 }
+                Err(_) if is_pending_round(&body) => Err(DrandClientError::RoundNotYetAvailable),
                 Err(_) => Err(DrandClientError::InvalidBeacon),
             },
         }
     }
+
+    fn chain_info_for_round(&self, round_number: u64) -> &ChainInfo {
+        match &self.key_schedule {
+            Some(key_schedule) => key_schedule.chain_info_for_round(round_number),
+            None => &self.chain_info,
+        }
+    }
+
+    fn check_round_plausible(&self, round_number: u64) -> Result<(), DrandClientError> {
+        if !self.reject_future_rounds {
+            return Ok(());
+        }
+        let current_round = round_for_time(&self.chain_info, SystemTime::now())?;
+        if round_number > current_round + 1 {
+            return Err(InvalidRound);
+        }
+        Ok(())
+    }
+
+    /// refuse beacons whose metadata says they belong to another chain, so they are never
+    /// verified against the wrong key
+    fn check_metadata(
+        &self,
+        metadata: Option<BeaconResponseMetadata>,
+    ) -> Result<(), DrandClientError> {
+        match metadata {
+            Some(metadata)
+                if !metadata.chain_hash.is_empty()
+                    && metadata.chain_hash != self.chain_info.chain_hash =>
+            {
+                Err(DrandClientError::ChainHashMismatch {
+                    expected: hex::encode(&self.chain_info.chain_hash),
+                    actual: hex::encode(metadata.chain_hash),
+                })
+            }
+            Some(metadata)
+                if !metadata.beacon_id.is_empty()
+                    && metadata.beacon_id != self.chain_info.metadata.beacon_id =>
+            {
+                Err(DrandClientError::BeaconIdMismatch {
+                    expected: self.chain_info.metadata.beacon_id.clone(),
+                    actual: metadata.beacon_id,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+/// a beacon as served by a relay, which may carry metadata identifying the chain it is from
+#[derive(Deserialize)]
+struct BeaconResponse {
+    #[serde(flatten)]
+    beacon: Beacon,
+    #[serde(default)]
+    metadata: Option<BeaconResponseMetadata>,
+}
+
+#[cfg(feature = "serde")]
+/// the partial object a relay may serve for a round near the chain head that is still being
+/// assembled, which has no signature (or randomness) yet
+#[derive(Deserialize)]
+struct PendingBeaconResponse {
+    #[allow(dead_code)]
+    round: u64,
+    #[serde(default)]
+    signature: String,
+}
+
+#[cfg(feature = "serde")]
+fn is_pending_round(body: &str) -> bool {
+    serde_json::from_str::<PendingBeaconResponse>(body)
+        .is_ok_and(|pending| pending.signature.is_empty())
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct BeaconResponseMetadata {
+    #[serde(default, with = "hex", alias = "chainHash")]
+    chain_hash: Vec<u8>,
+    #[serde(default, alias = "beaconID")]
+    beacon_id: String,
+}
+
+#[cfg(feature = "serde")]
+const ARCHIVE_FLUSH_INTERVAL: u64 = 100;
+
+#[cfg(feature = "serde")]
+const WATCH_RETRY_INTERVAL: Duration = Duration::from_millis(250);
+
+/// sleep until `time`, waking every `WATCH_RETRY_INTERVAL` to return early if `stop` is set
+#[cfg(feature = "serde")]
+fn sleep_until(time: SystemTime, stop: &AtomicBool) {
+    while let Ok(wait) = time.duration_since(SystemTime::now()) {
+        if stop.load(Relaxed) {
+            return;
+        }
+        sleep(wait.min(WATCH_RETRY_INTERVAL));
+    }
+}
+
+/// a beacon along with the chain info it can be verified against. Verifying the bundle only
+/// shows the beacon belongs to the bundled chain: recipients should check that
+/// `chain_info.chain_hash` (or the public key) is the chain they expect.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VerifiableBeacon {
+    pub beacon: Beacon,
+    pub chain_info: ChainInfo,
+}
+
+impl VerifiableBeacon {
+    /// verify the beacon against the bundled scheme and public key
+    pub fn verify(&self) -> Result<(), VerificationError> {
+        self.chain_info.verify(&self.beacon)
+    }
+
+    /// encode the bundle as a single CBOR blob, e.g. to pass it between services
+    #[cfg(feature = "cbor")]
+    pub fn to_cbor(&self) -> Result<Vec<u8>, DrandClientError> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(self, &mut bytes).map_err(|_| DrandClientError::UnexpectedError)?;
+        Ok(bytes)
+    }
+
+    /// decode a bundle encoded by `to_cbor`. The bundle still needs verifying.
+    #[cfg(feature = "cbor")]
+    pub fn from_cbor(bytes: &[u8]) -> Result<VerifiableBeacon, DrandClientError> {
+        ciborium::from_reader(bytes).map_err(|_| DrandClientError::InvalidBeacon)
+    }
+}
+
+/// format seconds since the unix epoch as an RFC 3339 UTC timestamp, e.g.
+/// `2020-07-22T15:19:30Z`, converting days to a civil date with Howard Hinnant's algorithm
+#[cfg(feature = "serde")]
+fn format_utc(epoch_seconds: u64) -> String {
+    let days = epoch_seconds / 86400;
+    let seconds_of_day = epoch_seconds % 86400;
+    // days since 0000-03-01, so leap days fall at the end of each year
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60,
+        seconds_of_day % 60
+    )
 }
 
 pub fn round_for_time(chain_info: &ChainInfo, time: SystemTime) -> Result<u64, DrandClientError> {
@@ -118,8 +853,7 @@ pub fn round_for_time(chain_info: &ChainInfo, time: SystemTime) -> Result<u64, D
         return Err(DrandClientError::RoundBeforeGenesis);
     }
 
-    // at genesis, the round == 1, so we add 1
-    Ok((epoch_seconds - chain_info.genesis_time) / chain_info.period_seconds as u64 + 1)
+    chain_info.round_at(epoch_seconds)
 }
 
 #[derive(Error, Debug, PartialEq)]
@@ -129,32 +863,92 @@ pub enum DrandClientError {
     #[error("invalid beacon")]
     InvalidBeacon,
     #[error("beacon failed verification")]
-    FailedVerification,
+    FailedVerification(#[from] VerificationError),
     #[error("invalid chain info")]
     InvalidChainInfo,
     #[error("not responding")]
-    NotResponding,
+    NotResponding(#[source] TransportError),
     #[error("round before genesis")]
     RoundBeforeGenesis,
     #[error("unexpected error")]
     UnexpectedError,
+    #[error("failed to write output")]
+    WriteFailed,
+    #[error("empty response")]
+    EmptyResponse,
+    #[error("chain hash mismatch: expected {expected} but the response was for {actual}")]
+    ChainHashMismatch { expected: String, actual: String },
+    #[error("beacon ID mismatch: expected {expected} but the response was for {actual}")]
+    BeaconIdMismatch { expected: String, actual: String },
+    #[error("conflicting beacons were served for round {round}")]
+    Equivocation { round: u64 },
+    #[error("the round is still being assembled and has no signature yet")]
+    RoundNotYetAvailable,
+    #[error("randomness mismatch: expected {expected} but the beacon had {actual}")]
+    RandomnessMismatch { expected: String, actual: String },
+    #[error("the beacon couldn't be fetched and verified before the deadline")]
+    DeadlineExceeded,
 }
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, PartialEq, Clone)]
 pub enum TransportError {
     #[error("not found")]
     NotFound,
-    #[error("unexpected")]
-    Unexpected,
+    /// the request failed for an unexpected reason. For unexpected HTTP responses, `status` is
+    /// the response's status code and `body` the start of its body, which often explains why
+    #[error("unexpected{}", unexpected_details(.status, .body))]
+    Unexpected { status: Option<u16>, body: String },
+    #[error("empty body")]
+    EmptyBody,
+    #[error("invalid transport configuration")]
+    InvalidConfiguration,
+    #[error("request timed out")]
+    Timeout,
+    /// the request wasn't made, as the transport has been failing, see `CircuitBreakerTransport`
+    #[error("circuit open after repeated failures")]
+    CircuitOpen,
 }
 
-#[cfg(test)]
+fn unexpected_details(status: &Option<u16>, body: &str) -> String {
+    match (status, body.is_empty()) {
+        (Some(status), true) => format!(" response with status {status}"),
+        (Some(status), false) => format!(" response with status {status}: {body}"),
+        (None, _) => String::new(),
+    }
+}
+
+/// lock `mutex`, carrying on if a holder panicked. The crate's mutexes only guard state that's
+/// never left half updated, so it's still usable after a panic.
+pub(crate) fn lock_unpoisoned<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(all(test, feature = "serde"))]
 mod test {
+    use crate::cache::{new_beacon_cache, new_verification_cache};
     use crate::chain_info::{ChainInfo, ChainInfoMetadata};
+    use crate::fn_transport::new_fn_transport;
+    use crate::http::HttpTransport;
+    use crate::key_schedule::new_key_schedule;
+    use crate::test_util::TestSigner;
+    use crate::testing::InMemoryTransport;
     use crate::verify::SchemeID::PedersenBlsChained;
+    use crate::verify::{Beacon, SchemeID, VerificationError};
+    use crate::watch::new_latest_round;
     use crate::DrandClientError::InvalidRound;
-    use crate::{new_http_client, DrandClient, DrandClientError, Transport, TransportError};
-    use std::time::{SystemTime, UNIX_EPOCH};
+    use crate::{
+        format_utc, new_client_with_url_template, new_http_client, round_for_time,
+        verify_latest_with, DrandClient, DrandClientError, Transport, TransportError, UrlTemplate,
+        VerifiableBeacon,
+    };
+    use std::error::Error;
+    use std::ops::ControlFlow;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering::SeqCst};
+    use std::sync::{mpsc, Arc};
+    use std::thread;
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
     #[test]
     fn request_chained_randomness_success() -> Result<(), DrandClientError> {
@@ -220,17 +1014,14 @@ mod test {
             group_hash: hex::decode("176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a").unwrap(),
             genesis_time: 1595431050,
             period_seconds: 30,
+            genesis_round: 1,
             metadata: ChainInfoMetadata {
                 beacon_id: "default".to_string(),
             },
         };
         let beacon = "{\"round\":2,\"randomness\":\"e8fee7dac6eb2b89df97d631cfccedbada7d5d05495bb546eef462e4145fdf8f\",\"signature\":\"aa18facd2d51b616511d542de6f9af8a3b920121401dad1434ed1db4a565f10e04fad8d9b2b4e3e0094364374caafe9b10478bf75650124831509c638b5a36a7a232ec70289f8751a2adb47fc32eb70b57dc81c39d48cbcac9fec46cdfc31663\",\"previous_signature\":\"8d61d9100567de44682506aea1a7a6fa6e5491cd27a0a0ed349ef6910ac5ac20ff7bc3e09d7c046566c9f7f3c6f3b10104990e7cb424998203d8f7de586fb7fa5f60045417a432684f85093b06ca91c769f0e7ca19268375e659c2a2352b4655\"";
         let transport = MockTransport { beacon };
-        let client = DrandClient {
-            transport,
-            base_url: "api.drand.sh",
-            chain_info: info,
-        };
+        let client = test_client(transport, info);
 
         client
             .randomness(4)
@@ -247,17 +1038,14 @@ mod test {
             group_hash: hex::decode("176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a").unwrap(),
             genesis_time: 1595431050,
             period_seconds: 30,
+            genesis_round: 1,
             metadata: ChainInfoMetadata {
                 beacon_id: "default".to_string(),
             },
         };
         let beacon = "{\"round\":2,\"randomness\":\"e8fee7dac6eb2b89df97d631cfccedbada7d5d05495bb546eef462e4145fdf8f\",\"signature\":\"aa18facd2d51b616511d542de6f9af8a3b920121401dad1434ed1db4a565f10e04fad8d9b2b4e3e0094364374caafe9b10478bf75650124831509c638b5a36a7a232ec70289f8751a2adb47fc32eb70b57dc81c39d48cbcac9fec46cdfc31663\",\"previous_signature\":\"8d61d9100567de44682506aea1a7a6fa6e5491cd27a0a0ed349ef6910ac5ac20ff7bc3e09d7c046566c9f7f3c6f3b10104990e7cb424998203d8f7de586fb7fa5f60045417a432684f85093b06ca91c769f0e7ca19268375e659c2a2352b4655\"";
         let transport = MockTransport { beacon };
-        let client = DrandClient {
-            transport,
-            base_url: "api.drand.sh",
-            chain_info: info,
-        };
+        let client = test_client(transport, info);
 
         client
             .latest_randomness()
@@ -275,17 +1063,14 @@ mod test {
             // here we set genesis so it should be round 3
             genesis_time: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() - 60,
             period_seconds: 30,
+            genesis_round: 1,
             metadata: ChainInfoMetadata {
                 beacon_id: "default".to_string(),
             },
         };
         let beacon = "{\"round\":2,\"randomness\":\"e8fee7dac6eb2b89df97d631cfccedbada7d5d05495bb546eef462e4145fdf8f\",\"signature\":\"aa18facd2d51b616511d542de6f9af8a3b920121401dad1434ed1db4a565f10e04fad8d9b2b4e3e0094364374caafe9b10478bf75650124831509c638b5a36a7a232ec70289f8751a2adb47fc32eb70b57dc81c39d48cbcac9fec46cdfc31663\",\"previous_signature\":\"8d61d9100567de44682506aea1a7a6fa6e5491cd27a0a0ed349ef6910ac5ac20ff7bc3e09d7c046566c9f7f3c6f3b10104990e7cb424998203d8f7de586fb7fa5f60045417a432684f85093b06ca91c769f0e7ca19268375e659c2a2352b4655\"}";
         let transport = MockTransport { beacon };
-        let client = DrandClient {
-            transport,
-            base_url: "api.drand.sh",
-            chain_info: info,
-        };
+        let client = test_client(transport, info);
 
         client
             .latest_randomness()
@@ -302,17 +1087,14 @@ mod test {
             group_hash: hex::decode("176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a").unwrap(),
             genesis_time: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() - 30,
             period_seconds: 30,
+            genesis_round: 1,
             metadata: ChainInfoMetadata {
                 beacon_id: "default".to_string(),
             },
         };
         let beacon = "{\"round\":2,\"randomness\":\"e8fee7dac6eb2b89df97d631cfccedbada7d5d05495bb546eef462e4145fdf8f\",\"signature\":\"aa18facd2d51b616511d542de6f9af8a3b920121401dad1434ed1db4a565f10e04fad8d9b2b4e3e0094364374caafe9b10478bf75650124831509c638b5a36a7a232ec70289f8751a2adb47fc32eb70b57dc81c39d48cbcac9fec46cdfc31663\",\"previous_signature\":\"8d61d9100567de44682506aea1a7a6fa6e5491cd27a0a0ed349ef6910ac5ac20ff7bc3e09d7c046566c9f7f3c6f3b10104990e7cb424998203d8f7de586fb7fa5f60045417a432684f85093b06ca91c769f0e7ca19268375e659c2a2352b4655\"}";
         let transport = MockTransport { beacon };
-        let client = DrandClient {
-            transport,
-            base_url: "api.drand.sh",
-            chain_info: info,
-        };
+        let client = test_client(transport, info);
 
         client
             .latest_randomness()
@@ -320,10 +1102,918 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn client_is_shareable_between_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<HttpTransport>();
+        assert_send_sync::<DrandClient<HttpTransport>>();
+
+        let info = ChainInfo {
+            scheme_id: PedersenBlsChained,
+            public_key: hex::decode("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31").unwrap(),
+            chain_hash: hex::decode("8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce").unwrap(),
+            group_hash: hex::decode("176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a").unwrap(),
+            genesis_time: 1595431050,
+            period_seconds: 30,
+            genesis_round: 1,
+            metadata: ChainInfoMetadata {
+                beacon_id: "default".to_string(),
+            },
+        };
+        let beacon = "{\"round\":2,\"randomness\":\"e8fee7dac6eb2b89df97d631cfccedbada7d5d05495bb546eef462e4145fdf8f\",\"signature\":\"aa18facd2d51b616511d542de6f9af8a3b920121401dad1434ed1db4a565f10e04fad8d9b2b4e3e0094364374caafe9b10478bf75650124831509c638b5a36a7a232ec70289f8751a2adb47fc32eb70b57dc81c39d48cbcac9fec46cdfc31663\",\"previous_signature\":\"8d61d9100567de44682506aea1a7a6fa6e5491cd27a0a0ed349ef6910ac5ac20ff7bc3e09d7c046566c9f7f3c6f3b10104990e7cb424998203d8f7de586fb7fa5f60045417a432684f85093b06ca91c769f0e7ca19268375e659c2a2352b4655\"}";
+        let transport = MockTransport { beacon };
+        let client = test_client(transport, info);
+
+        thread::scope(|s| {
+            let handles: Vec<_> = (0..4).map(|_| s.spawn(|| client.randomness(2))).collect();
+            for handle in handles {
+                assert_eq!(handle.join().unwrap().unwrap().round_number, 2);
+            }
+        });
+    }
+
+    #[test]
+    fn far_future_round_is_rejected_when_enabled() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let genesis_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            - 300;
+        let mut transport = InMemoryTransport::new();
+        let far_future = serde_json::to_string(&signer.beacon(1_000_000)).unwrap();
+        transport.insert("https://example.org/public/latest", &far_future);
+        transport.insert("https://example.org/public/1000000", &far_future);
+        let client = test_client(transport, signer.chain_info(genesis_time, 30));
+
+        assert_eq!(client.randomness(1_000_000), Ok(signer.beacon(1_000_000)));
+
+        let client = client.reject_future_rounds();
+        assert_eq!(client.randomness(1_000_000), Err(InvalidRound));
+        assert_eq!(client.latest_randomness(), Err(InvalidRound));
+    }
+
+    #[test]
+    fn client_verifies_across_reshare_with_key_schedule() {
+        let before = TestSigner::with_secret(SchemeID::UnchainedOnG1RFC9380, 1);
+        let after = TestSigner::with_secret(SchemeID::UnchainedOnG1RFC9380, 2);
+        let mut transport = InMemoryTransport::new();
+        for beacon in [before.beacon(99), after.beacon(100)] {
+            transport.insert(
+                &format!("https://example.org/public/{}", beacon.round_number),
+                &serde_json::to_string(&beacon).unwrap(),
+            );
+        }
+        let client = test_client(transport, before.chain_info(1692803367, 3));
+
+        assert_eq!(client.randomness(99), Ok(before.beacon(99)));
+        assert_eq!(
+            client.randomness(100),
+            Err(DrandClientError::FailedVerification(
+                VerificationError::SignatureFailedVerification
+            ))
+        );
+
+        let mut key_schedule = new_key_schedule(before.chain_info(1692803367, 3));
+        key_schedule.add(100, after.chain_info(1692803367, 3));
+        let client = client.with_key_schedule(key_schedule);
+
+        assert_eq!(client.randomness(99), Ok(before.beacon(99)));
+        assert_eq!(client.randomness(100), Ok(after.beacon(100)));
+        assert_eq!(
+            client.get_with_proof(100).map(|bundle| bundle.chain_info),
+            Ok(after.chain_info(1692803367, 3))
+        );
+    }
+
+    #[test]
+    fn clients_share_verification_cache() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let body = serde_json::to_string(&signer.beacon(5)).unwrap();
+        let cache = Arc::new(new_verification_cache(16));
+        let client = |body| {
+            test_client(
+                MockTransport { beacon: body },
+                signer.chain_info(1595431050, 30),
+            )
+            .with_verification_cache(cache.clone())
+        };
+
+        assert_eq!(client(&body).randomness(5), Ok(signer.beacon(5)));
+        assert_eq!(client(&body).randomness(5), Ok(signer.beacon(5)));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn try_latest_returns_highest_cached_round() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let client = test_client(
+            MockTransport { beacon: "" },
+            signer.chain_info(1595431050, 30),
+        );
+        assert_eq!(client.try_latest(), None);
+
+        let beacon_cache = new_beacon_cache(16);
+        for round in [4, 9, 2] {
+            beacon_cache.insert(signer.beacon(round));
+        }
+        let client = client.with_beacon_cache(beacon_cache);
+
+        assert_eq!(client.try_latest(), Some(signer.beacon(9)));
+        // the transport only serves empty responses, so this must come from the cache
+        assert_eq!(client.randomness(4), Ok(signer.beacon(4)));
+        assert_eq!(client.randomness(5), Err(DrandClientError::EmptyResponse));
+    }
+
+    #[test]
+    fn fetched_beacons_are_cached() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let body = serde_json::to_string(&signer.beacon(5)).unwrap();
+        let client = test_client(
+            MockTransport { beacon: &body },
+            signer.chain_info(1595431050, 30),
+        )
+        .with_beacon_cache(new_beacon_cache(16));
+
+        assert_eq!(client.randomness(5), Ok(signer.beacon(5)));
+        assert_eq!(client.try_latest(), Some(signer.beacon(5)));
+    }
+
+    #[test]
+    fn get_expecting_checks_randomness() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let body = serde_json::to_string(&signer.beacon(5)).unwrap();
+        let client = test_client(
+            MockTransport { beacon: &body },
+            signer.chain_info(1692803367, 3),
+        );
+        let expected = signer.beacon(5).randomness;
+
+        assert_eq!(client.get_expecting(5, &expected), Ok(signer.beacon(5)));
+
+        let other = signer.beacon(6).randomness;
+        assert_eq!(
+            client.get_expecting(5, &other),
+            Err(DrandClientError::RandomnessMismatch {
+                expected: hex::encode(&other),
+                actual: hex::encode(&expected),
+            })
+        );
+    }
+
+    #[test]
+    fn round_availability_uses_cache_then_transport() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let mut transport = InMemoryTransport::new();
+        transport.insert("https://example.org/public/2", "{}");
+        let beacon_cache = new_beacon_cache(16);
+        beacon_cache.insert(signer.beacon(1));
+        let client = test_client(transport, signer.chain_info(1595431050, 30))
+            .with_beacon_cache(beacon_cache);
+
+        assert_eq!(client.is_round_available(1), Ok(true));
+        assert_eq!(client.is_round_available(2), Ok(true));
+        assert_eq!(client.is_round_available(3), Ok(false));
+        assert_eq!(client.is_round_available(0), Err(InvalidRound));
+    }
+
+    #[test]
+    fn chain_starting_at_round_zero_serves_round_zero() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let mut transport = InMemoryTransport::new();
+        for round in 0..=1 {
+            transport.insert(
+                &format!("https://example.org/public/{round}"),
+                &serde_json::to_string(&signer.beacon(round)).unwrap(),
+            );
+        }
+        let client = DrandClient {
+            verification_cache: Some(Arc::new(new_verification_cache(16))),
+            ..test_client(
+                transport,
+                ChainInfo {
+                    genesis_round: 0,
+                    ..signer.chain_info(1595431050, 3)
+                },
+            )
+        };
+
+        assert_eq!(client.randomness(0), Ok(signer.beacon(0)));
+        assert_eq!(client.randomness(1), Ok(signer.beacon(1)));
+    }
+
+    /// a relay serving the chain info of `signer` under its computed chain hash, with the latest
+    /// beacon due
+    fn relay_for_verify_latest(signer: &TestSigner) -> (InMemoryTransport, String) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let mut chain_info = signer.chain_info(now - 100, 3);
+        chain_info.chain_hash = chain_info.compute_chain_hash().unwrap();
+        let chain_hash = hex::encode(&chain_info.chain_hash);
+        let latest = chain_info.round_at(now).unwrap();
+
+        let mut transport = InMemoryTransport::new();
+        transport.insert(
+            &format!("https://example.org/{chain_hash}/info"),
+            &serde_json::to_string(&chain_info).unwrap(),
+        );
+        transport.insert(
+            &format!("https://example.org/{chain_hash}/public/latest"),
+            &serde_json::to_string(&signer.beacon(latest)).unwrap(),
+        );
+        (transport, chain_hash)
+    }
+
+    #[test]
+    fn verify_latest_returns_verified_beacon() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let (transport, chain_hash) = relay_for_verify_latest(&signer);
+
+        let beacon = verify_latest_with(transport, "https://example.org/", &chain_hash).unwrap();
+        assert_eq!(
+            beacon.signature,
+            signer.beacon(beacon.round_number).signature
+        );
+    }
+
+    #[test]
+    fn verify_latest_rejects_chain_info_not_matching_the_hash() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let (mut transport, chain_hash) = relay_for_verify_latest(&signer);
+        // another key served under the expected chain hash
+        let impostor = TestSigner::with_secret(SchemeID::UnchainedOnG1RFC9380, 99);
+        let mut chain_info = impostor.chain_info(1595431050, 3);
+        chain_info.chain_hash = hex::decode(&chain_hash).unwrap();
+        transport.insert(
+            &format!("https://example.org/{chain_hash}/info"),
+            &serde_json::to_string(&chain_info).unwrap(),
+        );
+
+        assert!(matches!(
+            verify_latest_with(transport, "https://example.org", &chain_hash),
+            Err(DrandClientError::ChainHashMismatch { .. })
+        ));
+        assert_eq!(
+            verify_latest_with(InMemoryTransport::new(), "https://example.org", "zz"),
+            Err(DrandClientError::InvalidChainInfo)
+        );
+
+        // a period that drand's 32 bit hash would truncate to the real one
+        let (mut transport, chain_hash) = relay_for_verify_latest(&signer);
+        let info_url = format!("https://example.org/{chain_hash}/info");
+        let mut chain_info: ChainInfo =
+            serde_json::from_str(&transport.fetch(&info_url).unwrap()).unwrap();
+        chain_info.period_seconds += 1 << 32;
+        transport.insert(&info_url, &serde_json::to_string(&chain_info).unwrap());
+        assert_eq!(
+            verify_latest_with(transport, "https://example.org", &chain_hash),
+            Err(DrandClientError::InvalidChainInfo)
+        );
+    }
+
+    #[test]
+    fn for_each_verified_stops_when_told_to() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let mut transport = InMemoryTransport::new();
+        for round in 1..=5 {
+            transport.insert(
+                &format!("https://example.org/public/{round}"),
+                &serde_json::to_string(&signer.beacon(round)).unwrap(),
+            );
+        }
+        let client = test_client(transport, signer.chain_info(1595431050, 30));
+
+        let mut rounds = Vec::new();
+        let result = client.for_each_verified(2, |beacon| {
+            rounds.push(beacon.round_number);
+            if rounds.len() == 3 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(rounds, vec![2, 3, 4]);
+        assert_eq!(client.latest_round().get(), 4);
+
+        // running past the last stored round fails on the missing round
+        let mut count = 0;
+        let result = client.for_each_verified(4, |_| {
+            count += 1;
+            ControlFlow::Continue(())
+        });
+        assert_eq!(
+            result,
+            Err(DrandClientError::NotResponding(TransportError::NotFound))
+        );
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn backfill_stops_at_current_round() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        // half way through round 5
+        let genesis_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            - 4 * 30
+            - 15;
+        let mut transport = InMemoryTransport::new();
+        for round in 1..=6 {
+            transport.insert(
+                &format!("https://example.org/public/{round}"),
+                &serde_json::to_string(&signer.beacon(round)).unwrap(),
+            );
+        }
+        let client = test_client(transport, signer.chain_info(genesis_time, 30));
+
+        let rounds: Vec<u64> = client
+            .backfill_from(3)
+            .map(|beacon| beacon.unwrap().round_number)
+            .collect();
+        assert_eq!(rounds, vec![3, 4, 5]);
+        assert_eq!(client.backfill_from(6).count(), 0);
+    }
+
+    #[test]
+    fn backfill_stops_at_first_failure() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let genesis_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            - 4 * 30
+            - 15;
+        let mut transport = InMemoryTransport::new();
+        transport.insert(
+            "https://example.org/public/1",
+            &serde_json::to_string(&signer.beacon(1)).unwrap(),
+        );
+        let client = test_client(transport, signer.chain_info(genesis_time, 30));
+
+        let results: Vec<_> = client.backfill_from(1).collect();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0], Ok(signer.beacon(1)));
+        assert!(matches!(
+            results[1],
+            Err(DrandClientError::NotResponding(TransportError::NotFound))
+        ));
+    }
+
+    #[test]
+    fn watched_beacons_are_broadcast_to_every_subscriber() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let genesis_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            - 10;
+        let mut transport = InMemoryTransport::new();
+        for round in 1..=20 {
+            transport.insert(
+                &format!("https://example.org/public/{round}"),
+                &serde_json::to_string(&signer.beacon(round)).unwrap(),
+            );
+        }
+        let client = test_client(transport, signer.chain_info(genesis_time, 1));
+        let latest_round = client.latest_round();
+        let subscribers = [latest_round.subscribe(), latest_round.subscribe()];
+
+        let watched: Vec<Beacon> = client.watch().take(3).map(Result::unwrap).collect();
+
+        assert_eq!(latest_round.get(), watched[2].round_number);
+        assert_eq!(
+            watched[1].round_number,
+            watched[0].round_number + 1,
+            "rounds should be watched in order"
+        );
+        thread::scope(|s| {
+            for subscriber in subscribers {
+                let watched = &watched;
+                s.spawn(move || {
+                    let received: Vec<Beacon> = subscriber.iter().take(3).collect();
+                    assert_eq!(&received, watched);
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn watch_ends_when_stopped_from_another_thread() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let genesis_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            - 10;
+        let mut transport = InMemoryTransport::new();
+        for round in 1..=20 {
+            transport.insert(
+                &format!("https://example.org/public/{round}"),
+                &serde_json::to_string(&signer.beacon(round)).unwrap(),
+            );
+        }
+        let requests = AtomicUsize::new(0);
+        let counting = new_fn_transport(|url: &str| {
+            requests.fetch_add(1, SeqCst);
+            transport.fetch(url)
+        });
+        let client = test_client(counting, signer.chain_info(genesis_time, 60));
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut watched = client.watch_until(stop.clone());
+
+        assert_eq!(watched.next(), Some(Ok(signer.beacon(1))));
+
+        // the next round is ~50 seconds away, so only the flag can end the wait promptly
+        let started = Instant::now();
+        thread::scope(|s| {
+            s.spawn(|| {
+                thread::sleep(Duration::from_millis(100));
+                stop.store(true, SeqCst);
+            });
+            assert_eq!(watched.next(), None);
+        });
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // once ended, it stays ended without fetching anything more
+        let made = requests.load(SeqCst);
+        assert_eq!(watched.next(), None);
+        assert_eq!(requests.load(SeqCst), made);
+    }
+
+    #[test]
+    fn watched_beacons_are_sent_in_round_order() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let genesis_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            - 10;
+        let mut transport = InMemoryTransport::new();
+        for round in 1..=20 {
+            transport.insert(
+                &format!("https://example.org/public/{round}"),
+                &serde_json::to_string(&signer.beacon(round)).unwrap(),
+            );
+        }
+        let client = test_client(transport, signer.chain_info(genesis_time, 1));
+
+        let (sender, receiver) = mpsc::channel();
+        thread::scope(|s| {
+            let watching = s.spawn(|| client.watch_into(sender));
+
+            let received: Vec<u64> = receiver
+                .iter()
+                .take(3)
+                .map(|beacon| beacon.round_number)
+                .collect();
+            assert_eq!(
+                received,
+                vec![received[0], received[0] + 1, received[0] + 2]
+            );
+
+            // the loop ends once it next tries to send
+            drop(receiver);
+            watching.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn get_many_preserves_order_and_bounds_concurrency() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let mut beacons = InMemoryTransport::new();
+        for round in 1..=12 {
+            beacons.insert(
+                &format!("https://example.org/public/{round}"),
+                &serde_json::to_string(&signer.beacon(round)).unwrap(),
+            );
+        }
+        let client = test_client(
+            ConcurrencyTrackingTransport {
+                transport: beacons,
+                in_flight: AtomicUsize::new(0),
+                max_in_flight: AtomicUsize::new(0),
+            },
+            signer.chain_info(1595431050, 30),
+        );
+
+        let rounds = [9, 2, 12, 5, 13, 1, 7, 3, 11, 4];
+        let results = client.get_many(&rounds, 3);
+
+        assert_eq!(results.len(), rounds.len());
+        for (round, result) in rounds.iter().zip(&results) {
+            match round {
+                13 => assert!(matches!(
+                    result,
+                    Err(DrandClientError::NotResponding(TransportError::NotFound))
+                )),
+                _ => assert_eq!(result, &Ok(signer.beacon(*round))),
+            }
+        }
+        // the bound, not whether it was reached, as that depends on how threads are scheduled
+        let max_in_flight = client.transport.max_in_flight.load(SeqCst);
+        assert!((1..=3).contains(&max_in_flight));
+        assert!(client.get_many(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn get_by_deadline_gives_up_on_slow_transport() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let body = serde_json::to_string(&signer.beacon(5)).unwrap();
+        let client = test_client(
+            SlowTransport {
+                delay: Duration::from_millis(200),
+                beacon: &body,
+            },
+            signer.chain_info(1692803367, 3),
+        );
+
+        assert_eq!(
+            client.get_by_deadline(5, Instant::now() + Duration::from_millis(50)),
+            Err(DrandClientError::DeadlineExceeded)
+        );
+        assert_eq!(
+            client.get_by_deadline(5, Instant::now() + Duration::from_secs(10)),
+            Ok(signer.beacon(5))
+        );
+    }
+
+    #[test]
+    fn beacon_with_proof_verifies_in_isolation() -> Result<(), DrandClientError> {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let body = serde_json::to_string(&signer.beacon(5)).unwrap();
+        let client = test_client(
+            MockTransport { beacon: &body },
+            signer.chain_info(1595431050, 30),
+        );
+
+        let bundle = serde_json::to_string(&client.get_with_proof(5)?).unwrap();
+        let bundle: VerifiableBeacon = serde_json::from_str(&bundle).unwrap();
+
+        assert_eq!(bundle.beacon, signer.beacon(5));
+        assert_eq!(bundle.chain_info, signer.chain_info(1595431050, 30));
+        assert_eq!(bundle.verify(), Ok(()));
+
+        let mut tampered = bundle;
+        tampered.beacon.round_number = 6;
+        assert_eq!(
+            tampered.verify(),
+            Err(VerificationError::SignatureFailedVerification)
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn bundle_round_trips_through_cbor() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsChained);
+        let bundle = VerifiableBeacon {
+            beacon: signer.chained_beacon(5, &[0x6e; 96]),
+            chain_info: signer.chain_info(1595431050, 30),
+        };
+
+        let bytes = bundle.to_cbor().unwrap();
+        let decoded = VerifiableBeacon::from_cbor(&bytes).unwrap();
+
+        assert_eq!(decoded, bundle);
+        assert_eq!(decoded.verify(), Ok(()));
+        assert_eq!(
+            VerifiableBeacon::from_cbor(&bytes[..bytes.len() - 1]),
+            Err(DrandClientError::InvalidBeacon)
+        );
+    }
+
+    #[test]
+    fn beacon_is_described() -> Result<(), DrandClientError> {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let beacon = signer.beacon(5);
+        let body = serde_json::to_string(&beacon).unwrap();
+        let client = test_client(
+            MockTransport { beacon: &body },
+            signer.chain_info(1595431050, 30),
+        );
+
+        let report = client.describe(5)?;
+
+        assert!(report.contains("round: 5\n"));
+        assert!(report.contains("emitted at: 2020-07-22T15:19:30Z (unix time 1595431170)"));
+        assert!(report.contains(&hex::encode(&beacon.randomness)));
+        assert!(report.contains(&hex::encode(&beacon.signature)));
+        assert!(report.contains("scheme: bls-unchained-g1-rfc9380"));
+        assert!(report.contains("verified"));
+        Ok(())
+    }
+
+    #[test]
+    fn timestamps_are_formatted_in_utc() {
+        assert_eq!(format_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_utc(951782400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_utc(1692803367), "2023-08-23T15:09:27Z");
+    }
+
+    #[test]
+    fn beacon_id_urls_are_built() {
+        let template = UrlTemplate::BeaconId("quicknet".to_string());
+
+        assert_eq!(
+            template.info_url("https://example.org"),
+            "https://example.org/v2/beacons/quicknet/info"
+        );
+        assert_eq!(
+            template.beacon_url("https://example.org", "42"),
+            "https://example.org/v2/beacons/quicknet/rounds/42"
+        );
+        assert_eq!(
+            UrlTemplate::ChainHash.beacon_url("https://example.org", "latest"),
+            "https://example.org/public/latest"
+        );
+    }
+
+    #[test]
+    fn named_beacon_is_fetched_and_metadata_validated() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let mut info = signer.chain_info(1595431050, 3);
+        info.metadata.beacon_id = "quicknet".to_string();
+        let info_json = serde_json::json!({
+            "public_key": hex::encode(&info.public_key),
+            "period": 3,
+            "genesis_time": 1595431050,
+            "hash": hex::encode(&info.chain_hash),
+            "groupHash": hex::encode(&info.group_hash),
+            "schemeID": "bls-unchained-g1-rfc9380",
+            "metadata": { "beaconID": "quicknet" }
+        });
+        let beacon_with_id = |round: u64, beacon_id: &str| {
+            let mut beacon = serde_json::to_value(signer.beacon(round)).unwrap();
+            beacon["metadata"] = serde_json::json!({ "beaconID": beacon_id });
+            beacon.to_string()
+        };
+        let base = "https://example.org/v2/beacons/quicknet";
+        let mut transport = InMemoryTransport::new();
+        transport.insert(&format!("{base}/info"), &info_json.to_string());
+        transport.insert(&format!("{base}/rounds/1"), &beacon_with_id(1, "quicknet"));
+        transport.insert(&format!("{base}/rounds/2"), &beacon_with_id(2, "default"));
+
+        let client = new_client_with_url_template(
+            transport.clone(),
+            "https://example.org",
+            UrlTemplate::BeaconId("quicknet".to_string()),
+        )
+        .unwrap();
+
+        assert_eq!(client.randomness(1), Ok(signer.beacon(1)));
+        assert_eq!(
+            client.randomness(2),
+            Err(DrandClientError::BeaconIdMismatch {
+                expected: "quicknet".to_string(),
+                actual: "default".to_string(),
+            })
+        );
+
+        transport.insert(
+            "https://example.org/v2/beacons/evmnet/info",
+            &info_json.to_string(),
+        );
+        assert!(matches!(
+            new_client_with_url_template(
+                transport,
+                "https://example.org",
+                UrlTemplate::BeaconId("evmnet".to_string()),
+            ),
+            Err(DrandClientError::BeaconIdMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn archive_range_writes_verified_beacons() -> Result<(), DrandClientError> {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let mut transport = InMemoryTransport::new();
+        for round in 1..=4 {
+            let beacon = serde_json::to_string(&signer.beacon(round)).unwrap();
+            transport.insert(&format!("https://example.org/public/{round}"), &beacon);
+        }
+        let client = test_client(transport, signer.chain_info(1595431050, 30));
+
+        let mut archive = Vec::new();
+        let written = client.archive_range(2..=4, &mut archive)?;
+
+        assert_eq!(written, 3);
+        let beacons: Vec<Beacon> = String::from_utf8(archive)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(
+            beacons,
+            vec![signer.beacon(2), signer.beacon(3), signer.beacon(4)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn archive_range_stops_at_first_failure() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let mut transport = InMemoryTransport::new();
+        let mut invalid = signer.beacon(2);
+        invalid.signature = signer.beacon(3).signature;
+        let beacons = [signer.beacon(1), invalid, signer.beacon(3)];
+        for beacon in beacons {
+            transport.insert(
+                &format!("https://example.org/public/{}", beacon.round_number),
+                &serde_json::to_string(&beacon).unwrap(),
+            );
+        }
+        let client = test_client(transport, signer.chain_info(1595431050, 30));
+
+        let mut archive = Vec::new();
+        let result = client.archive_range(1..=3, &mut archive);
+
+        assert_eq!(
+            result,
+            Err(DrandClientError::FailedVerification(
+                VerificationError::InvalidRandomness
+            ))
+        );
+        assert_eq!(String::from_utf8(archive).unwrap().lines().count(), 1);
+    }
+
+    #[test]
+    fn empty_beacon_response_fails() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let client = test_client(
+            MockTransport { beacon: "  " },
+            signer.chain_info(1595431050, 30),
+        );
+
+        assert_eq!(client.randomness(1), Err(DrandClientError::EmptyResponse));
+    }
+
+    #[test]
+    fn beacon_without_signature_is_not_yet_available() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        for body in [
+            r#"{"round": 5}"#,
+            r#"{"round": 5, "randomness": ""}"#,
+            r#"{"round": 5, "randomness": "", "signature": ""}"#,
+        ] {
+            let client = test_client(
+                MockTransport { beacon: body },
+                signer.chain_info(1595431050, 30),
+            );
+
+            assert_eq!(
+                client.randomness(5),
+                Err(DrandClientError::RoundNotYetAvailable)
+            );
+        }
+
+        let client = test_client(
+            MockTransport {
+                beacon: r#"{"round": 5, "signature": "zz"}"#,
+            },
+            signer.chain_info(1595431050, 30),
+        );
+        assert_eq!(client.randomness(5), Err(DrandClientError::InvalidBeacon));
+    }
+
+    #[test]
+    fn wrapped_errors_are_exposed_as_sources() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let mut invalid = signer.beacon(1);
+        invalid.signature = signer.beacon(2).signature;
+        invalid.randomness = signer.beacon(2).randomness;
+        let body = serde_json::to_string(&invalid).unwrap();
+        let client = test_client(
+            MockTransport { beacon: &body },
+            signer.chain_info(1595431050, 30),
+        );
+
+        let err = client.randomness(1).unwrap_err();
+        let source = err
+            .source()
+            .and_then(|source| source.downcast_ref::<VerificationError>());
+        assert_eq!(
+            source,
+            Some(&VerificationError::SignatureFailedVerification)
+        );
+
+        let err = DrandClientError::NotResponding(TransportError::Timeout);
+        let source = err
+            .source()
+            .and_then(|source| source.downcast_ref::<TransportError>());
+        assert_eq!(source, Some(&TransportError::Timeout));
+    }
+
+    #[test]
+    fn beacon_with_matching_chain_hash_succeeds() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let info = signer.chain_info(1595431050, 30);
+        let mut response = serde_json::to_value(signer.beacon(1)).unwrap();
+        response["metadata"] = serde_json::json!({ "chainHash": hex::encode(&info.chain_hash) });
+        let body = response.to_string();
+        let client = test_client(MockTransport { beacon: &body }, info);
+
+        assert_eq!(client.randomness(1), Ok(signer.beacon(1)));
+    }
+
+    #[test]
+    fn beacon_with_mismatching_chain_hash_fails() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let info = signer.chain_info(1595431050, 30);
+        let other_chain = "8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce";
+        let mut response = serde_json::to_value(signer.beacon(1)).unwrap();
+        response["metadata"] = serde_json::json!({ "chainHash": other_chain });
+        let body = response.to_string();
+        let client = test_client(MockTransport { beacon: &body }, info.clone());
+
+        assert_eq!(
+            client.randomness(1),
+            Err(DrandClientError::ChainHashMismatch {
+                expected: hex::encode(&info.chain_hash),
+                actual: other_chain.to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn round_for_time_with_zero_period_fails() {
+        let mut info = TestSigner::new(SchemeID::PedersenBlsUnchained).chain_info(1595431050, 30);
+        info.period_seconds = 0;
+
+        assert_eq!(
+            round_for_time(&info, SystemTime::now()),
+            Err(DrandClientError::InvalidChainInfo)
+        );
+    }
+
+    #[test]
+    fn watch_with_an_overflowing_genesis_round_fails() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let mut info = signer.chain_info(1595431050, 30);
+        info.genesis_round = u64::MAX;
+        let client = test_client(InMemoryTransport::new(), info);
+
+        let mut watched = client.watch();
+        assert_eq!(
+            watched.next(),
+            Some(Err(DrandClientError::InvalidChainInfo))
+        );
+        assert_eq!(watched.next(), None);
+    }
+
+    /// a client for `transport` and `info` at `https://example.org`, with no caches or key
+    /// schedule, as `new_client` would build it without fetching the chain info
+    fn test_client<T: Transport>(transport: T, info: ChainInfo) -> DrandClient<'static, T> {
+        DrandClient {
+            transport,
+            base_url: "https://example.org",
+            chain_info: info,
+            reject_future_rounds: false,
+            url_template: UrlTemplate::ChainHash,
+            key_schedule: None,
+            verification_cache: None,
+            beacon_cache: None,
+            latest_round: new_latest_round(),
+        }
+    }
+
     struct MockTransport<'a> {
         beacon: &'a str,
     }
 
+    /// records the most requests it has had in flight at once
+    struct ConcurrencyTrackingTransport {
+        transport: InMemoryTransport,
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    impl Transport for ConcurrencyTrackingTransport {
+        fn fetch(&self, url: &str) -> Result<String, TransportError> {
+            let in_flight = self.in_flight.fetch_add(1, SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            self.in_flight.fetch_sub(1, SeqCst);
+            self.transport.fetch(url)
+        }
+    }
+
+    /// serves `beacon` after `delay`
+    struct SlowTransport<'a> {
+        delay: Duration,
+        beacon: &'a str,
+    }
+
+    impl Transport for SlowTransport<'_> {
+        fn fetch(&self, _: &str) -> Result<String, TransportError> {
+            thread::sleep(self.delay);
+            Ok(self.beacon.to_string())
+        }
+    }
+
     impl Transport for MockTransport<'_> {
         fn fetch(&self, _: &str) -> Result<String, TransportError> {
             Ok(self.beacon.to_string())
@@ -0,0 +1,151 @@
+//! # migration
+//!
+//! verifying the beacons of a network that migrated to a new chain, e.g. with a new chain hash,
+//! where each chain produced the beacons for its own range of rounds
+//!
+
+use crate::chain_info::ChainInfo;
+use crate::verify::{Beacon, VerificationError};
+use std::ops::RangeInclusive;
+use thiserror::Error;
+
+/// the chains of a network and the rounds each of them produced
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ChainMigration {
+    chains: Vec<(RangeInclusive<u64>, ChainInfo)>,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum MigrationError {
+    #[error("rounds {start}..={end} overlap the rounds of another chain")]
+    OverlappingRounds { start: u64, end: u64 },
+    #[error("no chain produced round {round}")]
+    NoChainForRound { round: u64 },
+    #[error("beacon failed verification")]
+    FailedVerification(#[from] VerificationError),
+}
+
+/// create a migration without any chains, to be added with [ChainMigration::add]
+pub fn new_chain_migration() -> ChainMigration {
+    ChainMigration::default()
+}
+
+impl ChainMigration {
+    /// record that `chain_info` describes the chain that produced `rounds`.
+    /// Each round can only belong to one chain.
+    pub fn add(
+        &mut self,
+        rounds: RangeInclusive<u64>,
+        chain_info: ChainInfo,
+    ) -> Result<(), MigrationError> {
+        let overlaps = self.chains.iter().any(|(existing, _)| {
+            rounds.start() <= existing.end() && existing.start() <= rounds.end()
+        });
+        if overlaps {
+            return Err(MigrationError::OverlappingRounds {
+                start: *rounds.start(),
+                end: *rounds.end(),
+            });
+        }
+
+        self.chains.push((rounds, chain_info));
+        Ok(())
+    }
+
+    /// the chain info of the chain that produced `round`, if any
+    pub fn chain_info_for_round(&self, round: u64) -> Option<&ChainInfo> {
+        self.chains
+            .iter()
+            .find(|(rounds, _)| rounds.contains(&round))
+            .map(|(_, chain_info)| chain_info)
+    }
+
+    /// verify `beacon` against the chain that produced its round
+    pub fn verify(&self, beacon: &Beacon) -> Result<(), MigrationError> {
+        let chain_info = self.chain_info_for_round(beacon.round_number).ok_or(
+            MigrationError::NoChainForRound {
+                round: beacon.round_number,
+            },
+        )?;
+        chain_info.verify(beacon)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::migration::{new_chain_migration, MigrationError};
+    use crate::test_util::TestSigner;
+    use crate::verify::{SchemeID, VerificationError};
+
+    #[test]
+    fn beacons_verify_against_chain_for_their_round() {
+        let old = TestSigner::with_secret(SchemeID::PedersenBlsChained, 1);
+        let new = TestSigner::with_secret(SchemeID::UnchainedOnG1RFC9380, 2);
+        let mut migration = new_chain_migration();
+        migration
+            .add(1..=999, old.chain_info(1595431050, 30))
+            .unwrap();
+        migration
+            .add(1000..=u64::MAX, new.chain_info(1692803367, 3))
+            .unwrap();
+
+        assert_eq!(migration.verify(&old.beacon(999)), Ok(()));
+        assert_eq!(migration.verify(&new.beacon(1000)), Ok(()));
+        assert_eq!(
+            migration.verify(&new.beacon(999)),
+            Err(MigrationError::FailedVerification(
+                VerificationError::ChainedBeaconNeedsPreviousSignature
+            ))
+        );
+        assert_eq!(
+            migration.verify(&old.beacon(1000)),
+            Err(MigrationError::FailedVerification(
+                VerificationError::UnexpectedPreviousSignature
+            ))
+        );
+    }
+
+    #[test]
+    fn beacons_verify_from_each_chains_genesis_round() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let mut chain_info = signer.chain_info(1692803367, 3);
+        chain_info.genesis_round = 0;
+        let mut migration = new_chain_migration();
+        migration.add(0..=u64::MAX, chain_info).unwrap();
+
+        assert_eq!(migration.verify(&signer.beacon(0)), Ok(()));
+    }
+
+    #[test]
+    fn rounds_outside_every_chain_are_rejected() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let mut migration = new_chain_migration();
+        migration
+            .add(10..=20, signer.chain_info(1595431050, 30))
+            .unwrap();
+
+        assert_eq!(
+            migration.verify(&signer.beacon(21)),
+            Err(MigrationError::NoChainForRound { round: 21 })
+        );
+    }
+
+    #[test]
+    fn overlapping_chains_are_rejected() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let mut migration = new_chain_migration();
+        migration
+            .add(10..=20, signer.chain_info(1595431050, 30))
+            .unwrap();
+
+        assert_eq!(
+            migration.add(20..=30, signer.chain_info(1595431050, 30)),
+            Err(MigrationError::OverlappingRounds { start: 20, end: 30 })
+        );
+        assert_eq!(
+            migration.add(21..=30, signer.chain_info(1595431050, 30)),
+            Ok(())
+        );
+    }
+}
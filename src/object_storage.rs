@@ -0,0 +1,164 @@
+//! # object_storage
+//!
+//! a transport reading beacons from an archive in object storage, e.g. S3, so archived beacons
+//! can be verified by the same client as beacons from a relay
+//!
+
+use crate::{AsyncTransport, Transport, TransportError};
+use object_store::path::Path;
+use object_store::ObjectStore;
+use tokio::runtime::Handle;
+
+/// a transport fetching each URL from the object with the same path in `store`, e.g. a client
+/// with a `base_url` of `archives/quicknet` reads round 5 from `archives/quicknet/public/5`
+/// and the chain info from `archives/quicknet/info`.
+///
+/// Blocking fetches drive the store's future on the calling thread, which only works for
+/// stores that don't need an async runtime, such as `InMemory` and `LocalFileSystem`. Stores
+/// built on tokio, such as the S3 store, need a runtime given with
+/// [with_runtime](ObjectStoreTransport::with_runtime).
+pub struct ObjectStoreTransport<S> {
+    store: S,
+    runtime: Option<Handle>,
+}
+
+/// create a transport reading objects from `store`
+pub fn new_object_store_transport<S: ObjectStore>(store: S) -> ObjectStoreTransport<S> {
+    ObjectStoreTransport {
+        store,
+        runtime: None,
+    }
+}
+
+impl<S> ObjectStoreTransport<S> {
+    /// drive blocking fetches on the tokio `runtime`, e.g. the handle of the multi-threaded
+    /// runtime the S3 store was built in. A current-thread runtime only makes progress while
+    /// it's blocked on elsewhere, so fetches through it hang otherwise. Like `Handle::block_on`,
+    /// blocking fetches panic if called from within an async context; use `AsyncTransport`
+    /// there instead.
+    pub fn with_runtime(mut self, runtime: Handle) -> Self {
+        self.runtime = Some(runtime);
+        self
+    }
+}
+
+fn unexpected(e: impl ToString) -> TransportError {
+    TransportError::Unexpected {
+        status: None,
+        body: e.to_string(),
+    }
+}
+
+impl<S: ObjectStore> AsyncTransport for ObjectStoreTransport<S> {
+    async fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        let path = Path::parse(url.trim_start_matches('/')).map_err(unexpected)?;
+        let bytes = match self.store.get(&path).await {
+            Ok(object) => object.bytes().await.map_err(unexpected)?,
+            Err(object_store::Error::NotFound { .. }) => return Err(TransportError::NotFound),
+            Err(e) => return Err(unexpected(e)),
+        };
+
+        let body = String::from_utf8(bytes.to_vec()).map_err(unexpected)?;
+        if body.trim().is_empty() {
+            return Err(TransportError::EmptyBody);
+        }
+        Ok(body)
+    }
+}
+
+impl<S: ObjectStore> Transport for ObjectStoreTransport<S> {
+    fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        let fetch = AsyncTransport::fetch(self, url);
+        match &self.runtime {
+            Some(runtime) => runtime.block_on(fetch),
+            None => futures::executor::block_on(fetch),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test {
+    use crate::object_storage::new_object_store_transport;
+    use crate::test_util::{block_on, TestSigner};
+    use crate::verify::SchemeID;
+    use crate::{new_client, DrandClientError, Transport, TransportError};
+    use object_store::memory::InMemory;
+    use object_store::path::Path;
+    use object_store::throttle::{ThrottleConfig, ThrottledStore};
+    use object_store::ObjectStore;
+    use std::time::Duration;
+
+    fn put(store: &InMemory, path: &str, body: String) {
+        block_on(store.put(&Path::parse(path).unwrap(), body.into())).unwrap();
+    }
+
+    #[test]
+    fn archived_beacons_verify() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let store = InMemory::new();
+        put(
+            &store,
+            "archives/quicknet/info",
+            serde_json::to_string(&signer.chain_info(1692803367, 3)).unwrap(),
+        );
+        for round in 1..=3 {
+            put(
+                &store,
+                &format!("archives/quicknet/public/{round}"),
+                serde_json::to_string(&signer.beacon(round)).unwrap(),
+            );
+        }
+
+        let client = new_client(new_object_store_transport(store), "archives/quicknet").unwrap();
+
+        assert_eq!(client.randomness(2), Ok(signer.beacon(2)));
+        assert_eq!(
+            client.randomness(4),
+            Err(DrandClientError::NotResponding(TransportError::NotFound))
+        );
+    }
+
+    #[test]
+    fn stores_needing_a_runtime_are_driven_on_it() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let store = InMemory::new();
+        put(
+            &store,
+            "info",
+            serde_json::to_string(&signer.chain_info(1692803367, 3)).unwrap(),
+        );
+        put(
+            &store,
+            "public/2",
+            serde_json::to_string(&signer.beacon(2)).unwrap(),
+        );
+        // the throttled store sleeps with tokio's timer, like the network stores use tokio's IO
+        let store = ThrottledStore::new(
+            store,
+            ThrottleConfig {
+                wait_get_per_call: Duration::from_millis(1),
+                ..ThrottleConfig::default()
+            },
+        );
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_time()
+            .build()
+            .unwrap();
+
+        let transport = new_object_store_transport(store).with_runtime(runtime.handle().clone());
+        let client = new_client(transport, "").unwrap();
+
+        assert_eq!(client.randomness(2), Ok(signer.beacon(2)));
+    }
+
+    #[test]
+    fn missing_and_empty_objects_fail() {
+        let store = InMemory::new();
+        put(&store, "empty", String::new());
+        let transport = new_object_store_transport(store);
+
+        assert_eq!(transport.fetch("missing"), Err(TransportError::NotFound));
+        assert_eq!(transport.fetch("/empty"), Err(TransportError::EmptyBody));
+    }
+}
@@ -0,0 +1,306 @@
+//! # randomness
+//!
+//! helpers for working with the randomness of beacons once they have been verified
+//!
+
+use crate::verify::{verify_beacon, Beacon, SchemeID, VerificationError};
+use sha2::{Digest, Sha256};
+
+/// the 32 bytes of randomness of a verified beacon
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Randomness([u8; 32]);
+
+impl Randomness {
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    /// the first 8 bytes of the randomness as a big-endian `u64`
+    pub fn to_u64(&self) -> u64 {
+        u64::from_be_bytes(self.0[..8].try_into().unwrap())
+    }
+
+    /// the randomness as a 32 byte seed, e.g. for `rand::SeedableRng::from_seed`
+    pub fn to_seed(&self) -> [u8; 32] {
+        self.0
+    }
+
+    /// a sanity check for randomness that is valid but looks like a constant rather than a hash
+    /// output, e.g. all zeros, all ones, a short repeating pattern or a counting sequence, which
+    /// would point to a bug upstream. A sha256 output with fewer than 8 distinct bytes is so
+    /// unlikely that this never flags real randomness in practice.
+    /// This is only a warning sign: it doesn't mean the beacon failed verification.
+    pub fn looks_degenerate(&self) -> bool {
+        let mut seen = [false; 256];
+        for byte in self.0 {
+            seen[byte as usize] = true;
+        }
+        let distinct = seen.iter().filter(|seen| **seen).count();
+
+        let step = self.0[1].wrapping_sub(self.0[0]);
+        let counting = self
+            .0
+            .windows(2)
+            .all(|pair| pair[1].wrapping_sub(pair[0]) == step);
+
+        distinct < 8 || counting
+    }
+
+    /// a ChaCha20 RNG seeded with the randomness, for drawing reproducible values tied to the
+    /// round. The seed is the 32 bytes of randomness as is, i.e.
+    /// `ChaCha20Rng::from_seed(randomness.to_seed())`, so any ChaCha20 implementation seeded
+    /// the same way produces the same stream.
+    #[cfg(feature = "rand")]
+    pub fn into_rng(&self) -> rand_chacha::ChaCha20Rng {
+        use rand_chacha::rand_core::SeedableRng;
+        rand_chacha::ChaCha20Rng::from_seed(self.to_seed())
+    }
+}
+
+/// verify a beacon for a given scheme and public key and return its randomness
+pub fn verify_randomness(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    beacon: &Beacon,
+) -> Result<Randomness, VerificationError> {
+    verify_beacon(scheme_id, public_key, beacon)?;
+    // verification checked the randomness is a sha256 digest, so it is 32 bytes
+    let randomness = beacon
+        .randomness
+        .as_slice()
+        .try_into()
+        .map_err(|_| VerificationError::InvalidRandomness)?;
+    Ok(Randomness(randomness))
+}
+
+/// a compact set of randomness values for fast "have I seen this" checks, built on a bloom
+/// filter. `contains` never misses a value that was inserted, but may claim to contain a value
+/// that wasn't, at roughly the false positive rate the set was created with for as long as it
+/// holds no more than its expected number of values, and more often beyond that. A `true` is
+/// only a hint to confirm with an exact lookup; a `false` is definite.
+#[derive(Debug, Clone)]
+pub struct RandomnessSet {
+    bits: Vec<u64>,
+    hash_count: u32,
+}
+
+/// create a set sized to hold `expected_values` with a false positive rate of about
+/// `false_positive_rate`, e.g. `0.01` for 1%
+pub fn new_randomness_set(expected_values: usize, false_positive_rate: f64) -> RandomnessSet {
+    let n = expected_values.max(1) as f64;
+    let p = false_positive_rate.clamp(f64::MIN_POSITIVE, 0.5);
+    let ln2 = std::f64::consts::LN_2;
+    let bit_count = (-n * p.ln() / (ln2 * ln2)).ceil().max(64.0);
+    let hash_count = (bit_count / n * ln2).round().max(1.0);
+
+    RandomnessSet {
+        bits: vec![0; (bit_count as usize).div_ceil(64)],
+        hash_count: hash_count as u32,
+    }
+}
+
+impl RandomnessSet {
+    pub fn insert(&mut self, randomness: &Randomness) {
+        for bit in self.bit_indices(randomness) {
+            self.bits[bit / 64] |= 1 << (bit % 64);
+        }
+    }
+
+    /// whether `randomness` may have been inserted; see [RandomnessSet] for false positives
+    pub fn contains(&self, randomness: &Randomness) -> bool {
+        self.bit_indices(randomness)
+            .all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    /// randomness is already a uniform hash output, so its bytes are used as the two hashes
+    /// that the filter's bit indices are derived from, rather than hashing it again
+    fn bit_indices(&self, randomness: &Randomness) -> impl Iterator<Item = usize> {
+        let bytes = randomness.as_bytes();
+        let first = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+        let second = u64::from_le_bytes(bytes[8..16].try_into().unwrap()) | 1;
+        let bit_count = (self.bits.len() * 64) as u64;
+        (0..u64::from(self.hash_count))
+            .map(move |i| (first.wrapping_add(i.wrapping_mul(second)) % bit_count) as usize)
+    }
+}
+
+/// combine the randomness of several beacons into a single value, for protocols that draw
+/// on a range of rounds. The result is `sha256(r_1 || r_2 || ... || r_n)`, where `r_i` is the
+/// randomness of the beacons sorted by ascending round number, so the order of `beacons`
+/// doesn't matter. Beacons appearing more than once are included each time.
+/// This doesn't verify anything: each beacon should be verified before combining.
+pub fn combine_randomness(beacons: &[Beacon]) -> [u8; 32] {
+    let mut sorted: Vec<&Beacon> = beacons.iter().collect();
+    sorted.sort_by_key(|beacon| beacon.round_number);
+
+    let mut hasher = Sha256::new();
+    for beacon in sorted {
+        hasher.update(&beacon.randomness);
+    }
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::randomness::{
+        combine_randomness, new_randomness_set, verify_randomness, Randomness,
+    };
+    use crate::test_util::TestSigner;
+    use crate::verify::{Beacon, SchemeID, VerificationError};
+
+    #[test]
+    fn combined_randomness_is_hash_of_randomness_in_round_order() {
+        let beacons = [
+            Beacon {
+                round_number: 397092,
+                randomness: hex::decode(
+                    "7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796",
+                )
+                .unwrap(),
+                signature: Vec::new(),
+                previous_signature: Vec::new(),
+            },
+            Beacon {
+                round_number: 397089,
+                randomness: hex::decode(
+                    "cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42",
+                )
+                .unwrap(),
+                signature: Vec::new(),
+                previous_signature: Vec::new(),
+            },
+        ];
+
+        let combined = combine_randomness(&beacons);
+
+        assert_eq!(
+            hex::encode(combined),
+            "87becb8c8f232d34f315c16e85a459f920286a9fbcfc3c652dcdd1a209f76f3c"
+        );
+        assert_eq!(
+            combine_randomness(&[beacons[1].clone(), beacons[0].clone()]),
+            combined
+        );
+    }
+
+    #[test]
+    fn verified_randomness_converts() {
+        let public_key = hex::decode("8d91ae0f4e3cd277cfc46aba26680232b0d5bb4444602cdb23442d62e17f43cdffb1104909e535430c10a6a1ce680a65").unwrap();
+        let beacon = Beacon {
+            round_number: 397092,
+            randomness: hex::decode("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796").unwrap(),
+            signature: hex::decode("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539").unwrap(),
+            previous_signature: Vec::new(),
+        };
+
+        let randomness =
+            verify_randomness(&SchemeID::PedersenBlsUnchained, &public_key, &beacon).unwrap();
+
+        assert_eq!(
+            randomness.as_bytes().as_slice(),
+            beacon.randomness.as_slice()
+        );
+        assert_eq!(
+            randomness.to_hex(),
+            "7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"
+        );
+        assert_eq!(randomness.to_u64(), 0x7731783ab8118d74);
+        assert_eq!(randomness.to_seed(), *randomness.as_bytes());
+    }
+
+    #[test]
+    fn unverified_randomness_is_not_returned() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1);
+        let mut beacon = signer.beacon(2);
+        beacon.round_number = 3;
+
+        assert_eq!(
+            verify_randomness(&SchemeID::UnchainedOnG1, &signer.public_key(), &beacon),
+            Err(VerificationError::SignatureFailedVerification)
+        );
+    }
+
+    #[test]
+    fn real_randomness_does_not_look_degenerate() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        for round in 1..=20 {
+            let randomness = verify_randomness(
+                &SchemeID::PedersenBlsUnchained,
+                &signer.public_key(),
+                &signer.beacon(round),
+            )
+            .unwrap();
+            assert!(!randomness.looks_degenerate());
+        }
+    }
+
+    #[test]
+    fn constant_randomness_looks_degenerate() {
+        let mut counting = [0u8; 32];
+        for (i, byte) in counting.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let mut repeating = [0u8; 32];
+        for (i, byte) in repeating.iter_mut().enumerate() {
+            *byte = [0xde, 0xad, 0xbe, 0xef][i % 4];
+        }
+
+        assert!(Randomness([0; 32]).looks_degenerate());
+        assert!(Randomness([0xff; 32]).looks_degenerate());
+        assert!(Randomness(counting).looks_degenerate());
+        assert!(Randomness(repeating).looks_degenerate());
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn same_randomness_seeds_same_rng() {
+        use rand_chacha::rand_core::RngCore;
+
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1);
+        let randomness = verify_randomness(
+            &SchemeID::UnchainedOnG1,
+            &signer.public_key(),
+            &signer.beacon(5),
+        )
+        .unwrap();
+        let other = verify_randomness(
+            &SchemeID::UnchainedOnG1,
+            &signer.public_key(),
+            &signer.beacon(6),
+        )
+        .unwrap();
+
+        let draws = |randomness: &Randomness| {
+            let mut rng = randomness.into_rng();
+            [rng.next_u64(), rng.next_u64(), rng.next_u64()]
+        };
+        assert_eq!(draws(&randomness), draws(&randomness));
+        assert_ne!(draws(&randomness), draws(&other));
+    }
+
+    #[test]
+    fn randomness_set_contains_inserted_values() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let randomness = |round: u64| {
+            verify_randomness(
+                &SchemeID::UnchainedOnG1RFC9380,
+                &signer.public_key(),
+                &signer.beacon(round),
+            )
+            .unwrap()
+        };
+        let mut set = new_randomness_set(100, 0.001);
+
+        for round in 1..=20 {
+            set.insert(&randomness(round));
+        }
+
+        assert!((1..=20).all(|round| set.contains(&randomness(round))));
+        assert!(!set.contains(&randomness(21)));
+        assert!(!new_randomness_set(100, 0.001).contains(&randomness(1)));
+    }
+}
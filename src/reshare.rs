@@ -0,0 +1,306 @@
+//! # reshare
+//!
+//! checking the transcript of a reshare, where a network hands its key over to a new set of
+//! nodes, so auditors can confirm the new group still holds the key its beacons are signed with
+//!
+
+use crate::verify::{decode_g1_public_key, decode_g2_public_key, VerificationError};
+use bls12_381::{G1Projective, G2Projective, Scalar};
+use std::ops::{Add, Mul};
+
+/// the public transcript of a reshare, as drand runs it: each dealer is a node of the old group,
+/// which deals its share of the group's secret key to the new group with a fresh polynomial
+/// whose constant term is that share. The new group's public polynomial is then the Lagrange
+/// combination, at 0, of the polynomials of the old threshold of dealers with the lowest
+/// indices, so its constant term is the old group's key.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ReshareProof {
+    pub dealers: Vec<ReshareDealer>,
+}
+
+/// a dealer of a reshare and the commitments to the coefficients of the polynomial it dealt,
+/// lowest degree first. Commitments are compressed points on the same group as the group's
+/// public key, and each dealer has as many as the new group's threshold.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ReshareDealer {
+    /// the dealer's index in the old group, as in its group file, counting from 0. Its share is
+    /// the old group's polynomial evaluated at `index + 1`.
+    pub index: u32,
+    pub commitments: Vec<Vec<u8>>,
+}
+
+/// check `proof` hands the old group's key over to the new group. `old_polynomial` is the old
+/// group's public polynomial, e.g. the `PublicKey.Coefficients` of its group file, and must come
+/// from a trusted source, as it fixes the old threshold and the shares the dealers hold. The
+/// checks are that:
+///
+/// - at least the old threshold of dealers, with distinct indices, took part
+/// - each dealer's polynomial has as many coefficients as `new_polynomial`, and its constant
+///   term is the dealer's share of the old group's polynomial
+/// - `new_polynomial` is the Lagrange combination of the polynomials of the old threshold of
+///   dealers with the lowest indices, which also makes its first coefficient the old key
+///
+/// This shows the new group's public polynomial, and so its key, was dealt from the old group's
+/// shares, not that every node of the new group received its share.
+pub fn verify_reshare(
+    old_polynomial: &[Vec<u8>],
+    new_polynomial: &[Vec<u8>],
+    proof: &ReshareProof,
+) -> Result<(), VerificationError> {
+    let old_key = old_polynomial
+        .first()
+        .ok_or(VerificationError::InvalidPublicKey)?;
+    match old_key.len() {
+        48 => verify_polynomials(old_polynomial, new_polynomial, proof, |point| {
+            decode_g1_public_key(point).map(G1Projective::from)
+        }),
+        96 => verify_polynomials(old_polynomial, new_polynomial, proof, |point| {
+            decode_g2_public_key(point).map(G2Projective::from)
+        }),
+        _ => Err(VerificationError::InvalidPublicKey),
+    }
+}
+
+fn verify_polynomials<G>(
+    old_polynomial: &[Vec<u8>],
+    new_polynomial: &[Vec<u8>],
+    proof: &ReshareProof,
+    decode: impl Fn(&[u8]) -> Result<G, VerificationError>,
+) -> Result<(), VerificationError>
+where
+    G: Copy + PartialEq + Add<Output = G> + Mul<Scalar, Output = G>,
+{
+    let old_polynomial = old_polynomial
+        .iter()
+        .map(|coefficient| decode(coefficient))
+        .collect::<Result<Vec<_>, _>>()?;
+    let decode_all = |points: &[Vec<u8>]| {
+        points
+            .iter()
+            .map(|point| decode(point).map_err(|_| VerificationError::InvalidReshareProof))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let new_polynomial = decode_all(new_polynomial)?;
+    if new_polynomial.is_empty() {
+        return Err(VerificationError::InvalidReshareProof);
+    }
+
+    let mut dealers = proof
+        .dealers
+        .iter()
+        .map(|dealer| Ok((dealer.index, decode_all(&dealer.commitments)?)))
+        .collect::<Result<Vec<_>, VerificationError>>()?;
+    dealers.sort_by_key(|(index, _)| *index);
+    if dealers.len() < old_polynomial.len() || dealers.windows(2).any(|pair| pair[0].0 == pair[1].0)
+    {
+        return Err(VerificationError::InvalidReshareProof);
+    }
+
+    let share_point = |index: u32| Scalar::from(u64::from(index) + 1);
+    for (index, commitments) in &dealers {
+        if commitments.len() != new_polynomial.len()
+            || commitments[0] != evaluate(&old_polynomial, share_point(*index))
+        {
+            return Err(VerificationError::InvalidReshareProof);
+        }
+    }
+
+    let qualified = &dealers[..old_polynomial.len()];
+    let lambdas = lagrange_at_zero(
+        &qualified
+            .iter()
+            .map(|(index, _)| share_point(*index))
+            .collect::<Vec<_>>(),
+    )
+    .ok_or(VerificationError::InvalidReshareProof)?;
+    for (degree, coefficient) in new_polynomial.iter().enumerate() {
+        let combined = qualified
+            .iter()
+            .zip(&lambdas)
+            .map(|((_, commitments), lambda)| commitments[degree] * *lambda)
+            .reduce(|sum, term| sum + term);
+        if combined != Some(*coefficient) {
+            return Err(VerificationError::InvalidReshareProof);
+        }
+    }
+    Ok(())
+}
+
+/// evaluate the polynomial with `coefficients`, lowest degree first, at `x` in the exponent
+fn evaluate<G>(coefficients: &[G], x: Scalar) -> G
+where
+    G: Copy + Add<Output = G> + Mul<Scalar, Output = G>,
+{
+    let (highest, rest) = coefficients
+        .split_last()
+        .expect("polynomials have at least one coefficient");
+    rest.iter()
+        .rev()
+        .fold(*highest, |sum, coefficient| sum * x + *coefficient)
+}
+
+/// the Lagrange coefficients for interpolating at 0 from the points at `xs`. `None` if two
+/// points share an `x`.
+fn lagrange_at_zero(xs: &[Scalar]) -> Option<Vec<Scalar>> {
+    xs.iter()
+        .enumerate()
+        .map(|(i, x_i)| {
+            let mut coefficient = Scalar::one();
+            for (j, x_j) in xs.iter().enumerate() {
+                if i != j {
+                    coefficient *= x_j * Option::<Scalar>::from((x_j - x_i).invert())?;
+                }
+            }
+            Some(coefficient)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::reshare::{verify_reshare, ReshareDealer, ReshareProof};
+    use crate::verify::VerificationError;
+    use bls12_381::{G1Affine, G2Affine, Scalar};
+
+    /// the old group's secret polynomial, 7 + 3x + 5x², so its threshold is 3
+    const OLD_POLYNOMIAL: [u64; 3] = [7, 3, 5];
+
+    fn evaluate(coefficients: &[Scalar], x: Scalar) -> Scalar {
+        coefficients
+            .iter()
+            .rev()
+            .fold(Scalar::zero(), |sum, coefficient| sum * x + coefficient)
+    }
+
+    fn g1(secret: &Scalar) -> Vec<u8> {
+        G1Affine::from(G1Affine::generator() * secret)
+            .to_compressed()
+            .to_vec()
+    }
+
+    fn g2(secret: &Scalar) -> Vec<u8> {
+        G2Affine::from(G2Affine::generator() * secret)
+            .to_compressed()
+            .to_vec()
+    }
+
+    /// the secret polynomial the dealer at `index` deals to a new group of threshold 2: its
+    /// share of the old polynomial, plus a coefficient of its own
+    fn dealt(index: u32) -> Vec<Scalar> {
+        let old = OLD_POLYNOMIAL.map(Scalar::from);
+        let share = evaluate(&old, Scalar::from(u64::from(index) + 1));
+        vec![share, Scalar::from(11 + u64::from(index))]
+    }
+
+    /// the old and new public polynomials, and the proof, of a reshare by the old nodes at
+    /// `indices`, committed to with `commit`
+    fn reshare(
+        indices: &[u32],
+        commit: fn(&Scalar) -> Vec<u8>,
+    ) -> (Vec<Vec<u8>>, Vec<Vec<u8>>, ReshareProof) {
+        let old_polynomial = OLD_POLYNOMIAL.map(|c| commit(&Scalar::from(c))).to_vec();
+
+        let mut qualified = indices.to_vec();
+        qualified.sort();
+        qualified.truncate(OLD_POLYNOMIAL.len());
+        let xs: Vec<Scalar> = qualified
+            .iter()
+            .map(|&i| Scalar::from(u64::from(i) + 1))
+            .collect();
+        let mut new_secret = [Scalar::zero(); 2];
+        for (i, x_i) in qualified.iter().zip(&xs) {
+            let mut lambda = Scalar::one();
+            for x_j in xs.iter().filter(|x_j| *x_j != x_i) {
+                lambda *= x_j * (x_j - x_i).invert().unwrap();
+            }
+            for (sum, coefficient) in new_secret.iter_mut().zip(dealt(*i)) {
+                *sum += lambda * coefficient;
+            }
+        }
+        // the new group shares the old key
+        assert_eq!(new_secret[0], Scalar::from(OLD_POLYNOMIAL[0]));
+
+        let proof = ReshareProof {
+            dealers: indices
+                .iter()
+                .map(|&index| ReshareDealer {
+                    index,
+                    commitments: dealt(index).iter().map(commit).collect(),
+                })
+                .collect(),
+        };
+        (
+            old_polynomial,
+            new_secret.iter().map(commit).collect(),
+            proof,
+        )
+    }
+
+    #[test]
+    fn valid_reshare_verifies() {
+        for indices in [&[0, 1, 2][..], &[4, 1, 3, 0]] {
+            let (old, new, proof) = reshare(indices, g1);
+            assert_eq!(verify_reshare(&old, &new, &proof), Ok(()));
+        }
+
+        let (old, new, proof) = reshare(&[1, 2, 3], g2);
+        assert_eq!(verify_reshare(&old, &new, &proof), Ok(()));
+    }
+
+    #[test]
+    fn tampered_reshare_fails() {
+        let (old, new, proof) = reshare(&[0, 1, 2, 3], g1);
+        let key = old[0].clone();
+
+        // a dealer with a share off the old polynomial, whether it's qualified or not
+        for tampered_dealer in [0, 3] {
+            let mut tampered = proof.clone();
+            tampered.dealers[tampered_dealer].commitments[0] = g1(&Scalar::from(8));
+            assert_eq!(
+                verify_reshare(&old, &new, &tampered),
+                Err(VerificationError::InvalidReshareProof)
+            );
+        }
+
+        // a new polynomial that isn't the one dealt, or is for another key
+        for coefficient in [0, 1] {
+            let mut tampered = new.clone();
+            tampered[coefficient] = g1(&Scalar::from(8));
+            assert_eq!(
+                verify_reshare(&old, &tampered, &proof),
+                Err(VerificationError::InvalidReshareProof)
+            );
+        }
+
+        // a proof claiming a lower threshold than the old group's, too few dealers, repeated
+        // dealers, and dealers of the wrong threshold
+        let single_dealer = ReshareProof {
+            dealers: vec![ReshareDealer {
+                index: 0,
+                commitments: vec![key.clone()],
+            }],
+        };
+        let mut repeated = proof.clone();
+        repeated.dealers[1].index = 0;
+        let mut too_few = proof.clone();
+        too_few.dealers.truncate(2);
+        let mut short_dealer = proof.clone();
+        short_dealer.dealers[2].commitments.pop();
+        for (new_polynomial, proof) in [
+            (vec![key.clone()], single_dealer),
+            (new.clone(), too_few),
+            (new.clone(), repeated),
+            (new.clone(), short_dealer),
+        ] {
+            assert_eq!(
+                verify_reshare(&old, &new_polynomial, &proof),
+                Err(VerificationError::InvalidReshareProof)
+            );
+        }
+
+        assert_eq!(
+            verify_reshare(&[], &new, &proof),
+            Err(VerificationError::InvalidPublicKey)
+        );
+    }
+}
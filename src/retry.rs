@@ -0,0 +1,162 @@
+//! # retry
+//!
+//! a transport decorator that retries failed requests, e.g. to ride out a relay timing out or
+//! briefly returning errors
+//!
+
+use crate::{AsyncTransport, Transport, TransportError};
+use std::time::Instant;
+
+/// a transport that retries each failed request up to `max_retries` times.
+/// `NotFound` is not retried, as it usually means the round hasn't been emitted yet, and neither
+/// is `CircuitOpen`, as the circuit stays open for a while.
+pub struct RetryTransport<T> {
+    transport: T,
+    max_retries: u32,
+}
+
+/// create a transport that retries failed requests made with `transport` up to `max_retries`
+/// times before returning the last error
+pub fn new_retry_transport<T>(transport: T, max_retries: u32) -> RetryTransport<T> {
+    RetryTransport {
+        transport,
+        max_retries,
+    }
+}
+
+fn should_retry<V>(result: &Result<V, TransportError>) -> bool {
+    !matches!(
+        result,
+        Ok(_) | Err(TransportError::NotFound | TransportError::CircuitOpen)
+    )
+}
+
+impl<T: Transport> Transport for RetryTransport<T> {
+    fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        let mut result = Transport::fetch(&self.transport, url);
+        for _ in 0..self.max_retries {
+            if !should_retry(&result) {
+                break;
+            }
+            result = Transport::fetch(&self.transport, url);
+        }
+        result
+    }
+
+    /// no further retries are made once `deadline` has passed
+    fn fetch_by_deadline(&self, url: &str, deadline: Instant) -> Result<String, TransportError> {
+        let mut result = self.transport.fetch_by_deadline(url, deadline);
+        for _ in 0..self.max_retries {
+            if !should_retry(&result) || Instant::now() >= deadline {
+                break;
+            }
+            result = self.transport.fetch_by_deadline(url, deadline);
+        }
+        result
+    }
+
+    fn exists(&self, url: &str) -> Result<bool, TransportError> {
+        let mut result = self.transport.exists(url);
+        for _ in 0..self.max_retries {
+            if !should_retry(&result) {
+                break;
+            }
+            result = self.transport.exists(url);
+        }
+        result
+    }
+}
+
+impl<T: AsyncTransport> AsyncTransport for RetryTransport<T> {
+    async fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        let mut result = AsyncTransport::fetch(&self.transport, url).await;
+        for _ in 0..self.max_retries {
+            if !should_retry(&result) {
+                break;
+            }
+            result = AsyncTransport::fetch(&self.transport, url).await;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::retry::new_retry_transport;
+    use crate::{Transport, TransportError};
+    use std::sync::Mutex;
+
+    #[test]
+    fn retry_succeeds_after_failures() {
+        let transport = FlakyTransport::new(vec![
+            Err(TransportError::Timeout),
+            Err(TransportError::Unexpected {
+                status: None,
+                body: String::new(),
+            }),
+            Ok("body".to_string()),
+        ]);
+        let retry = new_retry_transport(transport, 2);
+
+        assert_eq!(retry.fetch("/info").unwrap(), "body");
+    }
+
+    #[test]
+    fn retry_gives_up_after_max_retries() {
+        let transport = FlakyTransport::new(vec![
+            Err(TransportError::Unexpected {
+                status: None,
+                body: String::new(),
+            }),
+            Err(TransportError::Timeout),
+            Ok("body".to_string()),
+        ]);
+        let retry = new_retry_transport(transport, 1);
+
+        assert!(matches!(retry.fetch("/info"), Err(TransportError::Timeout)));
+        assert_eq!(retry.transport.remaining(), 1);
+    }
+
+    #[test]
+    fn not_found_is_not_retried() {
+        let transport =
+            FlakyTransport::new(vec![Err(TransportError::NotFound), Ok("body".to_string())]);
+        let retry = new_retry_transport(transport, 3);
+
+        assert!(matches!(
+            retry.fetch("/public/1"),
+            Err(TransportError::NotFound)
+        ));
+        assert_eq!(retry.transport.remaining(), 1);
+    }
+
+    struct FlakyTransport {
+        results: Mutex<Vec<Result<String, TransportError>>>,
+    }
+
+    impl FlakyTransport {
+        fn new(mut results: Vec<Result<String, TransportError>>) -> Self {
+            results.reverse();
+            FlakyTransport {
+                results: Mutex::new(results),
+            }
+        }
+
+        fn remaining(&self) -> usize {
+            self.results.lock().unwrap().len()
+        }
+    }
+
+    impl Transport for FlakyTransport {
+        fn fetch(&self, _url: &str) -> Result<String, TransportError> {
+            self.results
+                .lock()
+                .unwrap()
+                .pop()
+                .unwrap_or(Err(TransportError::Unexpected {
+                    status: None,
+                    body: String::new(),
+                }))
+        }
+    }
+}
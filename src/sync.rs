@@ -0,0 +1,254 @@
+//! # sync
+//!
+//! decoding and verification of beacons received over drand's sync protocol, as used by
+//! follower nodes, rather than the HTTP JSON API
+//!
+
+use crate::verify::{expected_randomness, verify_beacon, Beacon, SchemeID, VerificationError};
+use prost::Message;
+use std::io::Read;
+use thiserror::Error;
+
+/// the largest sync message accepted, well above the few hundred bytes of a `BeaconPacket`, so
+/// a corrupt or hostile length prefix can't make the verifier allocate gigabytes
+pub const MAX_SYNC_MESSAGE_LEN: usize = 4096;
+
+/// the `BeaconPacket` message streamed by drand nodes in response to a sync request. Its
+/// metadata isn't decoded, as nothing here can check the chain hash it claims.
+#[derive(Clone, PartialEq, Message)]
+struct BeaconPacket {
+    #[prost(bytes = "vec", tag = "1")]
+    previous_signature: Vec<u8>,
+    #[prost(uint64, tag = "2")]
+    round: u64,
+    #[prost(bytes = "vec", tag = "3")]
+    signature: Vec<u8>,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SyncError {
+    #[error("invalid sync message")]
+    InvalidMessage,
+    #[error("compressed sync messages are not supported")]
+    CompressedMessage,
+    #[error("failed to read sync stream")]
+    ReadFailed,
+    #[error("beacon failed verification")]
+    FailedVerification(#[from] VerificationError),
+}
+
+impl Beacon {
+    /// decode a beacon from the protobuf-encoded `BeaconPacket` of drand's sync protocol.
+    /// Sync messages don't carry the randomness, so it is derived from the signature.
+    pub fn from_sync_message(bytes: &[u8]) -> Result<Beacon, SyncError> {
+        let packet = BeaconPacket::decode(bytes).map_err(|_| SyncError::InvalidMessage)?;
+        Ok(Beacon {
+            round_number: packet.round,
+            randomness: expected_randomness(&packet.signature),
+            signature: packet.signature,
+            previous_signature: packet.previous_signature,
+        })
+    }
+}
+
+/// an iterator over the beacons of a sync stream, verifying each one as it is read
+pub struct SyncStreamVerifier<'a, R: Read> {
+    reader: R,
+    scheme_id: &'a SchemeID,
+    public_key: &'a [u8],
+    finished: bool,
+}
+
+/// verify a stream of sync messages framed as they are on the wire by gRPC, i.e. each message
+/// is prefixed by a one byte compression flag and its length as a big-endian `u32`. Messages
+/// longer than [MAX_SYNC_MESSAGE_LEN] fail with `SyncError::InvalidMessage`.
+/// Iteration ends at the end of the stream or after the first error.
+pub fn verify_sync_stream<'a, R: Read>(
+    reader: R,
+    scheme_id: &'a SchemeID,
+    public_key: &'a [u8],
+) -> SyncStreamVerifier<'a, R> {
+    SyncStreamVerifier {
+        reader,
+        scheme_id,
+        public_key,
+        finished: false,
+    }
+}
+
+impl<R: Read> SyncStreamVerifier<'_, R> {
+    fn next_message(&mut self) -> Result<Option<Vec<u8>>, SyncError> {
+        let mut header = [0u8; 5];
+        match self.reader.read_exact(&mut header[..1]) {
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(_) => return Err(SyncError::ReadFailed),
+            Ok(_) => {}
+        }
+        self.reader
+            .read_exact(&mut header[1..])
+            .map_err(|_| SyncError::ReadFailed)?;
+
+        if header[0] != 0 {
+            return Err(SyncError::CompressedMessage);
+        }
+
+        let len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+        if len > MAX_SYNC_MESSAGE_LEN {
+            return Err(SyncError::InvalidMessage);
+        }
+        let mut message = vec![0u8; len];
+        self.reader
+            .read_exact(&mut message)
+            .map_err(|_| SyncError::ReadFailed)?;
+        Ok(Some(message))
+    }
+}
+
+impl<R: Read> Iterator for SyncStreamVerifier<'_, R> {
+    type Item = Result<Beacon, SyncError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let result = self.next_message().and_then(|message| match message {
+            None => Ok(None),
+            Some(bytes) => {
+                let beacon = Beacon::from_sync_message(&bytes)?;
+                verify_beacon(self.scheme_id, self.public_key, &beacon)?;
+                Ok(Some(beacon))
+            }
+        });
+
+        match result {
+            Ok(Some(beacon)) => Some(Ok(beacon)),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::sync::{verify_sync_stream, SyncError, MAX_SYNC_MESSAGE_LEN};
+    use crate::verify::{Beacon, SchemeID, VerificationError};
+    use std::io::Cursor;
+
+    // a `BeaconPacket` for mainnet round 397089, including metadata
+    const SYNC_MESSAGE: &str = "0a60a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce10a19e181a6088ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a222b120764656661756c741a208990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce";
+    const PUBLIC_KEY: &str = "88a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb";
+
+    #[test]
+    fn sync_message_decodes() {
+        let beacon = Beacon::from_sync_message(&hex::decode(SYNC_MESSAGE).unwrap()).unwrap();
+
+        assert_eq!(beacon.round_number, 397089);
+        assert_eq!(
+            beacon.randomness,
+            hex::decode("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42")
+                .unwrap()
+        );
+        assert_eq!(beacon.previous_signature.len(), 96);
+    }
+
+    #[test]
+    fn invalid_sync_message_fails() {
+        assert_eq!(
+            Beacon::from_sync_message(&[0x0a, 0xff]),
+            Err(SyncError::InvalidMessage)
+        );
+    }
+
+    #[test]
+    fn sync_stream_verifies() {
+        let message = hex::decode(SYNC_MESSAGE).unwrap();
+        let stream = [frame(&message), frame(&message)].concat();
+        let public_key = hex::decode(PUBLIC_KEY).unwrap();
+
+        let beacons: Vec<_> = verify_sync_stream(
+            Cursor::new(stream),
+            &SchemeID::PedersenBlsChained,
+            &public_key,
+        )
+        .collect();
+
+        assert_eq!(beacons.len(), 2);
+        assert!(beacons.iter().all(|b| b.is_ok()));
+    }
+
+    #[test]
+    fn sync_stream_stops_at_failed_verification() {
+        let message = hex::decode(SYNC_MESSAGE).unwrap();
+        let stream = [frame(&message), frame(&message)].concat();
+        let public_key = hex::decode(PUBLIC_KEY).unwrap();
+
+        let beacons: Vec<_> = verify_sync_stream(
+            Cursor::new(stream),
+            &SchemeID::PedersenBlsUnchained,
+            &public_key,
+        )
+        .collect();
+
+        assert_eq!(
+            beacons,
+            vec![Err(SyncError::FailedVerification(
+                VerificationError::UnexpectedPreviousSignature
+            ))]
+        );
+    }
+
+    #[test]
+    fn sync_stream_truncated_fails() {
+        let message = hex::decode(SYNC_MESSAGE).unwrap();
+        let mut stream = frame(&message);
+        stream.truncate(stream.len() - 1);
+        let public_key = hex::decode(PUBLIC_KEY).unwrap();
+
+        let mut beacons = verify_sync_stream(
+            Cursor::new(stream),
+            &SchemeID::PedersenBlsChained,
+            &public_key,
+        );
+
+        assert_eq!(beacons.next(), Some(Err(SyncError::ReadFailed)));
+        assert_eq!(beacons.next(), None);
+    }
+
+    #[test]
+    fn oversized_sync_message_fails_before_reading_it() {
+        let mut stream = vec![0u8];
+        stream.extend_from_slice(&u32::MAX.to_be_bytes());
+        let public_key = hex::decode(PUBLIC_KEY).unwrap();
+
+        let mut beacons = verify_sync_stream(
+            Cursor::new(stream),
+            &SchemeID::PedersenBlsChained,
+            &public_key,
+        );
+
+        assert_eq!(beacons.next(), Some(Err(SyncError::InvalidMessage)));
+        assert_eq!(beacons.next(), None);
+
+        let message = vec![0u8; MAX_SYNC_MESSAGE_LEN + 1];
+        let mut beacons = verify_sync_stream(
+            Cursor::new(frame(&message)),
+            &SchemeID::PedersenBlsChained,
+            &public_key,
+        );
+        assert_eq!(beacons.next(), Some(Err(SyncError::InvalidMessage)));
+    }
+
+    fn frame(message: &[u8]) -> Vec<u8> {
+        let mut framed = vec![0u8];
+        framed.extend_from_slice(&(message.len() as u32).to_be_bytes());
+        framed.extend_from_slice(message);
+        framed
+    }
+}
@@ -0,0 +1,139 @@
+//! helpers shared between the test modules of the crate
+
+use crate::chain_info::{ChainInfo, ChainInfoMetadata};
+use crate::verify::{expected_randomness, Beacon, SchemeID, DST_G1, DST_G2};
+use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+use bls12_381::{G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
+use sha2::{Digest, Sha256};
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+/// signs beacons with a throwaway key, so tests can build valid beacons for any round
+pub(crate) struct TestSigner {
+    scheme_id: SchemeID,
+    secret_key: Scalar,
+}
+
+impl TestSigner {
+    pub(crate) fn new(scheme_id: SchemeID) -> Self {
+        Self::with_secret(scheme_id, 0x5eed)
+    }
+
+    pub(crate) fn with_secret(scheme_id: SchemeID, secret: u64) -> Self {
+        TestSigner {
+            scheme_id,
+            secret_key: Scalar::from(secret),
+        }
+    }
+
+    pub(crate) fn public_key(&self) -> Vec<u8> {
+        match self.scheme_id {
+            SchemeID::PedersenBlsChained | SchemeID::PedersenBlsUnchained => {
+                G1Affine::from(G1Affine::generator() * self.secret_key)
+                    .to_compressed()
+                    .to_vec()
+            }
+            SchemeID::UnchainedOnG1 | SchemeID::UnchainedOnG1RFC9380 => {
+                G2Affine::from(G2Affine::generator() * self.secret_key)
+                    .to_compressed()
+                    .to_vec()
+            }
+        }
+    }
+
+    pub(crate) fn sign(&self, message: &[u8]) -> Vec<u8> {
+        match self.scheme_id {
+            SchemeID::PedersenBlsChained | SchemeID::PedersenBlsUnchained => {
+                let m = <G2Projective as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve(
+                    message,
+                    DST_G2.as_bytes(),
+                );
+                G2Affine::from(m * self.secret_key).to_compressed().to_vec()
+            }
+            SchemeID::UnchainedOnG1 | SchemeID::UnchainedOnG1RFC9380 => {
+                let dst = if self.scheme_id == SchemeID::UnchainedOnG1 {
+                    DST_G2
+                } else {
+                    DST_G1
+                };
+                let m = <G1Projective as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve(
+                    message,
+                    dst.as_bytes(),
+                );
+                G1Affine::from(m * self.secret_key).to_compressed().to_vec()
+            }
+        }
+    }
+
+    /// a valid beacon for `round`; for the chained scheme the previous signature is an
+    /// arbitrary signature made with the same key
+    pub(crate) fn beacon(&self, round: u64) -> Beacon {
+        if self.scheme_id != SchemeID::PedersenBlsChained {
+            let signature = self.sign(&Sha256::digest(&round.to_be_bytes()));
+            return Beacon {
+                round_number: round,
+                randomness: expected_randomness(&signature),
+                signature,
+                previous_signature: Vec::new(),
+            };
+        }
+
+        let previous_signature = self.sign(&(round - 1).to_be_bytes());
+        self.chained_beacon(round, &previous_signature)
+    }
+
+    pub(crate) fn chained_beacon(&self, round: u64, previous_signature: &[u8]) -> Beacon {
+        let message: Vec<u8> = previous_signature
+            .iter()
+            .copied()
+            .chain(round.to_be_bytes())
+            .collect();
+        let signature = self.sign(&Sha256::digest(&message));
+        Beacon {
+            round_number: round,
+            randomness: expected_randomness(&signature),
+            signature,
+            previous_signature: previous_signature.to_vec(),
+        }
+    }
+
+    pub(crate) fn chain_info(&self, genesis_time: u64, period_seconds: usize) -> ChainInfo {
+        ChainInfo {
+            scheme_id: self.scheme_id.clone(),
+            public_key: self.public_key(),
+            chain_hash: vec![0xc4; 32],
+            group_hash: vec![0x6e; 32],
+            genesis_time,
+            period_seconds,
+            genesis_round: 1,
+            metadata: ChainInfoMetadata {
+                beacon_id: "default".to_string(),
+            },
+        }
+    }
+}
+
+/// drive a future to completion on the current thread, so async code can be tested without
+/// pulling in a runtime
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
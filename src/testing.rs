@@ -0,0 +1,154 @@
+//! # testing
+//!
+//! transports that are useful for testing code built on top of the client without
+//! connecting to the drand network
+//!
+
+use crate::lock_unpoisoned;
+use crate::{AsyncTransport, Transport, TransportError};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// a transport serving canned responses from memory, keyed by the full URL requested.
+/// Requests for URLs that haven't been inserted fail with `TransportError::NotFound`.
+#[derive(Default, Debug, Clone)]
+pub struct InMemoryTransport {
+    responses: HashMap<String, String>,
+}
+
+impl InMemoryTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// serve `body` for requests to `url`, replacing any previous response for it
+    pub fn insert(&mut self, url: &str, body: &str) {
+        self.responses.insert(url.to_string(), body.to_string());
+    }
+}
+
+impl Transport for InMemoryTransport {
+    fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        self.responses
+            .get(url)
+            .cloned()
+            .ok_or(TransportError::NotFound)
+    }
+}
+
+/// the async counterpart of `InMemoryTransport`, for testing code built on `AsyncTransport`
+#[derive(Default, Debug, Clone)]
+pub struct AsyncInMemoryTransport {
+    responses: HashMap<String, String>,
+}
+
+impl AsyncInMemoryTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// serve `body` for requests to `url`, replacing any previous response for it
+    pub fn insert(&mut self, url: &str, body: &str) {
+        self.responses.insert(url.to_string(), body.to_string());
+    }
+}
+
+impl AsyncTransport for AsyncInMemoryTransport {
+    async fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        self.responses
+            .get(url)
+            .cloned()
+            .ok_or(TransportError::NotFound)
+    }
+}
+
+/// an async transport recording the URL of every request before passing it on to `transport`,
+/// e.g. to assert which requests a decorator or cache ends up making
+#[derive(Debug, Default)]
+pub struct AsyncRecordingTransport<T> {
+    transport: T,
+    requests: Mutex<Vec<String>>,
+}
+
+impl<T> AsyncRecordingTransport<T> {
+    pub fn new(transport: T) -> Self {
+        AsyncRecordingTransport {
+            transport,
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// the URLs requested so far, oldest first
+    pub fn requests(&self) -> Vec<String> {
+        lock_unpoisoned(&self.requests).clone()
+    }
+}
+
+impl<T: AsyncTransport> AsyncTransport for AsyncRecordingTransport<T> {
+    async fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        lock_unpoisoned(&self.requests).push(url.to_string());
+        self.transport.fetch(url).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::retry::new_retry_transport;
+    use crate::test_util::block_on;
+    use crate::testing::{AsyncInMemoryTransport, AsyncRecordingTransport, InMemoryTransport};
+    use crate::{AsyncTransport, Transport, TransportError};
+
+    #[test]
+    fn in_memory_transport_serves_inserted_responses() {
+        let mut transport = InMemoryTransport::new();
+        transport.insert("https://example.org/info", "{}");
+
+        assert_eq!(transport.fetch("https://example.org/info").unwrap(), "{}");
+        assert!(matches!(
+            transport.fetch("https://example.org/public/1"),
+            Err(TransportError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn async_transports_serve_and_record_requests() {
+        let mut in_memory = AsyncInMemoryTransport::new();
+        in_memory.insert("https://example.org/info", "{}");
+        let transport = AsyncRecordingTransport::new(in_memory);
+
+        assert_eq!(
+            block_on(transport.fetch("https://example.org/info")),
+            Ok("{}".to_string())
+        );
+        assert_eq!(
+            block_on(transport.fetch("https://example.org/public/1")),
+            Err(TransportError::NotFound)
+        );
+        assert_eq!(
+            transport.requests(),
+            vec!["https://example.org/info", "https://example.org/public/1"]
+        );
+    }
+
+    #[test]
+    fn async_decorators_can_be_driven_in_memory() {
+        let mut in_memory = AsyncInMemoryTransport::new();
+        in_memory.insert("https://example.org/info", "{}");
+        let transport = new_retry_transport(in_memory, 3);
+
+        assert_eq!(
+            block_on(AsyncTransport::fetch(
+                &transport,
+                "https://example.org/info"
+            )),
+            Ok("{}".to_string())
+        );
+        assert_eq!(
+            block_on(AsyncTransport::fetch(
+                &transport,
+                "https://example.org/public/1"
+            )),
+            Err(TransportError::NotFound)
+        );
+    }
+}
@@ -0,0 +1,195 @@
+//! # throttle
+//!
+//! a transport decorator that limits how often requests are made, so backfilling lots of
+//! rounds doesn't hammer public relays
+//!
+
+use crate::lock_unpoisoned;
+use crate::{Transport, TransportError};
+use std::sync::Mutex;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+/// a transport that makes at most `requests_per_second` requests per second on average,
+/// blocking the calling thread before each request until it's allowed. Requests are allowed
+/// in bursts of up to `burst` after a quiet period, which defaults to 1.
+/// The limit is shared by every thread using the transport.
+pub struct ThrottledTransport<T> {
+    transport: T,
+    requests_per_second: f64,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    /// negative when requests are waiting for tokens that haven't been added yet
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// create a transport making at most `requests_per_second` requests per second with
+/// `transport`. A limit of 0 is treated as 1.
+pub fn new_throttled_transport<T>(transport: T, requests_per_second: u32) -> ThrottledTransport<T> {
+    ThrottledTransport {
+        transport,
+        requests_per_second: f64::from(requests_per_second.max(1)),
+        burst: 1.0,
+        bucket: Mutex::new(Bucket {
+            tokens: 1.0,
+            last_refill: Instant::now(),
+        }),
+    }
+}
+
+impl<T> ThrottledTransport<T> {
+    /// allow up to `burst` requests at once after a quiet period, rather than spacing every
+    /// request out evenly. A burst of 0 is treated as 1.
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = f64::from(burst.max(1));
+        lock_unpoisoned(&self.bucket).tokens = self.burst;
+        self
+    }
+
+    /// take a token for a request, returning how long to wait before making it. Tokens are
+    /// taken even when there are none yet, so waiting requests are let through in order.
+    fn reserve(&self) -> Duration {
+        let mut bucket = lock_unpoisoned(&self.bucket);
+        let now = Instant::now();
+        let refill =
+            now.duration_since(bucket.last_refill).as_secs_f64() * self.requests_per_second;
+        bucket.tokens = (bucket.tokens + refill).min(self.burst) - 1.0;
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.requests_per_second)
+        }
+    }
+
+    /// hand back a token taken by `reserve` for a request that wasn't made
+    fn release(&self) {
+        lock_unpoisoned(&self.bucket).tokens += 1.0;
+    }
+
+    fn throttle(&self) {
+        let wait = self.reserve();
+        if !wait.is_zero() {
+            sleep(wait);
+        }
+    }
+}
+
+impl<T: Transport> Transport for ThrottledTransport<T> {
+    fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        self.throttle();
+        self.transport.fetch(url)
+    }
+
+    fn exists(&self, url: &str) -> Result<bool, TransportError> {
+        self.throttle();
+        self.transport.exists(url)
+    }
+
+    /// fails with `TransportError::Timeout` straight away if the request wouldn't be allowed
+    /// before `deadline`, rather than waiting for it, and passes the deadline on otherwise
+    fn fetch_by_deadline(&self, url: &str, deadline: Instant) -> Result<String, TransportError> {
+        let wait = self.reserve();
+        if Instant::now() + wait >= deadline {
+            self.release();
+            return Err(TransportError::Timeout);
+        }
+        if !wait.is_zero() {
+            sleep(wait);
+        }
+        self.transport.fetch_by_deadline(url, deadline)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::testing::InMemoryTransport;
+    use crate::throttle::new_throttled_transport;
+    use crate::{Transport, TransportError};
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    /// records the deadline of each request
+    #[derive(Default)]
+    struct DeadlineTransport {
+        deadlines: Mutex<Vec<Instant>>,
+    }
+
+    impl Transport for DeadlineTransport {
+        fn fetch(&self, _: &str) -> Result<String, TransportError> {
+            Ok(String::new())
+        }
+
+        fn fetch_by_deadline(
+            &self,
+            url: &str,
+            deadline: Instant,
+        ) -> Result<String, TransportError> {
+            self.deadlines.lock().unwrap().push(deadline);
+            self.fetch(url)
+        }
+    }
+
+    #[test]
+    fn requests_are_spaced_out_by_rate() {
+        let transport = new_throttled_transport(InMemoryTransport::new(), 20);
+
+        let start = Instant::now();
+        for _ in 0..5 {
+            let _ = transport.fetch("https://example.org/info");
+        }
+
+        // the first request is let through straight away, then one every 50ms
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn bursts_are_let_through_at_once() {
+        let transport = new_throttled_transport(InMemoryTransport::new(), 2).with_burst(3);
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            let _ = transport.fetch("https://example.org/info");
+        }
+        assert!(start.elapsed() < Duration::from_millis(400));
+
+        let _ = transport.fetch("https://example.org/info");
+        assert!(start.elapsed() >= Duration::from_millis(400));
+    }
+
+    #[test]
+    fn deadlines_bound_the_wait_and_are_passed_on() {
+        let transport = new_throttled_transport(DeadlineTransport::default(), 10);
+        let url = "https://example.org/info";
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        assert_eq!(
+            transport.fetch_by_deadline(url, deadline),
+            Ok(String::new())
+        );
+
+        // the next request isn't allowed for another 100ms
+        let start = Instant::now();
+        assert_eq!(
+            transport.fetch_by_deadline(url, start + Duration::from_millis(20)),
+            Err(TransportError::Timeout)
+        );
+
+        // the timed out request didn't use up a token, so the next is let through after 100ms
+        // rather than 200ms
+        let next_deadline = start + Duration::from_millis(150);
+        assert_eq!(
+            transport.fetch_by_deadline(url, next_deadline),
+            Ok(String::new())
+        );
+        assert_eq!(
+            *transport.transport.deadlines.lock().unwrap(),
+            vec![deadline, next_deadline]
+        );
+    }
+}
@@ -4,59 +4,310 @@
 //! manually without the client
 //!
 
-use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+use crate::chain_info::ChainInfo;
+use bls12_381::hash_to_curve::{ExpandMessage, ExpandMsgXmd, HashToCurve};
 use bls12_381::{
     multi_miller_loop, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt,
 };
-use serde::{Deserialize, Deserializer};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sha2::{Digest, Sha256};
-use std::ops::Neg;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::ops::{Neg, RangeInclusive};
+use subtle::{Choice, ConstantTimeEq};
 use thiserror::Error;
 
-#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Beacon {
-    #[serde(alias = "round")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            rename = "round",
+            alias = "round_number",
+            deserialize_with = "numeric_or_string::deserialize"
+        )
+    )]
     pub round_number: u64,
-    #[serde(with = "hex")]
+    #[cfg_attr(feature = "serde", serde(with = "prefixed_hex"))]
     pub randomness: Vec<u8>,
-    #[serde(with = "hex")]
+    #[cfg_attr(feature = "serde", serde(with = "prefixed_hex"))]
     pub signature: Vec<u8>,
-    #[serde(default, with = "hex")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "prefixed_hex", skip_serializing_if = "Vec::is_empty")
+    )]
     pub previous_signature: Vec<u8>,
 }
 
-const DST_G1: &str = "BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
-const DST_G2: &str = "BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+/// hex (de)serialization that also accepts an optional `0x` prefix, as used by some tooling
+#[cfg(feature = "serde")]
+mod prefixed_hex {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        hex::serialize(bytes, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let digits = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .unwrap_or(&s);
+        hex::decode(digits).map_err(serde::de::Error::custom)
+    }
+}
+
+/// the signature inside `bytes`, which is either a plain 48 or 96 byte signature or one
+/// wrapped in a recognised envelope, as found in some beacon archives:
+///
+/// - length-prefixed: the signature's length as a big-endian `u16`, then the signature
+/// - checksummed: the signature, then the first 4 bytes of its SHA-256 hash
+///
+/// Returns `None` for anything else, including envelopes whose length or checksum doesn't match,
+/// rather than guessing at the signature.
+pub fn unwrap_signature_envelope(bytes: &[u8]) -> Option<&[u8]> {
+    let is_signature_length = |length: usize| length == 48 || length == 96;
+    if is_signature_length(bytes.len()) {
+        return Some(bytes);
+    }
+
+    if bytes.len() >= 2 {
+        let (prefix, signature) = bytes.split_at(2);
+        let length = u16::from_be_bytes([prefix[0], prefix[1]]) as usize;
+        if is_signature_length(signature.len()) && length == signature.len() {
+            return Some(signature);
+        }
+    }
+
+    if bytes.len() >= 4 {
+        let (signature, checksum) = bytes.split_at(bytes.len() - 4);
+        if is_signature_length(signature.len()) && Sha256::digest(signature)[..4] == *checksum {
+            return Some(signature);
+        }
+    }
+    None
+}
+
+/// a lenient deserializer for signature fields of archived beacons, accepting hex with an
+/// optional `0x` prefix that decodes to a signature in any envelope recognised by
+/// [unwrap_signature_envelope], e.g.
+/// `#[serde(deserialize_with = "enveloped_signature::deserialize")]`.
+/// Unrecognised envelopes fail to deserialize.
+#[cfg(feature = "serde")]
+pub mod enveloped_signature {
+    use crate::verify::{prefixed_hex, unwrap_signature_envelope};
+    use serde::Deserializer;
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let bytes = prefixed_hex::deserialize(deserializer)?;
+        unwrap_signature_envelope(&bytes)
+            .map(|signature| signature.to_vec())
+            .ok_or_else(|| {
+                serde::de::Error::custom(format!(
+                    "unrecognised signature envelope of {} bytes",
+                    bytes.len()
+                ))
+            })
+    }
+}
+
+/// a round deserialized from either a JSON number or a decimal string, as some API versions
+/// serialize it
+#[cfg(feature = "serde")]
+mod numeric_or_string {
+    use serde::de::{Error, Visitor};
+    use serde::Deserializer;
+    use std::fmt;
+
+    struct RoundVisitor;
+
+    impl Visitor<'_> for RoundVisitor {
+        type Value = u64;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a round number, or a string containing one")
+        }
+
+        fn visit_u64<E: Error>(self, round: u64) -> Result<u64, E> {
+            Ok(round)
+        }
+
+        fn visit_i64<E: Error>(self, round: i64) -> Result<u64, E> {
+            u64::try_from(round).map_err(E::custom)
+        }
+
+        fn visit_str<E: Error>(self, round: &str) -> Result<u64, E> {
+            round.parse().map_err(E::custom)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        deserializer.deserialize_any(RoundVisitor)
+    }
+}
+
+impl Beacon {
+    /// build a beacon, checking its fields have lengths that can make up a valid beacon of
+    /// some scheme: 32 bytes of randomness, a 48 or 96 byte signature, and a previous
+    /// signature that is either empty or as long as the signature. Round 1 of the chained
+    /// scheme may also have the 32 byte genesis seed as its previous signature. The beacon
+    /// still needs verifying against a scheme and public key.
+    pub fn new(
+        round_number: u64,
+        randomness: Vec<u8>,
+        signature: Vec<u8>,
+        previous_signature: Vec<u8>,
+    ) -> Result<Beacon, VerificationError> {
+        if randomness.len() != 32 {
+            return Err(VerificationError::InvalidBeaconField {
+                field: "randomness",
+                expected: &[32],
+                actual: randomness.len(),
+            });
+        }
+        let previous_signature_lengths: &'static [usize] = match signature.len() {
+            48 => &[0, 48],
+            96 if round_number == 1 => &[0, 32, 96],
+            96 => &[0, 96],
+            actual => {
+                return Err(VerificationError::InvalidBeaconField {
+                    field: "signature",
+                    expected: &[48, 96],
+                    actual,
+                })
+            }
+        };
+        if !previous_signature_lengths.contains(&previous_signature.len()) {
+            return Err(VerificationError::InvalidBeaconField {
+                field: "previous_signature",
+                expected: previous_signature_lengths,
+                actual: previous_signature.len(),
+            });
+        }
+        Ok(Beacon {
+            round_number,
+            randomness,
+            signature,
+            previous_signature,
+        })
+    }
+
+    /// whether this is round 1, the first round of chains starting at round 1. A beacon doesn't
+    /// know its chain's genesis round, so for chains starting at another round use
+    /// `ChainInfo::links_to_genesis`.
+    pub fn is_first_round(&self) -> bool {
+        self.round_number == 1
+    }
+}
+
+pub(crate) const DST_G1: &str = "BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+/// the DST of every scheme but `bls-unchained-g1-rfc9380`. This includes `bls-unchained-on-g1`,
+/// which signs on g1 but, by a quirk of its original implementation, hashes to g1 with the g2
+/// DST. Archived beacons of that scheme depend on this, so it must never change.
+pub(crate) const DST_G2: &str = "BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+
+/// the hash-to-curve message expander used by all the drand networks, as specified by the
+/// DSTs of the schemes
+pub type DefaultExpander = ExpandMsgXmd<Sha256>;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum SchemeID {
     PedersenBlsChained,
     PedersenBlsUnchained,
+    /// the original scheme with signatures on g1, which hashes to g1 with `DST_G2` rather than
+    /// the g1 DST. It was superseded by `UnchainedOnG1RFC9380`, but is kept for archived beacons.
     UnchainedOnG1,
     UnchainedOnG1RFC9380,
 }
 
+const SCHEME_NAMES: [&str; 4] = [
+    "pedersen-bls-chained",
+    "pedersen-bls-unchained",
+    "bls-unchained-on-g1",
+    "bls-unchained-g1-rfc9380",
+];
+
+impl SchemeID {
+    /// the scheme's identifier as used by drand, e.g. in the chain info
+    pub fn name(&self) -> &'static str {
+        match self {
+            SchemeID::PedersenBlsChained => SCHEME_NAMES[0],
+            SchemeID::PedersenBlsUnchained => SCHEME_NAMES[1],
+            SchemeID::UnchainedOnG1 => SCHEME_NAMES[2],
+            SchemeID::UnchainedOnG1RFC9380 => SCHEME_NAMES[3],
+        }
+    }
+
+    /// the scheme with the identifier `name` as used by drand, if there is one
+    pub fn from_name(name: &str) -> Option<SchemeID> {
+        match name {
+            "pedersen-bls-chained" => Some(SchemeID::PedersenBlsChained),
+            "pedersen-bls-unchained" => Some(SchemeID::PedersenBlsUnchained),
+            "bls-unchained-on-g1" => Some(SchemeID::UnchainedOnG1),
+            "bls-unchained-g1-rfc9380" => Some(SchemeID::UnchainedOnG1RFC9380),
+            _ => None,
+        }
+    }
+
+    /// the length in bytes of a compressed public key for the scheme
+    pub fn public_key_length(&self) -> usize {
+        match self {
+            SchemeID::PedersenBlsChained | SchemeID::PedersenBlsUnchained => 48,
+            SchemeID::UnchainedOnG1 | SchemeID::UnchainedOnG1RFC9380 => 96,
+        }
+    }
+
+    /// the length in bytes of a compressed signature for the scheme
+    pub fn signature_length(&self) -> usize {
+        match self {
+            SchemeID::PedersenBlsChained | SchemeID::PedersenBlsUnchained => 96,
+            SchemeID::UnchainedOnG1 | SchemeID::UnchainedOnG1RFC9380 => 48,
+        }
+    }
+
+    /// the domain separation tag the scheme hashes messages to the curve with. The RFC9380
+    /// scheme fixed the tag for signatures on G1; the older G1 scheme used the G2 tag.
+    pub(crate) fn dst(&self) -> &'static str {
+        match self {
+            SchemeID::UnchainedOnG1RFC9380 => DST_G1,
+            _ => DST_G2,
+        }
+    }
+
+    /// the schemes whose public keys are `len` bytes long, e.g. to suggest which schemes a
+    /// bare public key could be used with. This is only advisory: it doesn't check the key.
+    pub fn schemes_for_key_length(len: usize) -> Vec<SchemeID> {
+        [
+            SchemeID::PedersenBlsChained,
+            SchemeID::PedersenBlsUnchained,
+            SchemeID::UnchainedOnG1,
+            SchemeID::UnchainedOnG1RFC9380,
+        ]
+        .into_iter()
+        .filter(|scheme_id| scheme_id.public_key_length() == len)
+        .collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for SchemeID {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+#[cfg(feature = "serde")]
 impl<'de> Deserialize<'de> for SchemeID {
     fn deserialize<D>(deserializer: D) -> Result<SchemeID, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let s = Deserialize::deserialize(deserializer)?;
-        match s {
-            "pedersen-bls-chained" => Ok(SchemeID::PedersenBlsChained),
-            "pedersen-bls-unchained" => Ok(SchemeID::PedersenBlsUnchained),
-            "bls-unchained-on-g1" => Ok(SchemeID::UnchainedOnG1),
-            "bls-unchained-g1-rfc9380" => Ok(SchemeID::UnchainedOnG1RFC9380),
-            _ => Err(serde::de::Error::unknown_variant(
-                s,
-                &[
-                    "pedersen-bls-chained",
-                    "pedersen-bls-unchained",
-                    "bls-unchained-on-g1",
-                    "bls-unchained-g1-rfc9380",
-                ],
-            )),
-        }
+        // not borrowed, as not every format can lend out the string, e.g. TOML
+        let s: String = Deserialize::deserialize(deserializer)?;
+        SchemeID::from_name(&s).ok_or_else(|| serde::de::Error::unknown_variant(&s, &SCHEME_NAMES))
     }
 }
 
@@ -74,6 +325,204 @@ pub enum VerificationError {
     SignatureFailedVerification,
     #[error("the randomness for the beacon did not match the signature")]
     InvalidRandomness,
+    #[error("beacons of unchained schemes don't have a previous signature")]
+    UnexpectedPreviousSignature,
+    #[error("round is before the chain's genesis round")]
+    InvalidRound,
+    #[error("the message point is not a valid, hashed message")]
+    InvalidMessagePoint,
+    #[error("the signature is not a valid point for the scheme")]
+    InvalidSignaturePoint,
+    #[error(
+        "`{field}` should be {expected} bytes for {scheme} but was {actual} bytes{}",
+        length_comparison(*.expected, *.actual)
+    )]
+    WrongLength {
+        field: &'static str,
+        scheme: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("round {round} doesn't follow on from the beacon before it")]
+    BrokenChainLink { round: u64 },
+    #[error("the beacons end before reaching the trusted round {trusted_round}")]
+    TrustedRoundNotReached { trusted_round: u64 },
+    #[error("the reshare proof doesn't link the old and new group keys")]
+    InvalidReshareProof,
+    #[error(
+        "`{field}` should be {} bytes but was {actual} bytes",
+        either_length(.expected)
+    )]
+    InvalidBeaconField {
+        field: &'static str,
+        expected: &'static [usize],
+        actual: usize,
+    },
+    #[error("the range of rounds from {start} to {end} is empty or too long")]
+    InvalidRange { start: u64, end: u64 },
+    #[error("the `{scheme}` scheme isn't supported here")]
+    UnsupportedScheme { scheme: &'static str },
+}
+
+/// whether a value of the wrong length was too short or too long
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LengthMismatch {
+    TooShort,
+    TooLong,
+}
+
+fn length_comparison(expected: usize, actual: usize) -> String {
+    match actual.cmp(&expected) {
+        Ordering::Less => format!(", {} too short", expected - actual),
+        Ordering::Greater => format!(", {} too long", actual - expected),
+        Ordering::Equal => String::new(),
+    }
+}
+
+fn either_length(lengths: &[usize]) -> String {
+    lengths
+        .iter()
+        .map(usize::to_string)
+        .collect::<Vec<_>>()
+        .join(" or ")
+}
+
+/// the stable numeric codes of each [VerificationError], for reporting errors across language
+/// boundaries. `0` is never used, so it can signal success. Codes are never reused or changed:
+///
+/// | code | error                                 |
+/// |------|---------------------------------------|
+/// | 1    | `ChainedBeaconNeedsPreviousSignature` |
+/// | 2    | `InvalidSignatureLength`              |
+/// | 3    | `InvalidPublicKey`                    |
+/// | 4    | `EmptyMessage`                        |
+/// | 5    | `SignatureFailedVerification`         |
+/// | 6    | `InvalidRandomness`                   |
+/// | 7    | `UnexpectedPreviousSignature`         |
+/// | 8    | `InvalidRound`                        |
+/// | 9    | `InvalidMessagePoint`                 |
+/// | 10   | `InvalidSignaturePoint`               |
+/// | 11   | `WrongLength`                         |
+/// | 12   | `BrokenChainLink`                     |
+/// | 13   | `TrustedRoundNotReached`              |
+/// | 14   | `InvalidReshareProof`                 |
+/// | 15   | `InvalidBeaconField`                  |
+/// | 16   | `InvalidRange`                        |
+/// | 17   | `UnsupportedScheme`                   |
+impl VerificationError {
+    /// the stable numeric code of the error, see the table above
+    pub fn code(&self) -> u32 {
+        match self {
+            VerificationError::ChainedBeaconNeedsPreviousSignature => 1,
+            VerificationError::InvalidSignatureLength => 2,
+            VerificationError::InvalidPublicKey => 3,
+            VerificationError::EmptyMessage => 4,
+            VerificationError::SignatureFailedVerification => 5,
+            VerificationError::InvalidRandomness => 6,
+            VerificationError::UnexpectedPreviousSignature => 7,
+            VerificationError::InvalidRound => 8,
+            VerificationError::InvalidMessagePoint => 9,
+            VerificationError::InvalidSignaturePoint => 10,
+            VerificationError::WrongLength { .. } => 11,
+            VerificationError::BrokenChainLink { .. } => 12,
+            VerificationError::TrustedRoundNotReached { .. } => 13,
+            VerificationError::InvalidReshareProof => 14,
+            VerificationError::InvalidBeaconField { .. } => 15,
+            VerificationError::InvalidRange { .. } => 16,
+            VerificationError::UnsupportedScheme { .. } => 17,
+        }
+    }
+
+    /// for a `WrongLength` or `InvalidBeaconField` error, whether the value was too short or too
+    /// long, e.g. to tell a truncated signature from one with trailing garbage. A value between
+    /// two accepted lengths is neither.
+    pub fn length_mismatch(&self) -> Option<LengthMismatch> {
+        match self {
+            VerificationError::WrongLength {
+                expected, actual, ..
+            } => match actual.cmp(expected) {
+                Ordering::Less => Some(LengthMismatch::TooShort),
+                Ordering::Greater => Some(LengthMismatch::TooLong),
+                Ordering::Equal => None,
+            },
+            VerificationError::InvalidBeaconField {
+                expected, actual, ..
+            } => {
+                if expected.iter().all(|expected| actual < expected) {
+                    Some(LengthMismatch::TooShort)
+                } else if expected.iter().all(|expected| actual > expected) {
+                    Some(LengthMismatch::TooLong)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// the error for a code returned by [VerificationError::code], or `None` for unknown codes.
+    /// The details of errors such as `WrongLength` aren't part of their code, so they come back
+    /// empty.
+    pub fn from_code(code: u32) -> Option<VerificationError> {
+        match code {
+            1 => Some(VerificationError::ChainedBeaconNeedsPreviousSignature),
+            2 => Some(VerificationError::InvalidSignatureLength),
+            3 => Some(VerificationError::InvalidPublicKey),
+            4 => Some(VerificationError::EmptyMessage),
+            5 => Some(VerificationError::SignatureFailedVerification),
+            6 => Some(VerificationError::InvalidRandomness),
+            7 => Some(VerificationError::UnexpectedPreviousSignature),
+            8 => Some(VerificationError::InvalidRound),
+            9 => Some(VerificationError::InvalidMessagePoint),
+            10 => Some(VerificationError::InvalidSignaturePoint),
+            11 => Some(VerificationError::WrongLength {
+                field: "",
+                scheme: "",
+                expected: 0,
+                actual: 0,
+            }),
+            12 => Some(VerificationError::BrokenChainLink { round: 0 }),
+            13 => Some(VerificationError::TrustedRoundNotReached { trusted_round: 0 }),
+            14 => Some(VerificationError::InvalidReshareProof),
+            15 => Some(VerificationError::InvalidBeaconField {
+                field: "",
+                expected: &[],
+                actual: 0,
+            }),
+            16 => Some(VerificationError::InvalidRange { start: 0, end: 0 }),
+            17 => Some(VerificationError::UnsupportedScheme { scheme: "" }),
+            _ => None,
+        }
+    }
+}
+
+/// reject a public key of the wrong length for the scheme up front, e.g. a g2 key passed
+/// for a scheme with keys on g1, rather than failing to decode it
+fn check_public_key_length(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+) -> Result<(), VerificationError> {
+    if public_key.len() != scheme_id.public_key_length() {
+        return Err(VerificationError::WrongLength {
+            field: "public_key",
+            scheme: scheme_id.name(),
+            expected: scheme_id.public_key_length(),
+            actual: public_key.len(),
+        });
+    }
+    Ok(())
+}
+
+fn check_signature_length(scheme_id: &SchemeID, signature: &[u8]) -> Result<(), VerificationError> {
+    if signature.len() != scheme_id.signature_length() {
+        return Err(VerificationError::WrongLength {
+            field: "signature",
+            scheme: scheme_id.name(),
+            expected: scheme_id.signature_length(),
+            actual: signature.len(),
+        });
+    }
+    Ok(())
 }
 
 /// verify a randomness beacon for a given scheme and public key
@@ -82,526 +531,3052 @@ pub fn verify_beacon(
     public_key: &[u8],
     beacon: &Beacon,
 ) -> Result<(), VerificationError> {
-    if Sha256::digest(&beacon.signature).to_vec() != beacon.randomness {
+    verify_beacon_with_expander::<DefaultExpander>(scheme_id, public_key, beacon)
+}
+
+/// verify a randomness beacon for a given scheme and public key, hashing the message to the
+/// curve with the message expander `X` (e.g. `ExpandMsgXof`) instead of the default
+/// `ExpandMsgXmd<Sha256>`. The scheme's usual DST is still used; networks that also change
+/// the DST can use `verify_on_g1_with_expander`/`verify_on_g2_with_expander` directly, and
+/// those that only change the DST can use [verify_beacon_with_dst].
+pub fn verify_beacon_with_expander<X: ExpandMessage>(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    beacon: &Beacon,
+) -> Result<(), VerificationError> {
+    verify_beacon_inner::<X>(
+        scheme_id,
+        public_key,
+        beacon,
+        RandomnessFormat::default(),
+        scheme_id.dst(),
+        1,
+    )
+}
+
+/// verify a randomness beacon for a chain whose first round is `genesis_round` rather than 1,
+/// e.g. an experimental network starting at round 0. Rounds before it are rejected.
+pub fn verify_beacon_from_genesis_round(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    beacon: &Beacon,
+    genesis_round: u64,
+) -> Result<(), VerificationError> {
+    verify_beacon_inner::<DefaultExpander>(
+        scheme_id,
+        public_key,
+        beacon,
+        RandomnessFormat::default(),
+        scheme_id.dst(),
+        genesis_round,
+    )
+}
+
+/// verify a randomness beacon for a given scheme and public key, checking its randomness was
+/// derived from the signature using `format` rather than the default `sha256(signature)`.
+/// This is mostly useful for verifying archives of historical drand deployments.
+pub fn verify_beacon_with_randomness_format(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    beacon: &Beacon,
+    format: RandomnessFormat,
+) -> Result<(), VerificationError> {
+    verify_beacon_inner::<DefaultExpander>(
+        scheme_id,
+        public_key,
+        beacon,
+        format,
+        scheme_id.dst(),
+        1,
+    )
+}
+
+/// verify a randomness beacon for a given scheme and public key, hashing the message to the
+/// curve with `dst` rather than the scheme's DST. The signature is still checked on the
+/// scheme's groups. This is an escape hatch for experimenting with new domain separation tags:
+/// drand's networks all use their scheme's DST, so [verify_beacon] should be used for them.
+pub fn verify_beacon_with_dst(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    beacon: &Beacon,
+    dst: &str,
+) -> Result<(), VerificationError> {
+    verify_beacon_inner::<DefaultExpander>(
+        scheme_id,
+        public_key,
+        beacon,
+        RandomnessFormat::default(),
+        dst,
+        1,
+    )
+}
+
+fn verify_beacon_inner<X: ExpandMessage>(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    beacon: &Beacon,
+    format: RandomnessFormat,
+    dst: &str,
+    genesis_round: u64,
+) -> Result<(), VerificationError> {
+    check_public_key_length(scheme_id, public_key)?;
+    if beacon.round_number < genesis_round {
+        return Err(VerificationError::InvalidRound);
+    }
+    check_previous_signature(scheme_id, beacon)?;
+    if format.randomness(&beacon.signature) != beacon.randomness {
         return Err(VerificationError::InvalidRandomness);
     }
+    if *scheme_id == SchemeID::PedersenBlsChained && beacon.previous_signature.is_empty() {
+        return Err(VerificationError::ChainedBeaconNeedsPreviousSignature);
+    }
+    check_signature_length(scheme_id, &beacon.signature)?;
     match scheme_id {
-        SchemeID::PedersenBlsChained => verify_on_g2(
+        SchemeID::PedersenBlsChained => verify_on_g2_with_expander::<X>(
             public_key,
             &chained_beacon_message(beacon)?,
             &beacon.signature,
-            DST_G2,
-        ),
-        SchemeID::PedersenBlsUnchained => verify_on_g2(
-            public_key,
-            &unchained_beacon_message(beacon)?,
-            &beacon.signature,
-            DST_G2,
-        ),
-        SchemeID::UnchainedOnG1 => verify_on_g1(
-            public_key,
-            &unchained_beacon_message(beacon)?,
-            &beacon.signature,
-            DST_G2,
+            dst,
         ),
-        SchemeID::UnchainedOnG1RFC9380 => verify_on_g1(
+        SchemeID::PedersenBlsUnchained => verify_on_g2_with_expander::<X>(
             public_key,
             &unchained_beacon_message(beacon)?,
             &beacon.signature,
-            DST_G1,
+            dst,
         ),
+        SchemeID::UnchainedOnG1 | SchemeID::UnchainedOnG1RFC9380 => {
+            verify_on_g1_with_expander::<X>(
+                public_key,
+                &unchained_beacon_message(beacon)?,
+                &beacon.signature,
+                dst,
+            )
+        }
     }
 }
 
-fn unchained_beacon_message(beacon: &Beacon) -> Result<Vec<u8>, VerificationError> {
-    let round_bytes = beacon.round_number.to_be_bytes();
-
-    Ok(Sha256::digest(&round_bytes).to_vec())
-}
-
-fn chained_beacon_message(beacon: &Beacon) -> Result<Vec<u8>, VerificationError> {
-    if beacon.previous_signature.is_empty() {
-        Err(VerificationError::ChainedBeaconNeedsPreviousSignature)
-    } else {
-        let message: Vec<u8> = beacon
-            .previous_signature
-            .clone()
-            .into_iter()
-            .chain(beacon.round_number.to_be_bytes())
-            .collect();
-
-        Ok(Sha256::digest(message.as_slice()).to_vec())
-    }
-}
-
-/// verify a signature where the public key is on g1 and the signature is on g2 for a
-/// given domain separation tag
-pub fn verify_on_g2(
+/// verify a signature over an arbitrary `message`, e.g. from a threshold signing service built
+/// on drand's keys, on the scheme's groups and with its DST. Unlike [verify_beacon], the message
+/// is hashed to the curve as given rather than derived from a round.
+pub fn verify_message(
+    scheme_id: &SchemeID,
     public_key: &[u8],
     message: &[u8],
     signature: &[u8],
-    domain_separation_tag: &str,
 ) -> Result<(), VerificationError> {
-    let pub_key_bytes: &[u8; 48] = public_key
-        .try_into()
-        .map_err(|_| VerificationError::InvalidPublicKey)?;
+    check_public_key_length(scheme_id, public_key)?;
+    match scheme_id {
+        SchemeID::PedersenBlsChained | SchemeID::PedersenBlsUnchained => {
+            verify_on_g2(public_key, message, signature, scheme_id.dst())
+        }
+        SchemeID::UnchainedOnG1 | SchemeID::UnchainedOnG1RFC9380 => {
+            verify_on_g1(public_key, message, signature, scheme_id.dst())
+        }
+    }
+}
 
-    let sig_bytes: &[u8; 96] = signature
-        .try_into()
-        .map_err(|_| VerificationError::InvalidSignatureLength)?;
+/// find which of `candidate_rounds` an unchained `signature` was made for, e.g. to debug a
+/// beacon whose `round_number` disagrees with the round actually signed. The round can't be
+/// recovered from the signature, so each candidate is tried in turn, costing a hash to the curve
+/// and a pairing per round: keep the window small.
+/// Always `None` for the chained scheme, as its messages also depend on the previous signature.
+pub fn find_signed_round(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    signature: &[u8],
+    candidate_rounds: RangeInclusive<u64>,
+) -> Option<u64> {
+    if *scheme_id == SchemeID::PedersenBlsChained {
+        return None;
+    }
+    candidate_rounds.into_iter().find(|round| {
+        let message = Sha256::digest(&round.to_be_bytes());
+        verify_message(scheme_id, public_key, &message, signature).is_ok()
+    })
+}
 
-    let p = G1Affine::from_compressed(pub_key_bytes).unwrap_or(G1Affine::identity());
+/// whether `a` and `b` are the same public key for `scheme_id`, comparing the decoded points
+/// rather than the bytes, so a key pinned in compressed form matches the same key fetched in
+/// uncompressed form. Either key failing to decode is an error rather than a mismatch.
+pub fn public_keys_equal(
+    a: &[u8],
+    b: &[u8],
+    scheme_id: &SchemeID,
+) -> Result<bool, VerificationError> {
+    let a = VerifyingKey::from_any_encoding(scheme_id, a)?;
+    let b = VerifyingKey::from_any_encoding(scheme_id, b)?;
+    Ok(a.point == b.point)
+}
 
-    let q = G2Affine::from_compressed(sig_bytes).unwrap_or(G2Affine::identity());
+/// the first of `candidate_schemes` that `beacon` verifies under with `public_key`, e.g. to work
+/// out the scheme of a beacon found without its chain info. The candidates for a key can be
+/// found with [SchemeID::schemes_for_key_length]. `None` if it verifies under none of them.
+pub fn identify_scheme(
+    public_key: &[u8],
+    beacon: &Beacon,
+    candidate_schemes: &[SchemeID],
+) -> Option<SchemeID> {
+    candidate_schemes
+        .iter()
+        .find(|scheme_id| verify_beacon(scheme_id, public_key, beacon).is_ok())
+        .cloned()
+}
 
-    if p.is_on_curve().unwrap_u8() != 1 {
-        return Err(VerificationError::InvalidPublicKey);
-    }
+/// the rounds of `expected` with no beacon in `beacons`, in ascending order, e.g. to find the
+/// gaps in a sparse range response before fetching them individually. Beacons outside
+/// `expected` are ignored, and nothing is verified.
+pub fn missing_rounds(beacons: &[Beacon], expected: RangeInclusive<u64>) -> Vec<u64> {
+    let present: HashSet<u64> = beacons.iter().map(|beacon| beacon.round_number).collect();
+    expected.filter(|round| !present.contains(round)).collect()
+}
 
-    if p.is_identity().unwrap_u8() == 1 {
-        return Err(VerificationError::InvalidPublicKey);
+/// the most rounds [verify_range_aggregate] accepts in one range, as it hashes every round to
+/// the curve, so a relay can't keep it busy with an arbitrarily long range
+pub const MAX_AGGREGATE_RANGE_LEN: u64 = 1000;
+
+/// verify an aggregate signature covering every round from `start` to `end` inclusive, as
+/// served by drand's experimental aggregated range responses: the sum of the signatures of
+/// the rounds, verified with a single pairing check against the sum of their hashed messages.
+/// Only unchained schemes are supported, as chained messages depend on each round's previous
+/// signature. Hashing each round to the curve still takes time linear in the size of the range,
+/// so ranges of more than [MAX_AGGREGATE_RANGE_LEN] rounds fail with `InvalidRange`.
+///
+/// This is experimental, like the API it supports, and may change.
+pub fn verify_range_aggregate(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    start: u64,
+    end: u64,
+    aggregate_signature: &[u8],
+) -> Result<(), VerificationError> {
+    check_public_key_length(scheme_id, public_key)?;
+    if start == 0 {
+        return Err(VerificationError::InvalidRound);
     }
-
-    if message.is_empty() {
-        return Err(VerificationError::EmptyMessage);
+    if start > end || end - start >= MAX_AGGREGATE_RANGE_LEN {
+        return Err(VerificationError::InvalidRange { start, end });
     }
+    let dst = scheme_id.dst().as_bytes();
+    let message = |round: u64| Sha256::digest(&round.to_be_bytes());
 
-    if signature.is_empty() {
-        return Err(VerificationError::InvalidSignatureLength);
+    match scheme_id {
+        SchemeID::PedersenBlsChained => Err(VerificationError::UnsupportedScheme {
+            scheme: scheme_id.name(),
+        }),
+        SchemeID::PedersenBlsUnchained => {
+            let p = decode_g1_public_key(public_key)?;
+            let m = (start..=end).fold(G2Projective::identity(), |sum, round| {
+                sum + <G2Projective as HashToCurve<DefaultExpander>>::hash_to_curve(
+                    message(round),
+                    dst,
+                )
+            });
+            check_g2_pairing(&p, &G2Affine::from(m), aggregate_signature)
+        }
+        SchemeID::UnchainedOnG1 | SchemeID::UnchainedOnG1RFC9380 => {
+            let p = decode_g2_public_key(public_key)?;
+            let m = (start..=end).fold(G1Projective::identity(), |sum, round| {
+                sum + <G1Projective as HashToCurve<DefaultExpander>>::hash_to_curve(
+                    message(round),
+                    dst,
+                )
+            });
+            check_g1_pairing(&p, &G1Affine::from(m), aggregate_signature)
+        }
     }
+}
 
-    let m = <G2Projective as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve(
-        message,
-        domain_separation_tag.as_bytes(),
-    );
+/// verify a randomness beacon for a given scheme and public key, returning the decoded and
+/// validated public key so it can be reused for further verifications or key-dependent work
+pub fn verify_beacon_returning_key(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    beacon: &Beacon,
+) -> Result<VerifyingKey, VerificationError> {
+    let key = VerifyingKey::new(scheme_id, public_key)?;
+    key.verify(beacon)?;
+    Ok(key)
+}
 
-    let m_prepared = G2Prepared::from(G2Affine::from(m));
-    let q_prepared = G2Prepared::from(q);
-    let exp = multi_miller_loop(&[
-        (&p.neg(), &m_prepared),
-        (&G1Affine::generator(), &q_prepared),
-    ]);
+/// verify a run of consecutive beacons, newest first, back to a round that is already trusted,
+/// e.g. from a recent checkpoint rather than all the way from genesis. Each beacon is verified
+/// and, for the chained scheme, checked to carry the signature of the beacon after it in
+/// `beacons_desc` as its previous signature. Verification stops successfully at the beacon
+/// for `trusted_round`, so any older beacons are ignored.
+pub fn verify_chain_back(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    beacons_desc: &[Beacon],
+    trusted_round: u64,
+) -> Result<(), VerificationError> {
+    let key = VerifyingKey::new(scheme_id, public_key)?;
+    for (i, beacon) in beacons_desc.iter().enumerate() {
+        if beacon.round_number < trusted_round {
+            break;
+        }
+        key.verify(beacon)?;
+        if beacon.round_number == trusted_round {
+            return Ok(());
+        }
 
-    if exp.final_exponentiation() != Gt::identity() {
-        Err(VerificationError::SignatureFailedVerification)
-    } else {
-        Ok(())
+        let Some(older) = beacons_desc.get(i + 1) else {
+            break;
+        };
+        let linked = older.round_number + 1 == beacon.round_number
+            && (*scheme_id != SchemeID::PedersenBlsChained
+                || beacon.previous_signature == older.signature);
+        if !linked {
+            return Err(VerificationError::BrokenChainLink {
+                round: beacon.round_number,
+            });
+        }
     }
+    Err(VerificationError::TrustedRoundNotReached { trusted_round })
 }
 
-/// verify a signature where the public key is on g2 and the signature is on g1 for a
-/// given domain separation tag
-pub fn verify_on_g1(
-    public_key: &[u8],
-    message: &[u8],
-    signature: &[u8],
-    domain_separation_tag: &str,
-) -> Result<(), VerificationError> {
-    let pub_key_bytes: &[u8; 96] = public_key
-        .try_into()
-        .map_err(|_| VerificationError::InvalidPublicKey)?;
+/// a public key that has been decoded and validated once for a given scheme, so the work
+/// doesn't have to be repeated for every beacon
+#[derive(Debug, PartialEq, Clone)]
+pub struct VerifyingKey {
+    scheme_id: SchemeID,
+    point: PublicKeyPoint,
+    /// the first round of the chain; earlier rounds are rejected
+    genesis_round: u64,
+}
 
-    let sig_bytes: &[u8; 48] = signature
-        .try_into()
-        .map_err(|_| VerificationError::InvalidSignatureLength)?;
+/// the decoded curve point of a public key. Schemes with signatures on g2 have their public
+/// key on g1 and vice versa
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PublicKeyPoint {
+    G1(G1Affine),
+    G2(G2Affine),
+}
 
-    let signature_point = G1Affine::from_compressed(sig_bytes).unwrap_or(G1Affine::identity());
-    let pubkey_point = G2Affine::from_compressed(pub_key_bytes).unwrap_or(G2Affine::identity());
+impl VerifyingKey {
+    /// decode and validate a compressed public key for the given scheme
+    pub fn new(scheme_id: &SchemeID, public_key: &[u8]) -> Result<Self, VerificationError> {
+        check_public_key_length(scheme_id, public_key)?;
+        let point = match scheme_id {
+            SchemeID::PedersenBlsChained | SchemeID::PedersenBlsUnchained => {
+                PublicKeyPoint::G1(decode_g1_public_key(public_key)?)
+            }
+            SchemeID::UnchainedOnG1 | SchemeID::UnchainedOnG1RFC9380 => {
+                PublicKeyPoint::G2(decode_g2_public_key(public_key)?)
+            }
+        };
+        Ok(VerifyingKey {
+            scheme_id: scheme_id.clone(),
+            point,
+            genesis_round: 1,
+        })
+    }
 
-    if pubkey_point.is_on_curve().unwrap_u8() != 1 {
-        return Err(VerificationError::InvalidPublicKey);
+    /// decode and validate the public key of a chain once, with the chain's scheme and genesis
+    /// round, e.g. for a long-lived verifier of the chain's beacons
+    pub fn from_chain_info(chain_info: &ChainInfo) -> Result<Self, VerificationError> {
+        Ok(Self::new(&chain_info.scheme_id, &chain_info.public_key)?
+            .with_genesis_round(chain_info.genesis_round))
     }
 
-    if pubkey_point.is_identity().unwrap_u8() == 1 {
-        return Err(VerificationError::InvalidPublicKey);
+    /// verify beacons of a chain whose first round is `genesis_round` rather than 1, e.g. an
+    /// experimental network starting at round 0. Rounds before it are rejected.
+    pub fn with_genesis_round(mut self, genesis_round: u64) -> Self {
+        self.genesis_round = genesis_round;
+        self
     }
 
-    if message.is_empty() {
+    /// build and validate a key for a scheme with its public key on g1 from the affine
+    /// coordinates of the point, e.g. as given by chain configs that don't use compressed
+    /// points. Each coordinate is a big-endian field element of up to 48 bytes.
+    pub fn from_coordinates(
+        scheme_id: &SchemeID,
+        x: &[u8],
+        y: &[u8],
+    ) -> Result<Self, VerificationError> {
+        if scheme_id.public_key_length() != 48 {
+            return Err(VerificationError::InvalidPublicKey);
+        }
+        let mut uncompressed = [0u8; 96];
+        uncompressed[..48].copy_from_slice(&field_element(x)?);
+        uncompressed[48..].copy_from_slice(&field_element(y)?);
+
+        let point = Option::<G1Affine>::from(G1Affine::from_uncompressed(&uncompressed))
+            .ok_or(VerificationError::InvalidPublicKey)?;
+        Self::new(scheme_id, &point.to_compressed())
+    }
+
+    /// like `from_coordinates`, for a scheme with its public key on g2. Each coordinate is an
+    /// element of Fp2 given as its `(c0, c1)` components, i.e. `x = x.0 + x.1 * u`.
+    pub fn from_g2_coordinates(
+        scheme_id: &SchemeID,
+        x: (&[u8], &[u8]),
+        y: (&[u8], &[u8]),
+    ) -> Result<Self, VerificationError> {
+        if scheme_id.public_key_length() != 96 {
+            return Err(VerificationError::InvalidPublicKey);
+        }
+        // the uncompressed encoding puts the c1 component of each coordinate first
+        let mut uncompressed = [0u8; 192];
+        for (i, component) in [x.1, x.0, y.1, y.0].into_iter().enumerate() {
+            uncompressed[i * 48..(i + 1) * 48].copy_from_slice(&field_element(component)?);
+        }
+
+        let point = Option::<G2Affine>::from(G2Affine::from_uncompressed(&uncompressed))
+            .ok_or(VerificationError::InvalidPublicKey)?;
+        Self::new(scheme_id, &point.to_compressed())
+    }
+
+    /// like `new`, but also accepting the uncompressed encoding of the key
+    fn from_any_encoding(
+        scheme_id: &SchemeID,
+        public_key: &[u8],
+    ) -> Result<Self, VerificationError> {
+        let compressed_length = scheme_id.public_key_length();
+        if public_key.len() != 2 * compressed_length {
+            return Self::new(scheme_id, public_key);
+        }
+        let compressed = match compressed_length {
+            48 => public_key
+                .try_into()
+                .ok()
+                .and_then(|bytes| Option::<G1Affine>::from(G1Affine::from_uncompressed(bytes)))
+                .map(|point| point.to_compressed().to_vec()),
+            _ => public_key
+                .try_into()
+                .ok()
+                .and_then(|bytes| Option::<G2Affine>::from(G2Affine::from_uncompressed(bytes)))
+                .map(|point| point.to_compressed().to_vec()),
+        };
+        Self::new(
+            scheme_id,
+            &compressed.ok_or(VerificationError::InvalidPublicKey)?,
+        )
+    }
+
+    /// the scheme this key was decoded for
+    pub fn scheme_id(&self) -> &SchemeID {
+        &self.scheme_id
+    }
+
+    /// the validated public key point
+    pub fn point(&self) -> &PublicKeyPoint {
+        &self.point
+    }
+
+    /// verify a randomness beacon against this key
+    pub fn verify(&self, beacon: &Beacon) -> Result<(), VerificationError> {
+        if beacon.round_number < self.genesis_round {
+            return Err(VerificationError::InvalidRound);
+        }
+        check_previous_signature(&self.scheme_id, beacon)?;
+        if expected_randomness(&beacon.signature) != beacon.randomness {
+            return Err(VerificationError::InvalidRandomness);
+        }
+        if self.scheme_id == SchemeID::PedersenBlsChained && beacon.previous_signature.is_empty() {
+            return Err(VerificationError::ChainedBeaconNeedsPreviousSignature);
+        }
+        check_signature_length(&self.scheme_id, &beacon.signature)?;
+        self.verify_signature(beacon)
+    }
+
+    fn verify_signature(&self, beacon: &Beacon) -> Result<(), VerificationError> {
+        match (&self.scheme_id, &self.point) {
+            (SchemeID::PedersenBlsChained, PublicKeyPoint::G1(p)) => {
+                verify_g2_signature::<DefaultExpander>(
+                    p,
+                    &chained_beacon_message(beacon)?,
+                    &beacon.signature,
+                    DST_G2,
+                )
+            }
+            (SchemeID::PedersenBlsUnchained, PublicKeyPoint::G1(p)) => {
+                verify_g2_signature::<DefaultExpander>(
+                    p,
+                    &unchained_beacon_message(beacon)?,
+                    &beacon.signature,
+                    DST_G2,
+                )
+            }
+            (SchemeID::UnchainedOnG1, PublicKeyPoint::G2(p)) => {
+                verify_g1_signature::<DefaultExpander>(
+                    p,
+                    &unchained_beacon_message(beacon)?,
+                    &beacon.signature,
+                    DST_G2,
+                )
+            }
+            (SchemeID::UnchainedOnG1RFC9380, PublicKeyPoint::G2(p)) => {
+                verify_g1_signature::<DefaultExpander>(
+                    p,
+                    &unchained_beacon_message(beacon)?,
+                    &beacon.signature,
+                    DST_G1,
+                )
+            }
+            _ => Err(VerificationError::InvalidPublicKey),
+        }
+    }
+}
+
+/// the outcome of each check made when verifying a beacon, for working out which part of a
+/// beacon is wrong rather than just that it doesn't verify
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct VerifyReport {
+    /// whether the randomness is `sha256(signature)`
+    pub randomness_ok: bool,
+    /// whether the signature is valid for the round under the public key
+    pub signature_ok: bool,
+}
+
+impl VerifyReport {
+    /// whether the beacon passed every check
+    pub fn is_ok(&self) -> bool {
+        self.randomness_ok && self.signature_ok
+    }
+}
+
+/// verify a beacon's randomness and signature independently of one another, reporting the
+/// outcome of both rather than stopping at the first failure as `verify_beacon` does.
+/// An invalid public key fails the signature check.
+pub fn verify_detailed(scheme_id: &SchemeID, public_key: &[u8], beacon: &Beacon) -> VerifyReport {
+    VerifyReport {
+        randomness_ok: expected_randomness(&beacon.signature) == beacon.randomness,
+        signature_ok: VerifyingKey::new(scheme_id, public_key)
+            .and_then(|key| key.verify_signature(beacon))
+            .is_ok(),
+    }
+}
+
+/// a group of the BLS12-381 pairing
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Group {
+    G1,
+    G2,
+}
+
+/// the parameters a beacon was verified with, e.g. for audit logs recording exactly how each
+/// beacon was checked
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct VerifyAudit {
+    /// the domain separation tag the round's message was hashed to the curve with
+    pub dst: String,
+    /// the group of the signature and of the hashed message
+    pub signature_group: Group,
+    /// the group of the public key
+    pub key_group: Group,
+}
+
+/// verify a randomness beacon as `verify_beacon` does, also returning the DST and groups the
+/// scheme verified it with
+pub fn verify_beacon_audited(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    beacon: &Beacon,
+) -> Result<VerifyAudit, VerificationError> {
+    verify_beacon(scheme_id, public_key, beacon)?;
+    let (signature_group, key_group) = match scheme_id {
+        SchemeID::PedersenBlsChained | SchemeID::PedersenBlsUnchained => (Group::G2, Group::G1),
+        SchemeID::UnchainedOnG1 | SchemeID::UnchainedOnG1RFC9380 => (Group::G1, Group::G2),
+    };
+    Ok(VerifyAudit {
+        dst: scheme_id.dst().to_string(),
+        signature_group,
+        key_group,
+    })
+}
+
+/// the randomness the network derives from a beacon's signature, i.e. `sha256(signature)`.
+/// This doesn't verify anything: it can be used to precompute the randomness for a
+/// signature you expect the network to produce, while `verify_beacon` checks it matches.
+pub fn expected_randomness(signature: &[u8]) -> Vec<u8> {
+    Sha256::digest(signature).to_vec()
+}
+
+/// how a beacon's randomness is derived from its signature. Features such as `keccak` add
+/// variants, so matches outside the crate need a wildcard arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum RandomnessFormat {
+    /// `sha256(signature)`, as used by all current drand networks
+    #[default]
+    Sha256,
+    /// `sha256(sha256(signature))`, as used by at least one historical drand variant
+    DoubleSha256,
+    /// `keccak256(signature)`, as re-derived by some on-chain drand oracles to match EVM
+    /// tooling
+    #[cfg(feature = "keccak")]
+    Keccak256,
+}
+
+impl RandomnessFormat {
+    /// the randomness derived from `signature` in this format
+    pub fn randomness(&self, signature: &[u8]) -> Vec<u8> {
+        match self {
+            RandomnessFormat::Sha256 => expected_randomness(signature),
+            RandomnessFormat::DoubleSha256 => expected_randomness(&expected_randomness(signature)),
+            #[cfg(feature = "keccak")]
+            RandomnessFormat::Keccak256 => sha3::Keccak256::digest(signature).to_vec(),
+        }
+    }
+}
+
+/// unchained beacons don't sign over the previous signature, so one being present means the
+/// beacon was built from the wrong data, e.g. mixing up chained and unchained sources
+fn check_previous_signature(
+    scheme_id: &SchemeID,
+    beacon: &Beacon,
+) -> Result<(), VerificationError> {
+    if *scheme_id != SchemeID::PedersenBlsChained && !beacon.previous_signature.is_empty() {
+        return Err(VerificationError::UnexpectedPreviousSignature);
+    }
+    Ok(())
+}
+
+fn unchained_beacon_message(beacon: &Beacon) -> Result<Vec<u8>, VerificationError> {
+    let round_bytes = beacon.round_number.to_be_bytes();
+
+    Ok(Sha256::digest(&round_bytes).to_vec())
+}
+
+fn chained_beacon_message(beacon: &Beacon) -> Result<Vec<u8>, VerificationError> {
+    if beacon.previous_signature.is_empty() {
+        Err(VerificationError::ChainedBeaconNeedsPreviousSignature)
+    } else {
+        let message: Vec<u8> = beacon
+            .previous_signature
+            .clone()
+            .into_iter()
+            .chain(beacon.round_number.to_be_bytes())
+            .collect();
+
+        Ok(Sha256::digest(message.as_slice()).to_vec())
+    }
+}
+
+/// left-pad a big-endian field element to 48 bytes. The top three bits are the flags of the
+/// encodings of points rather than part of the element, and are never set for an element
+/// smaller than the modulus, so elements setting them are rejected rather than read as flags.
+fn field_element(bytes: &[u8]) -> Result<[u8; 48], VerificationError> {
+    if bytes.len() > 48 {
+        return Err(VerificationError::InvalidPublicKey);
+    }
+    let mut element = [0u8; 48];
+    element[48 - bytes.len()..].copy_from_slice(bytes);
+    if element[0] & 0xe0 != 0 {
+        return Err(VerificationError::InvalidPublicKey);
+    }
+    Ok(element)
+}
+
+pub(crate) fn decode_g1_public_key(public_key: &[u8]) -> Result<G1Affine, VerificationError> {
+    let pub_key_bytes: &[u8; 48] = public_key
+        .try_into()
+        .map_err(|_| VerificationError::InvalidPublicKey)?;
+
+    let p = G1Affine::from_compressed(pub_key_bytes).unwrap_or(G1Affine::identity());
+
+    if p.is_on_curve().unwrap_u8() != 1 {
+        return Err(VerificationError::InvalidPublicKey);
+    }
+
+    if p.is_identity().unwrap_u8() == 1 {
+        return Err(VerificationError::InvalidPublicKey);
+    }
+
+    Ok(p)
+}
+
+pub(crate) fn decode_g2_public_key(public_key: &[u8]) -> Result<G2Affine, VerificationError> {
+    let pub_key_bytes: &[u8; 96] = public_key
+        .try_into()
+        .map_err(|_| VerificationError::InvalidPublicKey)?;
+
+    let pubkey_point = G2Affine::from_compressed(pub_key_bytes).unwrap_or(G2Affine::identity());
+
+    if pubkey_point.is_on_curve().unwrap_u8() != 1 {
+        return Err(VerificationError::InvalidPublicKey);
+    }
+
+    if pubkey_point.is_identity().unwrap_u8() == 1 {
+        return Err(VerificationError::InvalidPublicKey);
+    }
+
+    Ok(pubkey_point)
+}
+
+/// check that `signature` has the right length for the scheme and decodes to a point in the
+/// right subgroup, without verifying it against a public key or message
+pub fn validate_signature(scheme_id: &SchemeID, signature: &[u8]) -> Result<(), VerificationError> {
+    check_signature_length(scheme_id, signature)?;
+
+    // `from_compressed` checks the point is on the curve and in the prime order subgroup
+    let is_identity = match scheme_id {
+        SchemeID::PedersenBlsChained | SchemeID::PedersenBlsUnchained => {
+            let bytes: &[u8; 96] = signature.try_into().unwrap();
+            Option::<G2Affine>::from(G2Affine::from_compressed(bytes)).map(|q| q.is_identity())
+        }
+        SchemeID::UnchainedOnG1 | SchemeID::UnchainedOnG1RFC9380 => {
+            let bytes: &[u8; 48] = signature.try_into().unwrap();
+            Option::<G1Affine>::from(G1Affine::from_compressed(bytes)).map(|q| q.is_identity())
+        }
+    };
+
+    match is_identity {
+        Some(is_identity) if is_identity.unwrap_u8() == 0 => Ok(()),
+        _ => Err(VerificationError::InvalidSignaturePoint),
+    }
+}
+
+/// verify a signature where the public key is on g1 and the signature is on g2 for a
+/// given domain separation tag
+pub fn verify_on_g2(
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+    domain_separation_tag: &str,
+) -> Result<(), VerificationError> {
+    verify_on_g2_with_expander::<DefaultExpander>(
+        public_key,
+        message,
+        signature,
+        domain_separation_tag,
+    )
+}
+
+/// like `verify_on_g2`, but hashing the message to the curve with the message expander `X`
+pub fn verify_on_g2_with_expander<X: ExpandMessage>(
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+    domain_separation_tag: &str,
+) -> Result<(), VerificationError> {
+    if public_key.len() != 48 {
+        return Err(VerificationError::InvalidPublicKey);
+    }
+
+    if signature.len() != 96 {
+        return Err(VerificationError::InvalidSignatureLength);
+    }
+
+    let p = decode_g1_public_key(public_key)?;
+    verify_g2_signature::<X>(&p, message, signature, domain_separation_tag)
+}
+
+fn verify_g2_signature<X: ExpandMessage>(
+    p: &G1Affine,
+    message: &[u8],
+    signature: &[u8],
+    domain_separation_tag: &str,
+) -> Result<(), VerificationError> {
+    if signature.len() != 96 {
+        return Err(VerificationError::InvalidSignatureLength);
+    }
+
+    if message.is_empty() {
+        return Err(VerificationError::EmptyMessage);
+    }
+
+    let m =
+        <G2Projective as HashToCurve<X>>::hash_to_curve(message, domain_separation_tag.as_bytes());
+    check_g2_pairing(p, &G2Affine::from(m), signature)
+}
+
+fn check_g2_pairing(p: &G1Affine, m: &G2Affine, signature: &[u8]) -> Result<(), VerificationError> {
+    check_identity(g2_pairing(p, m, signature)?)
+}
+
+fn check_identity(result: Gt) -> Result<(), VerificationError> {
+    if result != Gt::identity() {
+        Err(VerificationError::SignatureFailedVerification)
+    } else {
+        Ok(())
+    }
+}
+
+fn g2_pairing(p: &G1Affine, m: &G2Affine, signature: &[u8]) -> Result<Gt, VerificationError> {
+    let sig_bytes: &[u8; 96] = signature
+        .try_into()
+        .map_err(|_| VerificationError::InvalidSignatureLength)?;
+
+    let q = G2Affine::from_compressed(sig_bytes).unwrap_or(G2Affine::identity());
+
+    let m_prepared = G2Prepared::from(*m);
+    let q_prepared = G2Prepared::from(q);
+    let exp = multi_miller_loop(&[
+        (&p.neg(), &m_prepared),
+        (&G1Affine::generator(), &q_prepared),
+    ]);
+    Ok(exp.final_exponentiation())
+}
+
+/// hash a message to g2 as `verify_on_g2` does, e.g. to cache the point ahead of time in
+/// compressed form for `verify_on_g2_with_message_point`. Unlike `verify_on_g2`, an empty
+/// message isn't rejected, so callers hashing untrusted messages should check for one first.
+pub fn hash_to_g2(message: &[u8], domain_separation_tag: &str) -> G2Affine {
+    G2Affine::from(
+        <G2Projective as HashToCurve<DefaultExpander>>::hash_to_curve(
+            message,
+            domain_separation_tag.as_bytes(),
+        ),
+    )
+}
+
+/// like `verify_on_g2`, but with the message already hashed to g2 and compressed, e.g. by
+/// `hash_to_g2(..).to_compressed()`. The point is checked to be a valid, non-identity point
+/// of the subgroup before it is used.
+pub fn verify_on_g2_with_message_point(
+    public_key: &[u8],
+    message_point: &[u8],
+    signature: &[u8],
+) -> Result<(), VerificationError> {
+    if public_key.len() != 48 {
+        return Err(VerificationError::InvalidPublicKey);
+    }
+
+    if signature.len() != 96 {
+        return Err(VerificationError::InvalidSignatureLength);
+    }
+
+    let p = decode_g1_public_key(public_key)?;
+    let m = message_point
+        .try_into()
+        .ok()
+        .and_then(|bytes| Option::<G2Affine>::from(G2Affine::from_compressed(bytes)))
+        .filter(|m| !bool::from(m.is_identity()))
+        .ok_or(VerificationError::InvalidMessagePoint)?;
+    check_g2_pairing(&p, &m, signature)
+}
+
+/// verify a signature where the public key is on g2 and the signature is on g1 for a
+/// given domain separation tag
+pub fn verify_on_g1(
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+    domain_separation_tag: &str,
+) -> Result<(), VerificationError> {
+    verify_on_g1_with_expander::<DefaultExpander>(
+        public_key,
+        message,
+        signature,
+        domain_separation_tag,
+    )
+}
+
+/// verify a `bls-unchained-g1-rfc9380` signature over an explicitly supplied `identity`, using
+/// the scheme's RFC9380 DST. This is the check timelock encryption relies on: a ciphertext
+/// locked to an identity can be decrypted with any valid signature over it, so tlock
+/// implementations can use this to confirm a signature before decrypting with it. For drand's
+/// own beacons, the identity of a round is the SHA-256 hash of its big-endian round number.
+pub fn verify_on_g1_rfc9380(
+    public_key: &[u8],
+    identity: &[u8],
+    signature: &[u8],
+) -> Result<(), VerificationError> {
+    verify_on_g1(public_key, identity, signature, DST_G1)
+}
+
+/// like `verify_on_g1`, but hashing the message to the curve with the message expander `X`
+pub fn verify_on_g1_with_expander<X: ExpandMessage>(
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+    domain_separation_tag: &str,
+) -> Result<(), VerificationError> {
+    if public_key.len() != 96 {
+        return Err(VerificationError::InvalidPublicKey);
+    }
+
+    if signature.len() != 48 {
+        return Err(VerificationError::InvalidSignatureLength);
+    }
+
+    let pubkey_point = decode_g2_public_key(public_key)?;
+    verify_g1_signature::<X>(&pubkey_point, message, signature, domain_separation_tag)
+}
+
+fn verify_g1_signature<X: ExpandMessage>(
+    pubkey_point: &G2Affine,
+    message: &[u8],
+    signature: &[u8],
+    domain_separation_tag: &str,
+) -> Result<(), VerificationError> {
+    if signature.len() != 48 {
+        return Err(VerificationError::InvalidSignatureLength);
+    }
+
+    if message.is_empty() {
         return Err(VerificationError::EmptyMessage);
     }
 
-    if signature.is_empty() {
-        return Err(VerificationError::InvalidSignatureLength);
+    let m =
+        <G1Projective as HashToCurve<X>>::hash_to_curve(message, domain_separation_tag.as_bytes());
+    check_g1_pairing(pubkey_point, &G1Affine::from(m), signature)
+}
+
+fn check_g1_pairing(
+    pubkey_point: &G2Affine,
+    m: &G1Affine,
+    signature: &[u8],
+) -> Result<(), VerificationError> {
+    check_identity(g1_pairing(pubkey_point, m, signature)?)
+}
+
+fn g1_pairing(
+    pubkey_point: &G2Affine,
+    m: &G1Affine,
+    signature: &[u8],
+) -> Result<Gt, VerificationError> {
+    let sig_bytes: &[u8; 48] = signature
+        .try_into()
+        .map_err(|_| VerificationError::InvalidSignatureLength)?;
+
+    let signature_point = G1Affine::from_compressed(sig_bytes).unwrap_or(G1Affine::identity());
+
+    let pubkey_prepared = G2Prepared::from(pubkey_point.neg());
+    let g2_base = G2Prepared::from(G2Affine::generator());
+    let exp = multi_miller_loop(&[(m, &pubkey_prepared), (&signature_point, &g2_base)]);
+    Ok(exp.final_exponentiation())
+}
+
+/// the result of the pairing check behind `verify_on_g1` and `verify_on_g2`, for composing
+/// with other protocols: the signature is valid exactly when this is `Gt::identity()`, which
+/// is left to the caller to check. The groups are picked from the length of the public key:
+/// a 48 byte key on g1 with a signature on g2, or a 96 byte key on g2 with a signature on g1.
+pub fn pairing_check_gt(
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+    domain_separation_tag: &str,
+) -> Result<Gt, VerificationError> {
+    let expected_signature_length = match public_key.len() {
+        48 => 96,
+        96 => 48,
+        _ => return Err(VerificationError::InvalidPublicKey),
+    };
+    if signature.len() != expected_signature_length {
+        return Err(VerificationError::InvalidSignatureLength);
+    }
+    if message.is_empty() {
+        return Err(VerificationError::EmptyMessage);
+    }
+
+    if public_key.len() == 48 {
+        let p = decode_g1_public_key(public_key)?;
+        g2_pairing(&p, &hash_to_g2(message, domain_separation_tag), signature)
+    } else {
+        let pubkey_point = decode_g2_public_key(public_key)?;
+        g1_pairing(
+            &pubkey_point,
+            &hash_to_g1(message, domain_separation_tag),
+            signature,
+        )
+    }
+}
+
+/// hash a message to g1 as `verify_on_g1` does, e.g. to cache the point ahead of time in
+/// compressed form for `verify_on_g1_with_message_point`. Unlike `verify_on_g1`, an empty
+/// message isn't rejected, so callers hashing untrusted messages should check for one first.
+pub fn hash_to_g1(message: &[u8], domain_separation_tag: &str) -> G1Affine {
+    G1Affine::from(
+        <G1Projective as HashToCurve<DefaultExpander>>::hash_to_curve(
+            message,
+            domain_separation_tag.as_bytes(),
+        ),
+    )
+}
+
+/// like `verify_on_g1`, but with the message already hashed to g1 and compressed, e.g. by
+/// `hash_to_g1(..).to_compressed()`. The point is checked to be a valid, non-identity point
+/// of the subgroup before it is used.
+pub fn verify_on_g1_with_message_point(
+    public_key: &[u8],
+    message_point: &[u8],
+    signature: &[u8],
+) -> Result<(), VerificationError> {
+    if public_key.len() != 96 {
+        return Err(VerificationError::InvalidPublicKey);
+    }
+
+    if signature.len() != 48 {
+        return Err(VerificationError::InvalidSignatureLength);
+    }
+
+    let pubkey_point = decode_g2_public_key(public_key)?;
+    let m = message_point
+        .try_into()
+        .ok()
+        .and_then(|bytes| Option::<G1Affine>::from(G1Affine::from_compressed(bytes)))
+        .filter(|m| !bool::from(m.is_identity()))
+        .ok_or(VerificationError::InvalidMessagePoint)?;
+    check_g1_pairing(&pubkey_point, &m, signature)
+}
+
+/// the point on the scheme's signature group that `round` hashes to, compressed, e.g. to
+/// precompute the points of upcoming rounds for `verify_on_g1_with_message_point` or
+/// `verify_on_g2_with_message_point`. Chained rounds also hash the `previous_signature`,
+/// which unchained rounds must not have.
+pub fn round_message_point(
+    scheme_id: &SchemeID,
+    round: u64,
+    previous_signature: Option<&[u8]>,
+) -> Result<Vec<u8>, VerificationError> {
+    if round == 0 {
+        return Err(VerificationError::InvalidRound);
+    }
+    let beacon = Beacon {
+        round_number: round,
+        randomness: Vec::new(),
+        signature: Vec::new(),
+        previous_signature: previous_signature.unwrap_or_default().to_vec(),
+    };
+    check_previous_signature(scheme_id, &beacon)?;
+
+    Ok(match scheme_id {
+        SchemeID::PedersenBlsChained => {
+            hash_to_g2(&chained_beacon_message(&beacon)?, scheme_id.dst())
+                .to_compressed()
+                .to_vec()
+        }
+        SchemeID::PedersenBlsUnchained => {
+            hash_to_g2(&unchained_beacon_message(&beacon)?, scheme_id.dst())
+                .to_compressed()
+                .to_vec()
+        }
+        SchemeID::UnchainedOnG1 | SchemeID::UnchainedOnG1RFC9380 => {
+            hash_to_g1(&unchained_beacon_message(&beacon)?, scheme_id.dst())
+                .to_compressed()
+                .to_vec()
+        }
+    })
+}
+
+/// verify a randomness beacon for a given scheme and public key without returning early on
+/// failure. The round, randomness, public key, signature and pairing checks are all performed
+/// regardless of the outcome of the others and folded together with `subtle::Choice`, so
+/// the result doesn't reveal which check failed; every failure is reported as
+/// `SignatureFailedVerification`.
+/// Note that the hash-to-curve and pairing already dominate the verification time, so this
+/// mostly guards the cheap validity checks that would otherwise short-circuit.
+/// The lengths of the inputs are not treated as secret.
+pub fn verify_beacon_ct(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    beacon: &Beacon,
+) -> Result<(), VerificationError> {
+    let randomness_ok = expected_randomness(&beacon.signature).ct_eq(&beacon.randomness);
+    let round_ok = Choice::from((beacon.round_number != 0) as u8);
+
+    let (message, message_ok) = match scheme_id {
+        SchemeID::PedersenBlsChained => {
+            let message: Vec<u8> = beacon
+                .previous_signature
+                .iter()
+                .copied()
+                .chain(beacon.round_number.to_be_bytes())
+                .collect();
+            (
+                Sha256::digest(&message).to_vec(),
+                Choice::from(!beacon.previous_signature.is_empty() as u8),
+            )
+        }
+        _ => (
+            Sha256::digest(&beacon.round_number.to_be_bytes()).to_vec(),
+            Choice::from(beacon.previous_signature.is_empty() as u8),
+        ),
+    };
+
+    let verified = match scheme_id {
+        SchemeID::PedersenBlsChained | SchemeID::PedersenBlsUnchained => {
+            g2_signature_ct(public_key, &message, &beacon.signature, DST_G2)
+        }
+        SchemeID::UnchainedOnG1 => g1_signature_ct(public_key, &message, &beacon.signature, DST_G2),
+        SchemeID::UnchainedOnG1RFC9380 => {
+            g1_signature_ct(public_key, &message, &beacon.signature, DST_G1)
+        }
+    };
+
+    if bool::from(round_ok & randomness_ok & message_ok & verified) {
+        Ok(())
+    } else {
+        Err(VerificationError::SignatureFailedVerification)
+    }
+}
+
+fn g2_signature_ct(
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+    domain_separation_tag: &str,
+) -> Choice {
+    let key_bytes: [u8; 48] = public_key.try_into().unwrap_or([0u8; 48]);
+    let sig_bytes: [u8; 96] = signature.try_into().unwrap_or([0u8; 96]);
+    let lengths_ok = Choice::from((public_key.len() == 48 && signature.len() == 96) as u8);
+
+    let key = G1Affine::from_compressed(&key_bytes);
+    let key_ok = key.is_some() & !key.unwrap_or(G1Affine::identity()).is_identity();
+    let p = key.unwrap_or(G1Affine::generator());
+
+    let sig = G2Affine::from_compressed(&sig_bytes);
+    let sig_ok = sig.is_some();
+    let q = sig.unwrap_or(G2Affine::generator());
+
+    let m = <G2Projective as HashToCurve<DefaultExpander>>::hash_to_curve(
+        message,
+        domain_separation_tag.as_bytes(),
+    );
+
+    let m_prepared = G2Prepared::from(G2Affine::from(m));
+    let q_prepared = G2Prepared::from(q);
+    let exp = multi_miller_loop(&[
+        (&p.neg(), &m_prepared),
+        (&G1Affine::generator(), &q_prepared),
+    ]);
+
+    lengths_ok & key_ok & sig_ok & exp.final_exponentiation().ct_eq(&Gt::identity())
+}
+
+fn g1_signature_ct(
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+    domain_separation_tag: &str,
+) -> Choice {
+    let key_bytes: [u8; 96] = public_key.try_into().unwrap_or([0u8; 96]);
+    let sig_bytes: [u8; 48] = signature.try_into().unwrap_or([0u8; 48]);
+    let lengths_ok = Choice::from((public_key.len() == 96 && signature.len() == 48) as u8);
+
+    let key = G2Affine::from_compressed(&key_bytes);
+    let key_ok = key.is_some() & !key.unwrap_or(G2Affine::identity()).is_identity();
+    let pubkey_point = key.unwrap_or(G2Affine::generator());
+
+    let sig = G1Affine::from_compressed(&sig_bytes);
+    let sig_ok = sig.is_some();
+    let signature_point = sig.unwrap_or(G1Affine::generator());
+
+    let m = <G1Projective as HashToCurve<DefaultExpander>>::hash_to_curve(
+        message,
+        domain_separation_tag.as_bytes(),
+    );
+
+    let pubkey_prepared = G2Prepared::from(pubkey_point.neg());
+    let g2_base = G2Prepared::from(G2Affine::generator());
+    let exp = multi_miller_loop(&[
+        (&G1Affine::from(m), &pubkey_prepared),
+        (&signature_point, &g2_base),
+    ]);
+
+    lengths_ok & key_ok & sig_ok & exp.final_exponentiation().ct_eq(&Gt::identity())
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "serde")]
+    use crate::chain_info::ChainInfo;
+    use crate::test_util::TestSigner;
+    use crate::verify::{
+        chained_beacon_message, expected_randomness, find_signed_round, hash_to_g1, hash_to_g2,
+        identify_scheme, missing_rounds, pairing_check_gt, public_keys_equal, round_message_point,
+        unchained_beacon_message, unwrap_signature_envelope, validate_signature, verify_beacon,
+        verify_beacon_audited, verify_beacon_ct, verify_beacon_returning_key,
+        verify_beacon_with_dst, verify_beacon_with_expander, verify_beacon_with_randomness_format,
+        verify_chain_back, verify_detailed, verify_message, verify_on_g1, verify_on_g1_rfc9380,
+        verify_on_g1_with_expander, verify_on_g1_with_message_point, verify_on_g2,
+        verify_on_g2_with_expander, verify_on_g2_with_message_point, verify_range_aggregate,
+        Beacon, DefaultExpander, Group, LengthMismatch, PublicKeyPoint, RandomnessFormat, SchemeID,
+        VerificationError, VerifyAudit, VerifyReport, VerifyingKey, DST_G1, DST_G2,
+        MAX_AGGREGATE_RANGE_LEN,
+    };
+    use bls12_381::hash_to_curve::{ExpandMsgXof, HashToCurve};
+    use bls12_381::{G1Affine, G1Projective, G2Affine, G2Projective, Gt, Scalar};
+    use sha2::{Digest, Sha256};
+    use sha3::Shake256;
+    use std::collections::HashSet;
+
+    #[test]
+    fn default_beacon_verifies() {
+        let public_key = dehexify("88a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb");
+        let prev_sig = dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce");
+
+        let beacon = Beacon {
+            round_number: 397089,
+            randomness: dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
+            signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
+            previous_signature: prev_sig,
+        };
+
+        assert!(matches!(
+            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
+            Ok(()),
+        ));
+    }
+
+    #[test]
+    fn default_wrong_round_fails() {
+        let public_key = dehexify("88a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb");
+        let prev_sig = dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce");
+
+        let beacon = Beacon {
+            round_number: 1, // wrong round for randomness
+            randomness: dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
+            signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
+            previous_signature: prev_sig,
+        };
+
+        assert_error(
+            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
+            VerificationError::SignatureFailedVerification,
+        );
+    }
+
+    #[test]
+    fn default_with_invalid_randomness_fails() {
+        let public_key = dehexify("88a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb");
+        let prev_sig = dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce");
+
+        let beacon = Beacon {
+            round_number: 397089,
+            // updated the randomness hex to be wrong
+            randomness: dehexify("bd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
+            signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
+            previous_signature: prev_sig,
+        };
+
+        assert_error(
+            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
+            VerificationError::InvalidRandomness,
+        );
+    }
+
+    #[test]
+    fn default_beacon_missing_previous_sig_fails() {
+        let public_key = dehexify("88a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb");
+
+        let beacon = Beacon {
+            round_number: 397089,
+            randomness: dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
+            signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
+            previous_signature: Vec::new(),
+        };
+
+        assert_error(
+            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
+            VerificationError::ChainedBeaconNeedsPreviousSignature,
+        );
+    }
+
+    #[test]
+    fn default_beacon_invalid_public_key_fails() {
+        // public key is not correct
+        let public_key = dehexify("78a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb");
+        let prev_sig = dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce");
+
+        let beacon = Beacon {
+            round_number: 397089,
+            randomness: dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
+            signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
+            previous_signature: prev_sig,
+        };
+
+        assert_error(
+            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
+            VerificationError::InvalidPublicKey,
+        );
+    }
+
+    #[test]
+    fn default_beacon_empty_public_key_fails() {
+        let public_key = Vec::new();
+        let prev_sig = dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce");
+
+        let beacon = Beacon {
+            round_number: 397089,
+            randomness: dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
+            signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
+            previous_signature: prev_sig,
+        };
+
+        assert_error(
+            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
+            VerificationError::WrongLength {
+                field: "public_key",
+                scheme: "pedersen-bls-chained",
+                expected: 48,
+                actual: 0,
+            },
+        );
+    }
+
+    #[test]
+    fn default_beacon_infinity_public_key_fails() {
+        let public_key = G1Affine::identity().to_compressed();
+        let prev_sig = dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce");
+
+        let beacon = Beacon {
+            round_number: 397089,
+            randomness: dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
+            signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
+            previous_signature: prev_sig,
+        };
+
+        assert_error(
+            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
+            VerificationError::InvalidPublicKey,
+        );
+    }
+
+    #[test]
+    fn testnet_unchained_beacon_verifies() {
+        let public_key = dehexify("8d91ae0f4e3cd277cfc46aba26680232b0d5bb4444602cdb23442d62e17f43cdffb1104909e535430c10a6a1ce680a65");
+        let beacon = Beacon {
+            round_number: 397092,
+            randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
+            signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
+            previous_signature: Vec::new(),
+        };
+
+        assert!(verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon).is_ok());
+    }
+
+    #[test]
+    fn testnet_unchained_beacon_wrong_round_fails() {
+        let public_key = dehexify("8d91ae0f4e3cd277cfc46aba26680232b0d5bb4444602cdb23442d62e17f43cdffb1104909e535430c10a6a1ce680a65");
+        let beacon = Beacon {
+            round_number: 1, // wrong round
+            randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
+            signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
+            previous_signature: Vec::new(),
+        };
+
+        assert_error(
+            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
+            VerificationError::SignatureFailedVerification,
+        );
+    }
+
+    #[test]
+    fn testnet_unchained_beacon_randomness_not_matching_signature_fails() {
+        let public_key = dehexify("8d91ae0f4e3cd277cfc46aba26680232b0d5bb4444602cdb23442d62e17f43cdffb1104909e535430c10a6a1ce680a65");
+        let beacon = Beacon {
+            round_number: 397092,
+            // mismatching randomness
+            randomness: dehexify("a731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
+            signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
+            previous_signature: Vec::new(),
+        };
+
+        assert_error(
+            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
+            VerificationError::InvalidRandomness,
+        );
+    }
+
+    #[test]
+    fn testnet_unchained_beacon_containing_previous_sig_fails() {
+        let public_key = dehexify("8d91ae0f4e3cd277cfc46aba26680232b0d5bb4444602cdb23442d62e17f43cdffb1104909e535430c10a6a1ce680a65");
+        let beacon = Beacon {
+            round_number: 397092,
+            randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
+            signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
+            previous_signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
+        };
+
+        assert_error(
+            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
+            VerificationError::UnexpectedPreviousSignature,
+        );
+    }
+
+    #[test]
+    fn testnet_unchained_invalid_public_key_fails() {
+        // valid public key, but for wrong scheme
+        let public_key = dehexify("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31");
+        let beacon = Beacon {
+            round_number: 397092,
+            randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
+            signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
+            previous_signature: Vec::new(),
+        };
+
+        assert_error(
+            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
+            VerificationError::SignatureFailedVerification,
+        );
+    }
+
+    #[test]
+    fn testnet_unchained_beacon_empty_public_key_fails() {
+        let public_key = Vec::new();
+        let beacon = Beacon {
+            round_number: 397092,
+            randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
+            signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
+            previous_signature: Vec::new(),
+        };
+
+        assert_error(
+            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
+            VerificationError::WrongLength {
+                field: "public_key",
+                scheme: "pedersen-bls-unchained",
+                expected: 48,
+                actual: 0,
+            },
+        );
+    }
+
+    #[test]
+    fn testnet_unchained_beacon_infinity_public_key_fails() {
+        let public_key = G1Affine::identity().to_compressed();
+        let beacon = Beacon {
+            round_number: 397092,
+            randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
+            signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
+            previous_signature: Vec::new(),
+        };
+
+        assert_error(
+            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
+            VerificationError::InvalidPublicKey,
+        );
+    }
+
+    #[test]
+    fn g1g2_swap_non_rfc_beacon_verifies() {
+        let public_key = dehexify("a0b862a7527fee3a731bcb59280ab6abd62d5c0b6ea03dc4ddf6612fdfc9d01f01c31542541771903475eb1ec6615f8d0df0b8b6dce385811d6dcf8cbefb8759e5e616a3dfd054c928940766d9a5b9db91e3b697e5d70a975181e007f87fca5e");
+        let beacon = Beacon {
+            round_number: 3,
+            randomness: dehexify("a4eb0ed6c4132da066843c3bfdce732ce5013eda86e74c136ab8ccc387b798dd"),
+            signature: dehexify("8176555f90d71aa49ceb37739683749491c2bab15a46094b255289ed25cf8f01cdfb1fe8bd9cd5a19eb09448a3e53186"),
+            previous_signature: Vec::new(),
+        };
+
+        assert!(verify_beacon(&SchemeID::UnchainedOnG1, &public_key, &beacon).is_ok());
+    }
+
+    #[test]
+    fn g1g2_swap_non_rfc_beacon_uses_the_g2_dst() {
+        let public_key = dehexify("a0b862a7527fee3a731bcb59280ab6abd62d5c0b6ea03dc4ddf6612fdfc9d01f01c31542541771903475eb1ec6615f8d0df0b8b6dce385811d6dcf8cbefb8759e5e616a3dfd054c928940766d9a5b9db91e3b697e5d70a975181e007f87fca5e");
+        let beacon = Beacon {
+            round_number: 3,
+            randomness: dehexify("a4eb0ed6c4132da066843c3bfdce732ce5013eda86e74c136ab8ccc387b798dd"),
+            signature: dehexify("8176555f90d71aa49ceb37739683749491c2bab15a46094b255289ed25cf8f01cdfb1fe8bd9cd5a19eb09448a3e53186"),
+            previous_signature: Vec::new(),
+        };
+        let message = unchained_beacon_message(&beacon).unwrap();
+
+        assert_eq!(SchemeID::UnchainedOnG1.dst(), DST_G2);
+        assert_eq!(
+            verify_on_g1(&public_key, &message, &beacon.signature, DST_G2),
+            Ok(())
+        );
+        assert_error(
+            verify_on_g1(&public_key, &message, &beacon.signature, DST_G1),
+            VerificationError::SignatureFailedVerification,
+        );
+        assert_error(
+            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, &beacon),
+            VerificationError::SignatureFailedVerification,
+        );
+        assert_error(
+            verify_beacon_with_dst(&SchemeID::UnchainedOnG1, &public_key, &beacon, DST_G1),
+            VerificationError::SignatureFailedVerification,
+        );
+    }
+
+    #[test]
+    fn g1g2_swap_non_rfc_pinned_vector_verifies() {
+        // signed with the secret key 0x5eed, pinned so the signature can't silently move to
+        // another DST along with the signer
+        let public_key = dehexify("915375db81493926c1a14d5564d200ec53890beb1ec2b74b6dc48a3b830a7a8e460d340ce14d8d4e02631ef13a3c957d0d4e6c386e64469a8cf882f583c08e4145d832d1d439a02fcd5e3cfdd440f345c6641204a3f6464e1c394b2f9479723d");
+        let beacon = Beacon {
+            round_number: 42,
+            randomness: dehexify("675ca616c846da6da488ff364410b0fe1c9ae784c7f050cf49e2924aad0b1362"),
+            signature: dehexify("8949d5743f1dcffe6656386a43b11c4ebf92f60df861febb033e2eb8e9a81fd08f1d2fb511f4a6040e1439f4a1a3725b"),
+            previous_signature: Vec::new(),
+        };
+
+        assert_eq!(
+            verify_beacon(&SchemeID::UnchainedOnG1, &public_key, &beacon),
+            Ok(())
+        );
+        assert_error(
+            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, &beacon),
+            VerificationError::SignatureFailedVerification,
+        );
+    }
+
+    #[test]
+    fn g1g2_swap_rfc_beacon_verifies() {
+        let public_key = dehexify("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+        let beacon = Beacon {
+            round_number: 1000,
+            randomness: dehexify("fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"),
+            signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
+            previous_signature: Vec::new(),
+        };
+
+        assert!(verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, &beacon).is_ok());
+    }
+
+    #[test]
+    fn g1g2_swap_empty_public_key_fails() {
+        let public_key = Vec::new();
+        let beacon = Beacon {
+            round_number: 1000,
+            randomness: dehexify("fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"),
+            signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
+            previous_signature: Vec::new(),
+        };
+
+        assert_error(
+            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, &beacon),
+            VerificationError::WrongLength {
+                field: "public_key",
+                scheme: "bls-unchained-g1-rfc9380",
+                expected: 96,
+                actual: 0,
+            },
+        );
+    }
+
+    #[test]
+    fn g1g2_swap_infinity_public_key_fails() {
+        let public_key = G2Affine::identity().to_compressed();
+        let beacon = Beacon {
+            round_number: 1000,
+            randomness: dehexify("fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"),
+            signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
+            previous_signature: Vec::new(),
+        };
+
+        assert_error(
+            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, &beacon),
+            VerificationError::InvalidPublicKey,
+        );
+    }
+
+    #[test]
+    fn g1g2_swap_wrong_round_fails() {
+        let public_key = dehexify("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+        let beacon = Beacon {
+            round_number: 1,
+            randomness: dehexify("fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"),
+            signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
+            previous_signature: Vec::new(),
+        };
+
+        assert_error(
+            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, &beacon),
+            VerificationError::SignatureFailedVerification,
+        );
+    }
+
+    #[test]
+    fn g1g2_swap_invalid_randomness_fails() {
+        let public_key = dehexify("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+        let beacon = Beacon {
+            round_number: 1000,
+            // incorrect hash for the signature
+            randomness: dehexify("aa290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"),
+            signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
+            previous_signature: Vec::new(),
+        };
+
+        assert_error(
+            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, &beacon),
+            VerificationError::InvalidRandomness,
+        );
+    }
+
+    #[test]
+    fn g1g2_swap_invalid_signature_fails() {
+        let public_key = dehexify("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+        let beacon = Beacon {
+            round_number: 1000,
+            // this is not a valid signature
+            signature: dehexify("a44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
+            // but the hash matches it
+            randomness: dehexify("5993706587c56d4e7079d175bfa5d52295694896e68c691b93765242096c9fa7"),
+            previous_signature: Vec::new(),
+        };
+
+        assert_error(
+            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, &beacon),
+            VerificationError::SignatureFailedVerification,
+        );
+    }
+
+    #[test]
+    fn verifying_key_returned_matches_fresh_decode() {
+        let public_key = dehexify("88a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb");
+        let beacon = Beacon {
+            round_number: 397089,
+            randomness: dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
+            signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
+            previous_signature: dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce"),
+        };
+
+        let key = verify_beacon_returning_key(&SchemeID::PedersenBlsChained, &public_key, &beacon)
+            .expect("beacon should verify");
+
+        let fresh = G1Affine::from_compressed(&public_key.clone().try_into().unwrap()).unwrap();
+        assert_eq!(key.point(), &PublicKeyPoint::G1(fresh));
+        assert!(key.verify(&beacon).is_ok());
+    }
+
+    #[test]
+    fn verifying_key_on_g2_verifies() {
+        let public_key = dehexify("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+        let beacon = Beacon {
+            round_number: 1000,
+            randomness: dehexify("fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"),
+            signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
+            previous_signature: Vec::new(),
+        };
+
+        let key = VerifyingKey::new(&SchemeID::UnchainedOnG1RFC9380, &public_key).unwrap();
+        let fresh = G2Affine::from_compressed(&public_key.clone().try_into().unwrap()).unwrap();
+        assert_eq!(key.point(), &PublicKeyPoint::G2(fresh));
+        assert!(key.verify(&beacon).is_ok());
+    }
+
+    #[test]
+    fn mainnet_key_is_rebuilt_from_coordinates() {
+        let public_key = dehexify("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31");
+        let x = dehexify("068f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31");
+        let y = dehexify("026fa5eef143aaa17c53b3c150d96a18051b718531af576803cfb9acf29b8774a8184e63c62da81ddf4d76fb0a65895c");
+
+        assert_eq!(
+            VerifyingKey::from_coordinates(&SchemeID::PedersenBlsChained, &x, &y),
+            VerifyingKey::new(&SchemeID::PedersenBlsChained, &public_key)
+        );
+    }
+
+    #[test]
+    fn quicknet_key_is_rebuilt_from_g2_coordinates() {
+        let public_key = dehexify("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+        let x_c0 = dehexify("0d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+        let x_c1 = dehexify("03cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d106451");
+        let y_c0 = dehexify("0e5db2b6bfbb01c867749cadffca88b36c24f3012ba09fc4d3022c5c37dce0f977d3adb5d183c7477c442b1f04515273");
+        let y_c1 = dehexify("01a714f2edb74119a2f2b0d5a7c75ba902d163700a61bc224ededd8e63aef7be1aaf8e93d7a9718b047ccddb3eb5d68b");
+
+        assert_eq!(
+            VerifyingKey::from_g2_coordinates(
+                &SchemeID::UnchainedOnG1RFC9380,
+                (&x_c0, &x_c1),
+                (&y_c0, &y_c1)
+            ),
+            VerifyingKey::new(&SchemeID::UnchainedOnG1RFC9380, &public_key)
+        );
+        assert_eq!(
+            VerifyingKey::from_g2_coordinates(
+                &SchemeID::UnchainedOnG1RFC9380,
+                (&x_c1, &x_c0),
+                (&y_c1, &y_c0)
+            ),
+            Err(VerificationError::InvalidPublicKey)
+        );
+    }
+
+    #[test]
+    fn invalid_coordinates_are_rejected() {
+        let x = dehexify("068f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31");
+        let y = dehexify("026fa5eef143aaa17c53b3c150d96a18051b718531af576803cfb9acf29b8774a8184e63c62da81ddf4d76fb0a65895c");
+        let mut off_curve = y.clone();
+        off_curve[47] ^= 1;
+        let mut flagged = x.clone();
+        flagged[0] |= 0x40;
+
+        for (x, y) in [
+            (&x, &off_curve),
+            (&flagged, &y),
+            (&[0u8; 49].to_vec(), &y),
+            (&vec![0u8; 48], &vec![0u8; 48]),
+        ] {
+            assert_eq!(
+                VerifyingKey::from_coordinates(&SchemeID::PedersenBlsChained, x, y),
+                Err(VerificationError::InvalidPublicKey)
+            );
+        }
+        assert_eq!(
+            VerifyingKey::from_coordinates(&SchemeID::UnchainedOnG1RFC9380, &x, &y),
+            Err(VerificationError::InvalidPublicKey)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn verifying_key_from_deserialized_chain_info_verifies() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let json = serde_json::to_string(&signer.chain_info(1692803367, 3)).unwrap();
+        let info: ChainInfo = serde_json::from_str(&json).unwrap();
+
+        let key = VerifyingKey::from_chain_info(&info).unwrap();
+        assert_eq!(key.scheme_id(), &SchemeID::UnchainedOnG1RFC9380);
+        for round in [1, 2, 1000, 1001] {
+            assert_eq!(key.verify(&signer.beacon(round)), Ok(()));
+        }
+
+        let mismatched = ChainInfo {
+            scheme_id: SchemeID::PedersenBlsUnchained,
+            ..info
+        };
+        assert!(VerifyingKey::from_chain_info(&mismatched).is_err());
+    }
+
+    #[test]
+    fn beacon_constructor_accepts_valid_lengths() {
+        let chained = TestSigner::new(SchemeID::PedersenBlsChained).beacon(3);
+        let unchained = TestSigner::new(SchemeID::UnchainedOnG1RFC9380).beacon(3);
+        // the first round of a chained chain links to the 32 byte genesis seed
+        let first = TestSigner::new(SchemeID::PedersenBlsChained).chained_beacon(1, &[0x17; 32]);
+
+        for beacon in [chained, unchained, first] {
+            assert_eq!(
+                Beacon::new(
+                    beacon.round_number,
+                    beacon.randomness.clone(),
+                    beacon.signature.clone(),
+                    beacon.previous_signature.clone()
+                ),
+                Ok(beacon)
+            );
+        }
+    }
+
+    #[test]
+    fn beacon_constructor_rejects_invalid_lengths() {
+        let randomness = Beacon::new(1, vec![0; 31], vec![0; 48], Vec::new());
+        assert_eq!(
+            randomness,
+            Err(VerificationError::InvalidBeaconField {
+                field: "randomness",
+                expected: &[32],
+                actual: 31,
+            })
+        );
+        assert_eq!(
+            randomness.unwrap_err().to_string(),
+            "`randomness` should be 32 bytes but was 31 bytes"
+        );
+        for signature_length in [0, 47, 64, 97] {
+            assert_eq!(
+                Beacon::new(1, vec![0; 32], vec![0; signature_length], Vec::new()),
+                Err(VerificationError::InvalidBeaconField {
+                    field: "signature",
+                    expected: &[48, 96],
+                    actual: signature_length,
+                })
+            );
+        }
+        let previous_signature = Beacon::new(2, vec![0; 32], vec![0; 96], vec![0; 48]);
+        assert_eq!(
+            previous_signature,
+            Err(VerificationError::InvalidBeaconField {
+                field: "previous_signature",
+                expected: &[0, 96],
+                actual: 48,
+            })
+        );
+        assert_eq!(
+            previous_signature.unwrap_err().to_string(),
+            "`previous_signature` should be 0 or 96 bytes but was 48 bytes"
+        );
+        // only the first round links to the genesis seed
+        assert_eq!(
+            Beacon::new(2, vec![0; 32], vec![0; 96], vec![0; 32]),
+            Err(VerificationError::InvalidBeaconField {
+                field: "previous_signature",
+                expected: &[0, 96],
+                actual: 32,
+            })
+        );
+    }
+
+    #[test]
+    fn beacon_field_errors_tell_short_from_long() {
+        let error = |actual| VerificationError::InvalidBeaconField {
+            field: "signature",
+            expected: &[48, 96],
+            actual,
+        };
+        assert_eq!(error(47).length_mismatch(), Some(LengthMismatch::TooShort));
+        assert_eq!(error(64).length_mismatch(), None);
+        assert_eq!(error(97).length_mismatch(), Some(LengthMismatch::TooLong));
+    }
+
+    #[test]
+    fn verifying_key_rejects_infinity() {
+        let public_key = G1Affine::identity().to_compressed();
+        assert_eq!(
+            VerifyingKey::new(&SchemeID::PedersenBlsChained, &public_key),
+            Err(VerificationError::InvalidPublicKey)
+        );
+    }
+
+    #[test]
+    fn constant_time_verification_matches_normal_path() {
+        let chained_key = dehexify("88a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb");
+        let chained = Beacon {
+            round_number: 397089,
+            randomness: dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
+            signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
+            previous_signature: dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce"),
+        };
+        let unchained_key = dehexify("8d91ae0f4e3cd277cfc46aba26680232b0d5bb4444602cdb23442d62e17f43cdffb1104909e535430c10a6a1ce680a65");
+        let unchained = Beacon {
+            round_number: 397092,
+            randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
+            signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
+            previous_signature: Vec::new(),
+        };
+        let g1_key = dehexify("a0b862a7527fee3a731bcb59280ab6abd62d5c0b6ea03dc4ddf6612fdfc9d01f01c31542541771903475eb1ec6615f8d0df0b8b6dce385811d6dcf8cbefb8759e5e616a3dfd054c928940766d9a5b9db91e3b697e5d70a975181e007f87fca5e");
+        let g1 = Beacon {
+            round_number: 3,
+            randomness: dehexify("a4eb0ed6c4132da066843c3bfdce732ce5013eda86e74c136ab8ccc387b798dd"),
+            signature: dehexify("8176555f90d71aa49ceb37739683749491c2bab15a46094b255289ed25cf8f01cdfb1fe8bd9cd5a19eb09448a3e53186"),
+            previous_signature: Vec::new(),
+        };
+        let rfc_key = dehexify("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+        let rfc = Beacon {
+            round_number: 1000,
+            randomness: dehexify("fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"),
+            signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
+            previous_signature: Vec::new(),
+        };
+
+        let mut cases = vec![
+            (
+                SchemeID::PedersenBlsChained,
+                chained_key.clone(),
+                chained.clone(),
+            ),
+            (
+                SchemeID::PedersenBlsUnchained,
+                unchained_key.clone(),
+                unchained.clone(),
+            ),
+            (SchemeID::UnchainedOnG1, g1_key.clone(), g1.clone()),
+            (SchemeID::UnchainedOnG1RFC9380, rfc_key.clone(), rfc.clone()),
+            // wrong scheme for the key
+            (
+                SchemeID::PedersenBlsUnchained,
+                chained_key.clone(),
+                chained.clone(),
+            ),
+            (SchemeID::UnchainedOnG1, rfc_key.clone(), rfc.clone()),
+            // invalid and empty keys
+            (
+                SchemeID::PedersenBlsChained,
+                G1Affine::identity().to_compressed().to_vec(),
+                chained.clone(),
+            ),
+            (SchemeID::UnchainedOnG1RFC9380, Vec::new(), rfc.clone()),
+        ];
+        let mut wrong_round = unchained.clone();
+        wrong_round.round_number = 1;
+        cases.push((SchemeID::PedersenBlsUnchained, unchained_key, wrong_round));
+        let mut wrong_randomness = g1.clone();
+        wrong_randomness.randomness[0] ^= 1;
+        cases.push((SchemeID::UnchainedOnG1, g1_key, wrong_randomness));
+        let mut missing_previous = chained.clone();
+        missing_previous.previous_signature = Vec::new();
+        cases.push((SchemeID::PedersenBlsChained, chained_key, missing_previous));
+        // correctly signed, but for round 0
+        let round_zero_signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        cases.push((
+            SchemeID::UnchainedOnG1RFC9380,
+            round_zero_signer.public_key(),
+            round_zero_signer.beacon(0),
+        ));
+
+        for (scheme, key, beacon) in cases {
+            assert_eq!(
+                verify_beacon_ct(&scheme, &key, &beacon).is_ok(),
+                verify_beacon(&scheme, &key, &beacon).is_ok(),
+                "mismatch for {scheme:?} round {}",
+                beacon.round_number
+            );
+        }
+    }
+
+    #[test]
+    fn default_expander_matches_verify_beacon() {
+        let public_key = dehexify("88a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb");
+        let beacon = Beacon {
+            round_number: 397089,
+            randomness: dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
+            signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
+            previous_signature: dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce"),
+        };
+
+        assert!(verify_beacon_with_expander::<DefaultExpander>(
+            &SchemeID::PedersenBlsChained,
+            &public_key,
+            &beacon
+        )
+        .is_ok());
+        assert_error(
+            verify_beacon_with_expander::<ExpandMsgXof<Shake256>>(
+                &SchemeID::PedersenBlsChained,
+                &public_key,
+                &beacon,
+            ),
+            VerificationError::SignatureFailedVerification,
+        );
     }
 
-    let m = <G1Projective as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve(
-        message,
-        domain_separation_tag.as_bytes(),
-    );
+    #[test]
+    fn xof_expander_signature_verifies() {
+        let secret_key = Scalar::from(1234u64);
+        let public_key = G1Affine::from(G1Affine::generator() * secret_key).to_compressed();
+        let message = b"hello drand";
+        let m = <G2Projective as HashToCurve<ExpandMsgXof<Shake256>>>::hash_to_curve(
+            message,
+            DST_G2.as_bytes(),
+        );
+        let signature = G2Affine::from(m * secret_key).to_compressed();
+
+        assert!(verify_on_g2_with_expander::<ExpandMsgXof<Shake256>>(
+            &public_key,
+            message,
+            &signature,
+            DST_G2
+        )
+        .is_ok());
+        assert_error(
+            verify_on_g2(&public_key, message, &signature, DST_G2),
+            VerificationError::SignatureFailedVerification,
+        );
+    }
 
-    let pubkey_prepared = G2Prepared::from(pubkey_point.neg());
-    let g2_base = G2Prepared::from(G2Affine::generator());
-    let exp = multi_miller_loop(&[
-        (&G1Affine::from(m), &pubkey_prepared),
-        (&signature_point, &g2_base),
-    ]);
+    #[test]
+    fn expected_randomness_matches_mainnet_beacon() {
+        let signature = dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a");
 
-    if exp.final_exponentiation() != Gt::identity() {
-        Err(VerificationError::SignatureFailedVerification)
-    } else {
-        Ok(())
+        assert_eq!(
+            expected_randomness(&signature),
+            dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42")
+        );
     }
-}
 
-#[cfg(test)]
-mod test {
-    use crate::verify::{verify_beacon, Beacon, SchemeID, VerificationError};
-    use bls12_381::{G1Affine, G2Affine};
+    #[test]
+    fn g2_public_key_for_g1_key_scheme_fails_with_wrong_length() {
+        let public_key = dehexify("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+        let beacon = Beacon {
+            round_number: 397092,
+            randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
+            signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
+            previous_signature: Vec::new(),
+        };
+
+        assert_error(
+            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
+            VerificationError::WrongLength {
+                field: "public_key",
+                scheme: "pedersen-bls-unchained",
+                expected: 48,
+                actual: 96,
+            },
+        );
+    }
 
     #[test]
-    fn default_beacon_verifies() {
+    fn g1_public_key_for_g2_key_scheme_fails_with_wrong_length() {
         let public_key = dehexify("88a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb");
-        let prev_sig = dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce");
+        let beacon = Beacon {
+            round_number: 1000,
+            randomness: dehexify("fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"),
+            signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
+            previous_signature: Vec::new(),
+        };
+
+        for scheme_id in [SchemeID::UnchainedOnG1, SchemeID::UnchainedOnG1RFC9380] {
+            assert_error(
+                verify_beacon(&scheme_id, &public_key, &beacon),
+                VerificationError::WrongLength {
+                    field: "public_key",
+                    scheme: scheme_id.name(),
+                    expected: 96,
+                    actual: 48,
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn wrong_length_public_key_is_rejected_for_every_scheme() {
+        let public_key = [1u8; 47];
+        let beacon = Beacon {
+            round_number: 1,
+            randomness: expected_randomness(&[2u8; 48]),
+            signature: vec![2u8; 48],
+            previous_signature: vec![3u8; 96],
+        };
+
+        for scheme_id in [
+            SchemeID::PedersenBlsChained,
+            SchemeID::PedersenBlsUnchained,
+            SchemeID::UnchainedOnG1,
+            SchemeID::UnchainedOnG1RFC9380,
+        ] {
+            let expected = || VerificationError::WrongLength {
+                field: "public_key",
+                scheme: scheme_id.name(),
+                expected: scheme_id.public_key_length(),
+                actual: 47,
+            };
+            assert_error(verify_beacon(&scheme_id, &public_key, &beacon), expected());
+            assert_eq!(
+                VerifyingKey::new(&scheme_id, &public_key).err(),
+                Some(expected())
+            );
+        }
+    }
+
+    #[test]
+    fn wrong_length_error_names_the_scheme() {
+        let err = VerificationError::WrongLength {
+            field: "public_key",
+            scheme: SchemeID::UnchainedOnG1RFC9380.name(),
+            expected: 96,
+            actual: 48,
+        };
+        assert_eq!(
+            err.to_string(),
+            "`public_key` should be 96 bytes for bls-unchained-g1-rfc9380 but was 48 bytes, 48 too short"
+        );
+    }
+
+    #[test]
+    fn truncated_and_overlong_signatures_are_told_apart() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let beacon = signer.beacon(7);
+
+        for (length, mismatch) in [
+            (47, LengthMismatch::TooShort),
+            (49, LengthMismatch::TooLong),
+        ] {
+            let mut signature = beacon.signature.clone();
+            signature.resize(length, 0);
+            let resized = Beacon {
+                randomness: expected_randomness(&signature),
+                signature,
+                ..beacon.clone()
+            };
+
+            let err = verify_beacon(
+                &SchemeID::UnchainedOnG1RFC9380,
+                &signer.public_key(),
+                &resized,
+            )
+            .unwrap_err();
+            assert_eq!(
+                err,
+                VerificationError::WrongLength {
+                    field: "signature",
+                    scheme: "bls-unchained-g1-rfc9380",
+                    expected: 48,
+                    actual: length,
+                }
+            );
+            assert_eq!(err.length_mismatch(), Some(mismatch));
+            assert_eq!(
+                validate_signature(&SchemeID::UnchainedOnG1RFC9380, &resized.signature)
+                    .unwrap_err()
+                    .length_mismatch(),
+                Some(mismatch)
+            );
+        }
+        assert!(VerificationError::InvalidSignaturePoint
+            .length_mismatch()
+            .is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn beacon_with_0x_prefixed_hex_deserializes() {
+        let unprefixed = r#"{
+            "round": 397089,
+            "randomness": "cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42",
+            "signature": "88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a",
+            "previous_signature": "a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce"
+        }"#;
+        let prefixed = r#"{
+            "round": 397089,
+            "randomness": "0xcd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42",
+            "signature": "0X88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a",
+            "previous_signature": "0xa2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce"
+        }"#;
+
+        let unprefixed: Beacon = serde_json::from_str(unprefixed).unwrap();
+        let prefixed: Beacon = serde_json::from_str(prefixed).unwrap();
+
+        assert_eq!(prefixed, unprefixed);
+        assert_eq!(
+            prefixed.randomness,
+            dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42")
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn beacon_with_invalid_hex_fails_to_deserialize() {
+        let json = r#"{"round": 1, "randomness": "0xzz", "signature": "00"}"#;
+        assert!(serde_json::from_str::<Beacon>(json).is_err());
+    }
+
+    #[test]
+    fn double_sha256_randomness_verifies_only_with_its_format() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let mut beacon = signer.beacon(42);
+        beacon.randomness = expected_randomness(&expected_randomness(&beacon.signature));
+
+        assert!(verify_beacon_with_randomness_format(
+            &SchemeID::PedersenBlsUnchained,
+            &signer.public_key(),
+            &beacon,
+            RandomnessFormat::DoubleSha256,
+        )
+        .is_ok());
+        assert_error(
+            verify_beacon(
+                &SchemeID::PedersenBlsUnchained,
+                &signer.public_key(),
+                &beacon,
+            ),
+            VerificationError::InvalidRandomness,
+        );
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn keccak_randomness_verifies_only_with_its_format() {
+        assert_eq!(
+            hex::encode(RandomnessFormat::Keccak256.randomness(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let mut beacon = signer.beacon(42);
+        beacon.randomness = RandomnessFormat::Keccak256.randomness(&beacon.signature);
+
+        assert_eq!(
+            verify_beacon_with_randomness_format(
+                &SchemeID::UnchainedOnG1RFC9380,
+                &signer.public_key(),
+                &beacon,
+                RandomnessFormat::Keccak256,
+            ),
+            Ok(())
+        );
+        assert_error(
+            verify_beacon_with_randomness_format(
+                &SchemeID::UnchainedOnG1RFC9380,
+                &signer.public_key(),
+                &beacon,
+                RandomnessFormat::Sha256,
+            ),
+            VerificationError::InvalidRandomness,
+        );
+    }
+
+    #[test]
+    fn schemes_for_key_length_matches_key_group() {
+        assert_eq!(
+            SchemeID::schemes_for_key_length(48),
+            vec![SchemeID::PedersenBlsChained, SchemeID::PedersenBlsUnchained]
+        );
+        assert_eq!(
+            SchemeID::schemes_for_key_length(96),
+            vec![SchemeID::UnchainedOnG1, SchemeID::UnchainedOnG1RFC9380]
+        );
+        assert!(SchemeID::schemes_for_key_length(32).is_empty());
+    }
+
+    #[test]
+    fn round_zero_beacon_fails() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let mut beacon = signer.beacon(1);
+        beacon.round_number = 0;
+
+        assert_error(
+            verify_beacon(
+                &SchemeID::UnchainedOnG1RFC9380,
+                &signer.public_key(),
+                &beacon,
+            ),
+            VerificationError::InvalidRound,
+        );
+        let key = VerifyingKey::new(&SchemeID::UnchainedOnG1RFC9380, &signer.public_key()).unwrap();
+        assert_error(key.verify(&beacon), VerificationError::InvalidRound);
+
+        // unless the chain starts at round 0
+        let mut chain_info = signer.chain_info(1692803367, 3);
+        chain_info.genesis_round = 0;
+        let key = VerifyingKey::from_chain_info(&chain_info).unwrap();
+        assert_eq!(key.verify(&signer.beacon(0)), Ok(()));
+        assert_eq!(
+            key.with_genesis_round(2).verify(&signer.beacon(1)),
+            Err(VerificationError::InvalidRound)
+        );
+    }
 
+    #[test]
+    fn pedersen_bls_chained_vector_fails_on_g1() {
         let beacon = Beacon {
             round_number: 397089,
-            randomness: dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
+            randomness: Vec::new(),
             signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
-            previous_signature: prev_sig,
+            previous_signature: dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce"),
         };
+        assert_only_verifies_on_g2(
+            &dehexify("88a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb"),
+            &chained_beacon_message(&beacon).unwrap(),
+            &beacon.signature,
+        );
+    }
 
-        assert!(matches!(
-            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
-            Ok(()),
-        ));
+    #[test]
+    fn pedersen_bls_unchained_vector_fails_on_g1() {
+        let beacon = Beacon {
+            round_number: 397092,
+            randomness: Vec::new(),
+            signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
+            previous_signature: Vec::new(),
+        };
+        assert_only_verifies_on_g2(
+            &dehexify("8d91ae0f4e3cd277cfc46aba26680232b0d5bb4444602cdb23442d62e17f43cdffb1104909e535430c10a6a1ce680a65"),
+            &unchained_beacon_message(&beacon).unwrap(),
+            &beacon.signature,
+        );
     }
 
     #[test]
-    fn default_wrong_round_fails() {
-        let public_key = dehexify("88a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb");
-        let prev_sig = dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce");
+    fn unchained_on_g1_vector_fails_on_g2() {
+        let beacon = Beacon {
+            round_number: 3,
+            randomness: Vec::new(),
+            signature: dehexify("8176555f90d71aa49ceb37739683749491c2bab15a46094b255289ed25cf8f01cdfb1fe8bd9cd5a19eb09448a3e53186"),
+            previous_signature: Vec::new(),
+        };
+        assert_only_verifies_on_g1(
+            &dehexify("a0b862a7527fee3a731bcb59280ab6abd62d5c0b6ea03dc4ddf6612fdfc9d01f01c31542541771903475eb1ec6615f8d0df0b8b6dce385811d6dcf8cbefb8759e5e616a3dfd054c928940766d9a5b9db91e3b697e5d70a975181e007f87fca5e"),
+            &unchained_beacon_message(&beacon).unwrap(),
+            &beacon.signature,
+            DST_G2,
+        );
+    }
 
+    #[test]
+    fn unchained_on_g1_rfc9380_vector_fails_on_g2() {
         let beacon = Beacon {
-            round_number: 1, // wrong round for randomness
-            randomness: dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
-            signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
-            previous_signature: prev_sig,
+            round_number: 1000,
+            randomness: Vec::new(),
+            signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
+            previous_signature: Vec::new(),
         };
+        assert_only_verifies_on_g1(
+            &dehexify("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a"),
+            &unchained_beacon_message(&beacon).unwrap(),
+            &beacon.signature,
+            DST_G1,
+        );
+    }
+
+    fn assert_only_verifies_on_g2(public_key: &[u8], message: &[u8], signature: &[u8]) {
+        assert!(verify_on_g2(public_key, message, signature, DST_G2).is_ok());
+        for dst in [DST_G1, DST_G2] {
+            assert_error(
+                verify_on_g1(public_key, message, signature, dst),
+                VerificationError::InvalidPublicKey,
+            );
+        }
+    }
+
+    fn assert_only_verifies_on_g1(public_key: &[u8], message: &[u8], signature: &[u8], dst: &str) {
+        assert!(verify_on_g1(public_key, message, signature, dst).is_ok());
+        for dst in [DST_G1, DST_G2] {
+            assert_error(
+                verify_on_g2(public_key, message, signature, dst),
+                VerificationError::InvalidPublicKey,
+            );
+        }
+    }
+
+    #[test]
+    fn compressed_g2_message_point_verifies() {
+        let public_key = dehexify("8d91ae0f4e3cd277cfc46aba26680232b0d5bb4444602cdb23442d62e17f43cdffb1104909e535430c10a6a1ce680a65");
+        let signature = dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539");
+        let message = Sha256::digest(&397092u64.to_be_bytes());
+        let message_point = hash_to_g2(&message, DST_G2).to_compressed();
+
+        assert!(verify_on_g2_with_message_point(&public_key, &message_point, &signature).is_ok());
 
+        let other_round = hash_to_g2(&Sha256::digest(&397093u64.to_be_bytes()), DST_G2);
         assert_error(
-            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
+            verify_on_g2_with_message_point(&public_key, &other_round.to_compressed(), &signature),
             VerificationError::SignatureFailedVerification,
         );
     }
 
     #[test]
-    fn default_with_invalid_randomness_fails() {
-        let public_key = dehexify("88a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb");
-        let prev_sig = dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce");
+    fn compressed_g1_message_point_verifies() {
+        let public_key = dehexify("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+        let signature = dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39");
+        let message = Sha256::digest(&1000u64.to_be_bytes());
+        let message_point = hash_to_g1(&message, DST_G1).to_compressed();
+
+        assert!(verify_on_g1_with_message_point(&public_key, &message_point, &signature).is_ok());
+    }
+
+    #[test]
+    fn invalid_compressed_message_point_fails() {
+        let public_key = dehexify("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+        let signature = dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39");
+
+        for message_point in [
+            vec![0x11; 48],
+            G1Affine::identity().to_compressed().to_vec(),
+            vec![0xc0; 47],
+        ] {
+            assert_error(
+                verify_on_g1_with_message_point(&public_key, &message_point, &signature),
+                VerificationError::InvalidMessagePoint,
+            );
+        }
+    }
+
+    #[test]
+    fn detailed_verification_reports_checks_independently() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsChained);
+        let public_key = signer.public_key();
+        let beacon = signer.beacon(4);
+
+        let mut bad_randomness = beacon.clone();
+        bad_randomness.randomness = vec![0; 32];
+        let mut bad_signature = beacon.clone();
+        bad_signature.signature = signer.beacon(5).signature;
+        bad_signature.randomness = signer.beacon(5).randomness;
+
+        let scheme = SchemeID::PedersenBlsChained;
+        assert!(verify_detailed(&scheme, &public_key, &beacon).is_ok());
+        assert_eq!(
+            verify_detailed(&scheme, &public_key, &bad_randomness),
+            VerifyReport {
+                randomness_ok: false,
+                signature_ok: true
+            }
+        );
+        assert_eq!(
+            verify_detailed(&scheme, &public_key, &bad_signature),
+            VerifyReport {
+                randomness_ok: true,
+                signature_ok: false
+            }
+        );
+        assert_eq!(
+            verify_detailed(&scheme, &[], &bad_randomness),
+            VerifyReport {
+                randomness_ok: false,
+                signature_ok: false
+            }
+        );
+    }
+
+    #[test]
+    fn unchained_round_one_verifies() {
+        for scheme_id in [
+            SchemeID::PedersenBlsUnchained,
+            SchemeID::UnchainedOnG1,
+            SchemeID::UnchainedOnG1RFC9380,
+        ] {
+            let signer = TestSigner::new(scheme_id.clone());
+            let beacon = signer.beacon(1);
+
+            assert!(verify_beacon(&scheme_id, &signer.public_key(), &beacon).is_ok());
+        }
+    }
 
+    #[test]
+    fn unchained_beacon_with_previous_signature_fails() {
+        let public_key = dehexify("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
         let beacon = Beacon {
-            round_number: 397089,
-            // updated the randomness hex to be wrong
-            randomness: dehexify("bd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
-            signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
-            previous_signature: prev_sig,
+            round_number: 1000,
+            randomness: dehexify("fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"),
+            signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
+            previous_signature: vec![0xde, 0xad, 0xbe, 0xef],
         };
+        let scheme_id = SchemeID::UnchainedOnG1RFC9380;
 
         assert_error(
-            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
-            VerificationError::InvalidRandomness,
+            verify_beacon(&scheme_id, &public_key, &beacon),
+            VerificationError::UnexpectedPreviousSignature,
+        );
+        assert_error(
+            VerifyingKey::new(&scheme_id, &public_key)
+                .unwrap()
+                .verify(&beacon),
+            VerificationError::UnexpectedPreviousSignature,
         );
+        assert!(verify_beacon_ct(&scheme_id, &public_key, &beacon).is_err());
     }
 
     #[test]
-    fn default_beacon_missing_previous_sig_fails() {
-        let public_key = dehexify("88a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb");
+    fn error_codes_are_unique_and_round_trip() {
+        let errors = [
+            VerificationError::ChainedBeaconNeedsPreviousSignature,
+            VerificationError::InvalidSignatureLength,
+            VerificationError::InvalidPublicKey,
+            VerificationError::EmptyMessage,
+            VerificationError::SignatureFailedVerification,
+            VerificationError::InvalidRandomness,
+            VerificationError::UnexpectedPreviousSignature,
+            VerificationError::InvalidRound,
+            VerificationError::InvalidMessagePoint,
+            VerificationError::InvalidSignaturePoint,
+            VerificationError::WrongLength {
+                field: "signature",
+                scheme: "bls-unchained-g1-rfc9380",
+                expected: 48,
+                actual: 96,
+            },
+            VerificationError::BrokenChainLink { round: 5 },
+            VerificationError::TrustedRoundNotReached { trusted_round: 5 },
+            VerificationError::InvalidReshareProof,
+            VerificationError::InvalidBeaconField {
+                field: "signature",
+                expected: &[48, 96],
+                actual: 64,
+            },
+            VerificationError::InvalidRange { start: 7, end: 3 },
+            VerificationError::UnsupportedScheme {
+                scheme: "pedersen-bls-chained",
+            },
+        ];
+
+        let codes: HashSet<u32> = errors.iter().map(VerificationError::code).collect();
+        assert_eq!(codes.len(), errors.len());
+        assert!(!codes.contains(&0));
+
+        for error in errors {
+            let round_tripped = VerificationError::from_code(error.code()).unwrap();
+            assert_eq!(round_tripped.code(), error.code());
+            if !matches!(
+                error,
+                VerificationError::WrongLength { .. }
+                    | VerificationError::BrokenChainLink { .. }
+                    | VerificationError::TrustedRoundNotReached { .. }
+                    | VerificationError::InvalidBeaconField { .. }
+                    | VerificationError::InvalidRange { .. }
+                    | VerificationError::UnsupportedScheme { .. }
+            ) {
+                assert_eq!(round_tripped, error);
+            }
+        }
+        assert_eq!(VerificationError::from_code(0), None);
+        assert_eq!(VerificationError::from_code(18), None);
+    }
 
+    #[test]
+    fn verify_with_explicit_dst() {
+        let public_key = dehexify("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
         let beacon = Beacon {
-            round_number: 397089,
-            randomness: dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
-            signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
+            round_number: 1000,
+            randomness: dehexify("fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"),
+            signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
             previous_signature: Vec::new(),
         };
+        let scheme_id = SchemeID::UnchainedOnG1RFC9380;
+
+        assert_eq!(
+            verify_beacon_with_dst(&scheme_id, &public_key, &beacon, DST_G1),
+            Ok(())
+        );
+        assert_error(
+            verify_beacon_with_dst(&scheme_id, &public_key, &beacon, DST_G2),
+            VerificationError::SignatureFailedVerification,
+        );
+        assert_error(
+            verify_beacon_with_dst(&scheme_id, &public_key, &beacon, "NOT_A_DRAND_DST_"),
+            VerificationError::SignatureFailedVerification,
+        );
+    }
+
+    #[test]
+    fn arbitrary_messages_verify() {
+        for scheme_id in [
+            SchemeID::PedersenBlsChained,
+            SchemeID::PedersenBlsUnchained,
+            SchemeID::UnchainedOnG1,
+            SchemeID::UnchainedOnG1RFC9380,
+        ] {
+            let signer = TestSigner::new(scheme_id.clone());
+            let message = b"a message that isn't a round";
+            let signature = signer.sign(message);
+
+            assert_eq!(
+                verify_message(&scheme_id, &signer.public_key(), message, &signature),
+                Ok(())
+            );
+            assert_error(
+                verify_message(
+                    &scheme_id,
+                    &signer.public_key(),
+                    b"another message",
+                    &signature,
+                ),
+                VerificationError::SignatureFailedVerification,
+            );
+        }
+    }
+
+    #[test]
+    fn range_aggregate_verifies() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let aggregate = (3..=7).fold(G2Projective::identity(), |sum, round| {
+            let signature = signer.beacon(round).signature;
+            sum + G2Affine::from_compressed(&signature.try_into().unwrap()).unwrap()
+        });
+        let aggregate = G2Affine::from(aggregate).to_compressed();
+        let scheme_id = SchemeID::PedersenBlsUnchained;
+        let public_key = signer.public_key();
+
+        assert_eq!(
+            verify_range_aggregate(&scheme_id, &public_key, 3, 7, &aggregate),
+            Ok(())
+        );
+        assert_error(
+            verify_range_aggregate(&scheme_id, &public_key, 3, 8, &aggregate),
+            VerificationError::SignatureFailedVerification,
+        );
+        assert_error(
+            verify_range_aggregate(&scheme_id, &public_key, 7, 3, &aggregate),
+            VerificationError::InvalidRange { start: 7, end: 3 },
+        );
+        assert_error(
+            verify_range_aggregate(&scheme_id, &public_key, 0, 7, &aggregate),
+            VerificationError::InvalidRound,
+        );
+    }
 
+    #[test]
+    fn range_aggregate_rejects_ranges_over_the_limit() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let scheme_id = SchemeID::PedersenBlsUnchained;
+        let public_key = signer.public_key();
+        let signature = signer.beacon(1).signature;
+
+        for end in [MAX_AGGREGATE_RANGE_LEN + 1, u64::MAX] {
+            assert_error(
+                verify_range_aggregate(&scheme_id, &public_key, 1, end, &signature),
+                VerificationError::InvalidRange { start: 1, end },
+            );
+        }
+        // the longest range allowed is hashed and checked
         assert_error(
-            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
-            VerificationError::ChainedBeaconNeedsPreviousSignature,
+            verify_range_aggregate(
+                &scheme_id,
+                &public_key,
+                1,
+                MAX_AGGREGATE_RANGE_LEN,
+                &signature,
+            ),
+            VerificationError::SignatureFailedVerification,
         );
     }
 
     #[test]
-    fn default_beacon_invalid_public_key_fails() {
-        // public key is not correct
-        let public_key = dehexify("78a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb");
-        let prev_sig = dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce");
-
-        let beacon = Beacon {
-            round_number: 397089,
-            randomness: dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
-            signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
-            previous_signature: prev_sig,
-        };
-
+    fn range_aggregate_verifies_on_g1() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let aggregate = (1..=4).fold(G1Projective::identity(), |sum, round| {
+            let signature = signer.beacon(round).signature;
+            sum + G1Affine::from_compressed(&signature.try_into().unwrap()).unwrap()
+        });
+        let aggregate = G1Affine::from(aggregate).to_compressed();
+        let scheme_id = SchemeID::UnchainedOnG1RFC9380;
+        let public_key = signer.public_key();
+
+        assert_eq!(
+            verify_range_aggregate(&scheme_id, &public_key, 1, 4, &aggregate),
+            Ok(())
+        );
         assert_error(
-            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
-            VerificationError::InvalidPublicKey,
+            verify_range_aggregate(&scheme_id, &public_key, 2, 4, &aggregate),
+            VerificationError::SignatureFailedVerification,
+        );
+        assert_error(
+            verify_range_aggregate(&SchemeID::UnchainedOnG1, &public_key, 1, 4, &aggregate),
+            VerificationError::SignatureFailedVerification,
         );
     }
 
     #[test]
-    fn default_beacon_empty_public_key_fails() {
-        let public_key = Vec::new();
-        let prev_sig = dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce");
-
-        let beacon = Beacon {
-            round_number: 397089,
-            randomness: dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
-            signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
-            previous_signature: prev_sig,
-        };
+    fn range_aggregate_of_chained_scheme_fails() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsChained);
 
         assert_error(
-            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
-            VerificationError::InvalidPublicKey,
+            verify_range_aggregate(
+                &SchemeID::PedersenBlsChained,
+                &signer.public_key(),
+                1,
+                2,
+                &signer.beacon(1).signature,
+            ),
+            VerificationError::UnsupportedScheme {
+                scheme: "pedersen-bls-chained",
+            },
         );
     }
 
     #[test]
-    fn default_beacon_infinity_public_key_fails() {
-        let public_key = G1Affine::identity().to_compressed();
-        let prev_sig = dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce");
+    fn beacons_are_identified_by_the_one_scheme_they_verify_under() {
+        for scheme_id in [
+            SchemeID::PedersenBlsChained,
+            SchemeID::PedersenBlsUnchained,
+            SchemeID::UnchainedOnG1,
+            SchemeID::UnchainedOnG1RFC9380,
+        ] {
+            let signer = TestSigner::new(scheme_id.clone());
+            let beacon = match scheme_id {
+                SchemeID::PedersenBlsChained => signer.chained_beacon(5, &[0x6e; 96]),
+                _ => signer.beacon(5),
+            };
+            let public_key = signer.public_key();
+            let candidates = SchemeID::schemes_for_key_length(public_key.len());
+
+            let verifying: Vec<&SchemeID> = candidates
+                .iter()
+                .filter(|candidate| verify_beacon(candidate, &public_key, &beacon).is_ok())
+                .collect();
+            assert_eq!(verifying, vec![&scheme_id]);
+            assert_eq!(
+                identify_scheme(&public_key, &beacon, &candidates),
+                Some(scheme_id.clone())
+            );
+            let others: Vec<SchemeID> = candidates
+                .into_iter()
+                .filter(|candidate| *candidate != scheme_id)
+                .collect();
+            assert_eq!(identify_scheme(&public_key, &beacon, &others), None);
+        }
+    }
 
-        let beacon = Beacon {
-            round_number: 397089,
-            randomness: dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
-            signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
-            previous_signature: prev_sig,
-        };
+    #[test]
+    fn public_keys_are_compared_by_point() {
+        let g1_signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let g1_key = g1_signer.public_key();
+        let g1_uncompressed = G1Affine::from_compressed(g1_key.as_slice().try_into().unwrap())
+            .unwrap()
+            .to_uncompressed();
+        let g2_signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let g2_key = g2_signer.public_key();
+        let g2_uncompressed = G2Affine::from_compressed(g2_key.as_slice().try_into().unwrap())
+            .unwrap()
+            .to_uncompressed();
+
+        assert_eq!(
+            public_keys_equal(&g1_key, &g1_uncompressed, &SchemeID::PedersenBlsUnchained),
+            Ok(true)
+        );
+        assert_eq!(
+            public_keys_equal(&g2_uncompressed, &g2_key, &SchemeID::UnchainedOnG1RFC9380),
+            Ok(true)
+        );
 
-        assert_error(
-            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
-            VerificationError::InvalidPublicKey,
+        let other = TestSigner::with_secret(SchemeID::PedersenBlsUnchained, 99).public_key();
+        assert_eq!(
+            public_keys_equal(&g1_key, &other, &SchemeID::PedersenBlsUnchained),
+            Ok(false)
+        );
+        assert_eq!(
+            public_keys_equal(&g1_key, &g2_key, &SchemeID::PedersenBlsUnchained),
+            Err(VerificationError::InvalidPublicKey)
         );
     }
 
     #[test]
-    fn testnet_unchained_beacon_verifies() {
-        let public_key = dehexify("8d91ae0f4e3cd277cfc46aba26680232b0d5bb4444602cdb23442d62e17f43cdffb1104909e535430c10a6a1ce680a65");
-        let beacon = Beacon {
-            round_number: 397092,
-            randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
-            signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
-            previous_signature: Vec::new(),
-        };
+    fn missing_rounds_finds_gaps() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let beacons: Vec<Beacon> = [9, 3, 4, 6, 8, 12]
+            .into_iter()
+            .map(|round| signer.beacon(round))
+            .collect();
 
-        assert!(matches!(
-            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
-            Ok(_),
-        ));
+        assert_eq!(missing_rounds(&beacons, 3..=9), vec![5, 7]);
+        assert_eq!(missing_rounds(&beacons, 3..=4), Vec::<u64>::new());
+        assert_eq!(missing_rounds(&[], 1..=2), vec![1, 2]);
     }
 
+    #[cfg(feature = "serde")]
     #[test]
-    fn testnet_unchained_beacon_wrong_round_fails() {
-        let public_key = dehexify("8d91ae0f4e3cd277cfc46aba26680232b0d5bb4444602cdb23442d62e17f43cdffb1104909e535430c10a6a1ce680a65");
-        let beacon = Beacon {
-            round_number: 1, // wrong round
-            randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
-            signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
-            previous_signature: Vec::new(),
-        };
+    fn round_deserializes_from_number_or_string() {
+        let beacon = TestSigner::new(SchemeID::PedersenBlsUnchained).beacon(397089);
+        let mut json = serde_json::to_value(&beacon).unwrap();
+        assert_eq!(json["round"], 397089);
+        assert_eq!(
+            serde_json::from_value::<Beacon>(json.clone()).unwrap(),
+            beacon
+        );
 
-        assert_error(
-            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
-            VerificationError::SignatureFailedVerification,
+        json["round"] = "397089".into();
+        assert_eq!(
+            serde_json::from_value::<Beacon>(json.clone()).unwrap(),
+            beacon
         );
+
+        for invalid in [serde_json::json!("397O89"), serde_json::json!(-1)] {
+            json["round"] = invalid;
+            assert!(serde_json::from_value::<Beacon>(json.clone()).is_err());
+        }
     }
 
     #[test]
-    fn testnet_unchained_beacon_randomness_not_matching_signature_fails() {
-        let public_key = dehexify("8d91ae0f4e3cd277cfc46aba26680232b0d5bb4444602cdb23442d62e17f43cdffb1104909e535430c10a6a1ce680a65");
-        let beacon = Beacon {
-            round_number: 397092,
-            // mismatching randomness
-            randomness: dehexify("a731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
-            signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
-            previous_signature: Vec::new(),
-        };
+    fn signature_envelopes_are_unwrapped() {
+        let signature = TestSigner::new(SchemeID::UnchainedOnG1RFC9380)
+            .beacon(1)
+            .signature;
+        let length_prefixed = [&48u16.to_be_bytes()[..], &signature].concat();
+        let checksummed = [&signature[..], &Sha256::digest(&signature)[..4]].concat();
+
+        for bytes in [&signature, &length_prefixed, &checksummed] {
+            assert_eq!(unwrap_signature_envelope(bytes), Some(&signature[..]));
+        }
 
-        assert_error(
-            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
-            VerificationError::InvalidRandomness,
-        );
+        let wrong_length = [&96u16.to_be_bytes()[..], &signature].concat();
+        let mut wrong_checksum = checksummed.clone();
+        wrong_checksum[48] ^= 1;
+        for bytes in [&wrong_length, &wrong_checksum, &signature[..47].to_vec()] {
+            assert_eq!(unwrap_signature_envelope(bytes), None);
+        }
     }
 
+    #[cfg(feature = "serde")]
     #[test]
-    fn testnet_unchained_beacon_containing_previous_sig_ignores_previous_sig() {
-        let public_key = dehexify("8d91ae0f4e3cd277cfc46aba26680232b0d5bb4444602cdb23442d62e17f43cdffb1104909e535430c10a6a1ce680a65");
-        let beacon = Beacon {
-            round_number: 397092,
-            randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
-            signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
-            previous_signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
+    fn enveloped_signatures_deserialize() {
+        #[derive(serde::Deserialize)]
+        struct ArchivedBeacon {
+            #[serde(deserialize_with = "crate::verify::enveloped_signature::deserialize")]
+            signature: Vec<u8>,
+        }
+        let parse = |signature: String| {
+            serde_json::from_value::<ArchivedBeacon>(serde_json::json!({ "signature": signature }))
+                .map(|archived| archived.signature)
         };
+        let signature = TestSigner::new(SchemeID::PedersenBlsUnchained)
+            .beacon(1)
+            .signature;
+        let wrapped = [&96u16.to_be_bytes()[..], &signature].concat();
+
+        assert_eq!(parse(hex::encode(&signature)).unwrap(), signature);
+        assert_eq!(
+            parse(format!("0x{}", hex::encode(&wrapped))).unwrap(),
+            signature
+        );
+        assert!(parse(hex::encode([0u8; 99])).is_err());
+    }
 
-        assert!(matches!(
-            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
-            Ok(())
-        ));
+    #[cfg(feature = "serde")]
+    #[test]
+    fn beacon_with_invalid_hex_fails_to_parse() {
+        for invalid in ["abc", "zz", "0x0g"] {
+            for field in ["randomness", "signature", "previous_signature"] {
+                let mut json = serde_json::json!({
+                    "round": 1,
+                    "randomness": "00",
+                    "signature": "00",
+                });
+                json[field] = invalid.into();
+
+                assert!(serde_json::from_value::<Beacon>(json).is_err());
+            }
+        }
     }
 
     #[test]
-    fn testnet_unchained_invalid_public_key_fails() {
-        // valid public key, but for wrong scheme
-        let public_key = dehexify("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31");
-        let beacon = Beacon {
-            round_number: 397092,
-            randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
-            signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
-            previous_signature: Vec::new(),
-        };
+    fn signature_over_explicit_identity_verifies() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let beacon = signer.beacon(1234);
+        let identity = Sha256::digest(&1234u64.to_be_bytes());
 
+        assert_eq!(
+            verify_on_g1_rfc9380(&signer.public_key(), &identity, &beacon.signature),
+            Ok(())
+        );
         assert_error(
-            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
+            verify_on_g1_rfc9380(
+                &signer.public_key(),
+                &Sha256::digest(&1235u64.to_be_bytes()),
+                &beacon.signature,
+            ),
             VerificationError::SignatureFailedVerification,
         );
-    }
-
-    #[test]
-    fn testnet_unchained_beacon_empty_public_key_fails() {
-        let public_key = Vec::new();
-        let beacon = Beacon {
-            round_number: 397092,
-            randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
-            signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
-            previous_signature: Vec::new(),
-        };
 
+        // signatures made with the DST of bls-unchained-on-g1 don't verify under RFC9380's
+        let non_rfc = TestSigner::new(SchemeID::UnchainedOnG1);
         assert_error(
-            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
-            VerificationError::InvalidPublicKey,
+            verify_on_g1_rfc9380(
+                &non_rfc.public_key(),
+                &identity,
+                &non_rfc.beacon(1234).signature,
+            ),
+            VerificationError::SignatureFailedVerification,
         );
     }
 
     #[test]
-    fn testnet_unchained_beacon_infinity_public_key_fails() {
-        let public_key = G2Affine::identity().to_uncompressed();
-        let beacon = Beacon {
-            round_number: 397092,
-            randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
-            signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
-            previous_signature: Vec::new(),
-        };
+    fn pairing_check_gt_is_identity_for_valid_signatures() {
+        let message = Sha256::digest(&7u64.to_be_bytes());
+        for (scheme_id, dst) in [
+            (SchemeID::PedersenBlsUnchained, DST_G2),
+            (SchemeID::UnchainedOnG1RFC9380, DST_G1),
+        ] {
+            let signer = TestSigner::new(scheme_id);
+            let public_key = signer.public_key();
+            let signature = signer.beacon(7).signature;
+
+            assert_eq!(
+                pairing_check_gt(&public_key, &message, &signature, dst),
+                Ok(Gt::identity())
+            );
+            let other = Sha256::digest(&8u64.to_be_bytes());
+            let result = pairing_check_gt(&public_key, &other, &signature, dst).unwrap();
+            assert_ne!(result, Gt::identity());
+        }
 
-        assert_error(
-            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
-            VerificationError::InvalidPublicKey,
+        assert_eq!(
+            pairing_check_gt(&[0; 64], &message, &[0; 48], DST_G1),
+            Err(VerificationError::InvalidPublicKey)
         );
     }
 
-    #[test]
-    fn g1g2_swap_non_rfc_beacon_verifies() {
-        let public_key = dehexify("a0b862a7527fee3a731bcb59280ab6abd62d5c0b6ea03dc4ddf6612fdfc9d01f01c31542541771903475eb1ec6615f8d0df0b8b6dce385811d6dcf8cbefb8759e5e616a3dfd054c928940766d9a5b9db91e3b697e5d70a975181e007f87fca5e");
-        let beacon = Beacon {
-            round_number: 3,
-            randomness: dehexify("a4eb0ed6c4132da066843c3bfdce732ce5013eda86e74c136ab8ccc387b798dd"),
-            signature: dehexify("8176555f90d71aa49ceb37739683749491c2bab15a46094b255289ed25cf8f01cdfb1fe8bd9cd5a19eb09448a3e53186"),
-            previous_signature: Vec::new(),
-        };
-
-        assert!(matches!(
-            verify_beacon(&SchemeID::UnchainedOnG1, &public_key, &beacon),
-            Ok(_)
-        ));
+    /// the beacons of a chained chain from round 1 to `head`, newest first
+    fn chain_desc(signer: &TestSigner, head: u64) -> Vec<Beacon> {
+        let mut beacons = vec![signer.chained_beacon(1, &[0x6e; 32])];
+        for round in 2..=head {
+            let previous = &beacons[beacons.len() - 1].signature;
+            beacons.push(signer.chained_beacon(round, previous));
+        }
+        beacons.reverse();
+        beacons
     }
 
     #[test]
-    fn g1g2_swap_rfc_beacon_verifies() {
-        let public_key = dehexify("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
-        let beacon = Beacon {
-            round_number: 1000,
-            randomness: dehexify("fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"),
-            signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
-            previous_signature: Vec::new(),
-        };
-
-        assert!(matches!(
-            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, &beacon),
-            Ok(_)
-        ));
+    fn chain_back_stops_at_trusted_round() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsChained);
+        let mut beacons = chain_desc(&signer, 10);
+        // beacons older than the trusted round are never looked at
+        beacons[7].randomness[0] ^= 1;
+
+        assert_eq!(
+            verify_chain_back(
+                &SchemeID::PedersenBlsChained,
+                &signer.public_key(),
+                &beacons,
+                7
+            ),
+            Ok(())
+        );
+        assert_error(
+            verify_chain_back(
+                &SchemeID::PedersenBlsChained,
+                &signer.public_key(),
+                &beacons,
+                2,
+            ),
+            VerificationError::InvalidRandomness,
+        );
     }
 
     #[test]
-    fn g1g2_swap_empty_public_key_fails() {
-        let public_key = Vec::new();
-        let beacon = Beacon {
-            round_number: 1000,
-            randomness: dehexify("fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"),
-            signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
-            previous_signature: Vec::new(),
-        };
+    fn chain_back_detects_broken_links() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsChained);
+        let mut beacons = chain_desc(&signer, 10);
+        // a valid beacon for round 8, but not the one round 9 was chained to
+        beacons[2] = signer.chained_beacon(8, &beacons[4].signature);
 
         assert_error(
-            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, &beacon),
-            VerificationError::InvalidPublicKey,
+            verify_chain_back(
+                &SchemeID::PedersenBlsChained,
+                &signer.public_key(),
+                &beacons,
+                5,
+            ),
+            VerificationError::BrokenChainLink { round: 9 },
+        );
+
+        let unchained = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let with_gap = [unchained.beacon(9), unchained.beacon(7)];
+        assert_error(
+            verify_chain_back(
+                &SchemeID::UnchainedOnG1RFC9380,
+                &unchained.public_key(),
+                &with_gap,
+                7,
+            ),
+            VerificationError::BrokenChainLink { round: 9 },
         );
     }
 
     #[test]
-    fn g1g2_swap_infinity_public_key_fails() {
-        let public_key = G2Affine::identity().to_uncompressed();
-        let beacon = Beacon {
-            round_number: 1000,
-            randomness: dehexify("fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"),
-            signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
-            previous_signature: Vec::new(),
-        };
+    fn chain_back_must_reach_trusted_round() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsChained);
+        let beacons = chain_desc(&signer, 10);
 
         assert_error(
-            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, &beacon),
-            VerificationError::InvalidPublicKey,
+            verify_chain_back(
+                &SchemeID::PedersenBlsChained,
+                &signer.public_key(),
+                &beacons[..3],
+                5,
+            ),
+            VerificationError::TrustedRoundNotReached { trusted_round: 5 },
+        );
+        assert_error(
+            verify_chain_back(&SchemeID::PedersenBlsChained, &signer.public_key(), &[], 5),
+            VerificationError::TrustedRoundNotReached { trusted_round: 5 },
         );
     }
 
     #[test]
-    fn g1g2_swap_wrong_round_fails() {
-        let public_key = dehexify("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
-        let beacon = Beacon {
-            round_number: 1,
-            randomness: dehexify("fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"),
-            signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
-            previous_signature: Vec::new(),
-        };
+    fn round_message_points_verify_like_rounds() {
+        for scheme_id in [
+            SchemeID::PedersenBlsChained,
+            SchemeID::PedersenBlsUnchained,
+            SchemeID::UnchainedOnG1,
+            SchemeID::UnchainedOnG1RFC9380,
+        ] {
+            let signer = TestSigner::new(scheme_id.clone());
+            let public_key = signer.public_key();
+            let beacon = signer.beacon(42);
+            let previous_signature = Some(beacon.previous_signature.as_slice())
+                .filter(|_| scheme_id == SchemeID::PedersenBlsChained);
+
+            let point = round_message_point(&scheme_id, 42, previous_signature).unwrap();
+            let from_point = match scheme_id {
+                SchemeID::PedersenBlsChained | SchemeID::PedersenBlsUnchained => {
+                    verify_on_g2_with_message_point(&public_key, &point, &beacon.signature)
+                }
+                SchemeID::UnchainedOnG1 | SchemeID::UnchainedOnG1RFC9380 => {
+                    verify_on_g1_with_message_point(&public_key, &point, &beacon.signature)
+                }
+            };
+            assert_eq!(from_point, verify_beacon(&scheme_id, &public_key, &beacon));
+            assert_eq!(from_point, Ok(()));
 
-        assert_error(
-            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, &beacon),
-            VerificationError::SignatureFailedVerification,
+            let other_round = round_message_point(&scheme_id, 43, previous_signature).unwrap();
+            assert_ne!(point, other_round);
+        }
+    }
+
+    #[test]
+    fn round_message_point_checks_previous_signature() {
+        assert_eq!(
+            round_message_point(&SchemeID::PedersenBlsChained, 42, None),
+            Err(VerificationError::ChainedBeaconNeedsPreviousSignature)
+        );
+        assert_eq!(
+            round_message_point(&SchemeID::UnchainedOnG1RFC9380, 42, Some(&[1; 48])),
+            Err(VerificationError::UnexpectedPreviousSignature)
+        );
+        assert_eq!(
+            round_message_point(&SchemeID::UnchainedOnG1RFC9380, 0, None),
+            Err(VerificationError::InvalidRound)
         );
     }
 
     #[test]
-    fn g1g2_swap_invalid_randomness_fails() {
-        let public_key = dehexify("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
-        let beacon = Beacon {
-            round_number: 1000,
-            // incorrect hash for the signature
-            randomness: dehexify("aa290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"),
-            signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
-            previous_signature: Vec::new(),
-        };
+    fn signed_round_is_found_in_window() {
+        let signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let scheme_id = SchemeID::UnchainedOnG1RFC9380;
+        let mut beacon = signer.beacon(1003);
+        // the beacon claims a different round to the one that was signed
+        beacon.round_number = 1000;
+
+        assert_eq!(
+            find_signed_round(
+                &scheme_id,
+                &signer.public_key(),
+                &beacon.signature,
+                995..=1005
+            ),
+            Some(1003)
+        );
+        assert_eq!(
+            find_signed_round(
+                &scheme_id,
+                &signer.public_key(),
+                &beacon.signature,
+                995..=1002
+            ),
+            None
+        );
 
-        assert_error(
-            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, &beacon),
-            VerificationError::InvalidRandomness,
+        let chained = TestSigner::new(SchemeID::PedersenBlsChained);
+        assert_eq!(
+            find_signed_round(
+                &SchemeID::PedersenBlsChained,
+                &chained.public_key(),
+                &chained.beacon(3).signature,
+                1..=5
+            ),
+            None
         );
     }
 
     #[test]
-    fn g1g2_swap_invalid_signature_fails() {
-        let public_key = dehexify("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
-        let beacon = Beacon {
-            round_number: 1000,
-            // this is not a valid signature
-            signature: dehexify("a44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
-            // but the hash matches it
-            randomness: dehexify("5993706587c56d4e7079d175bfa5d52295694896e68c691b93765242096c9fa7"),
-            previous_signature: Vec::new(),
-        };
+    fn audit_records_the_dst_and_groups_of_each_scheme() {
+        let expected = [
+            (SchemeID::PedersenBlsChained, DST_G2, Group::G2, Group::G1),
+            (SchemeID::PedersenBlsUnchained, DST_G2, Group::G2, Group::G1),
+            (SchemeID::UnchainedOnG1, DST_G2, Group::G1, Group::G2),
+            (SchemeID::UnchainedOnG1RFC9380, DST_G1, Group::G1, Group::G2),
+        ];
+        for (scheme_id, dst, signature_group, key_group) in expected {
+            let signer = TestSigner::new(scheme_id.clone());
+            let beacon = signer.beacon(3);
+
+            assert_eq!(
+                verify_beacon_audited(&scheme_id, &signer.public_key(), &beacon),
+                Ok(VerifyAudit {
+                    dst: dst.to_string(),
+                    signature_group,
+                    key_group,
+                })
+            );
+
+            let mut tampered = beacon;
+            tampered.round_number = 4;
+            assert!(verify_beacon_audited(&scheme_id, &signer.public_key(), &tampered).is_err());
+        }
+    }
 
-        assert_error(
-            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, &beacon),
-            VerificationError::SignatureFailedVerification,
-        );
+    #[test]
+    fn empty_message_is_rejected_before_hashing() {
+        let g1_signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let g1_key = g1_signer.public_key();
+        let g2_signature = g1_signer.sign(b"message");
+        let g2_signer = TestSigner::new(SchemeID::UnchainedOnG1RFC9380);
+        let g2_key = g2_signer.public_key();
+        let g1_signature = g2_signer.sign(b"message");
+
+        let results = [
+            verify_on_g2(&g1_key, &[], &g2_signature, DST_G2),
+            verify_on_g2_with_expander::<DefaultExpander>(&g1_key, &[], &g2_signature, DST_G2),
+            verify_on_g1(&g2_key, &[], &g1_signature, DST_G1),
+            verify_on_g1_rfc9380(&g2_key, &[], &g1_signature),
+            verify_on_g1_with_expander::<DefaultExpander>(&g2_key, &[], &g1_signature, DST_G1),
+            verify_message(&SchemeID::PedersenBlsChained, &g1_key, &[], &g2_signature),
+            verify_message(&SchemeID::PedersenBlsUnchained, &g1_key, &[], &g2_signature),
+            verify_message(&SchemeID::UnchainedOnG1, &g2_key, &[], &g1_signature),
+            verify_message(&SchemeID::UnchainedOnG1RFC9380, &g2_key, &[], &g1_signature),
+            pairing_check_gt(&g1_key, &[], &g2_signature, DST_G2).map(|_| ()),
+            pairing_check_gt(&g2_key, &[], &g1_signature, DST_G1).map(|_| ()),
+        ];
+        for result in results {
+            assert_eq!(result, Err(VerificationError::EmptyMessage));
+        }
     }
 
     fn dehexify(s: &str) -> Vec<u8> {
@@ -0,0 +1,89 @@
+//! # watch
+//!
+//! sharing the beacons a client's watch loop receives, so many consumers can follow the chain
+//! head without each of them polling the relay
+//!
+
+use crate::lock_unpoisoned;
+use crate::verify::Beacon;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+/// a handle to the latest round seen by a watch loop, which also broadcasts each new beacon to
+/// its subscribers. Clones share the same round and subscribers, so the handle can be passed
+/// to other threads.
+#[derive(Debug, Clone, Default)]
+pub struct LatestRound {
+    round: Arc<AtomicU64>,
+    subscribers: Arc<Mutex<Vec<Sender<Beacon>>>>,
+}
+
+/// create a handle that hasn't seen any rounds yet
+pub fn new_latest_round() -> LatestRound {
+    LatestRound::default()
+}
+
+impl LatestRound {
+    /// the highest round seen so far, or 0 if no beacons have been seen yet
+    pub fn get(&self) -> u64 {
+        self.round.load(Ordering::Acquire)
+    }
+
+    /// receive every beacon for a new round seen from now on, in the order they're seen.
+    /// Dropping the receiver unsubscribes.
+    pub fn subscribe(&self) -> Receiver<Beacon> {
+        let (sender, receiver) = channel();
+        lock_unpoisoned(&self.subscribers).push(sender);
+        receiver
+    }
+
+    /// record `beacon` and send it to every subscriber, unless a beacon for the same or a later
+    /// round has already been seen, e.g. by another watch loop sharing the handle.
+    /// Only verified beacons may be published, which is why only the client does so.
+    pub(crate) fn publish(&self, beacon: &Beacon) {
+        // hold the lock while updating the round, so beacons are sent in the order of their
+        // rounds even when several threads publish at once
+        let mut subscribers = lock_unpoisoned(&self.subscribers);
+        if self.round.fetch_max(beacon.round_number, Ordering::AcqRel) >= beacon.round_number {
+            return;
+        }
+        subscribers.retain(|subscriber| subscriber.send(beacon.clone()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lock_unpoisoned;
+    use crate::test_util::TestSigner;
+    use crate::verify::SchemeID;
+    use crate::watch::new_latest_round;
+
+    #[test]
+    fn stale_rounds_are_not_broadcast() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let latest_round = new_latest_round();
+        let receiver = latest_round.subscribe();
+
+        for round in [1, 3, 2, 3, 4] {
+            latest_round.publish(&signer.beacon(round));
+        }
+
+        let rounds: Vec<u64> = receiver.try_iter().map(|b| b.round_number).collect();
+        assert_eq!(rounds, vec![1, 3, 4]);
+        assert_eq!(latest_round.get(), 4);
+    }
+
+    #[test]
+    fn dropped_subscribers_are_removed() {
+        let signer = TestSigner::new(SchemeID::PedersenBlsUnchained);
+        let latest_round = new_latest_round();
+        drop(latest_round.subscribe());
+        let receiver = latest_round.subscribe();
+
+        latest_round.publish(&signer.beacon(1));
+
+        assert_eq!(lock_unpoisoned(&latest_round.subscribers).len(), 1);
+        assert_eq!(receiver.recv(), Ok(signer.beacon(1)));
+    }
+}